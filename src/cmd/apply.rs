@@ -2,13 +2,25 @@ static USAGE: &str = r#"
 Apply a series of transformation functions to a given CSV column. This can be used to
 perform typical data-wrangling tasks and/or to harmonize some values, etc.
 
-It has six subcommands:
- * operations - 36 string, format, currency, regex & NLP operators.
+It has eighteen subcommands:
+ * operations - 50 string, format, currency, date, regex & NLP operators.
  * emptyreplace - replace empty cells with <--replacement> string.
  * datefmt - Formats a recognized date column to a specified format using <--formatstr>.
  * dynfmt - Dynamically constructs a new column from other columns using the <--formatstr> template.
  * geocode - geocodes a WGS84 location against a static copy of the Geonames cities database.
  * calcconv - parse and evaluate math expressions, with support for units and conversions.
+ * rank - rank each row within its --groupby group by the value of a numeric column.
+ * pct_rank - the percentile rank of each row within its --groupby group, by the value of a numeric column.
+ * winsorize - cap the values of a numeric column at its --lower & --upper percentiles.
+ * useragent - parse a user-agent string into its browser, os and/or device components.
+ * calendar - the ISO week, day of the week, quarter and/or weekend flag of a date column.
+ * kv_parse - parse a column of key=value pairs into a new column for each key.
+ * math - add, subtract, multiply or divide a numeric column by another column or a constant.
+ * sortkey - build a fixed-width key from several columns that sorts them correctly as text.
+ * nearest - the label & distance of the nearest location of a --reference file.
+ * completeness - the fraction of non-empty fields of the selected columns of each row.
+ * cum_distinct - the running count of the distinct values of a column within its --groupby group.
+ * partition_valid - split the rows into valid & invalid ones, by whether a column matches a regex.
 
 OPERATIONS
 Multiple operations can be applied, with the comma-delimited operation series
@@ -24,13 +36,17 @@ number of transformed columns with the --rename option is the same. e.g.:
 
 $ qsv apply operations trim,upper col1,col2,col3 -r newcol1,newcol2,newcol3 file.csv
 
-It has 36 supported operations:
+It has 50 supported operations:
 
   * len: Return string length
   * lower: Transform to lowercase
   * upper: Transform to uppercase
   * squeeze: Compress consecutive whitespaces
   * squeeze0: Remove whitespace
+  * digits_only: Remove all the characters that are not ASCII digits (0-9), for comparable
+      phone numbers & identifiers (e.g. "(555) 123-4567" => 5551234567). With --keep-plus,
+      a leading + is kept (e.g. "+1 555-123-4567" => +15551234567).
+      Values without any digits are blanked out.
   * trim: Trim (drop whitespace left & right of the string)
   * ltrim: Left trim whitespace
   * rtrim: Right trim whitespace
@@ -47,6 +63,19 @@ It has 36 supported operations:
   * regex_replace: Replace all regex matches in --comparand w/ --replacement.
   * titlecase - capitalizes English text using Daring Fireball titlecase style
       https://daringfireball.net/2008/05/title_case
+  * slug: Generate a URL/identifier-friendly slug - lowercased, transliterated to ASCII,
+      with runs of non-alphanumeric characters replaced by --sep (e.g. "Hello World!" => hello-world)
+  * tokencount: Count the tokens of a text, with the --mode tokenization rule:
+        words - the number of words. Words are found using the Unicode word boundaries of
+          Unicode Standard Annex #29 (not just whitespace), only keeping the words with
+          alphanumeric characters - so punctuation and whitespace are not words, while
+          "can't", "3.14" and "e-mail" are one, one and two words respectively.
+          Scripts without spaces between words (e.g. Chinese) are counted per character.
+        unique - the number of distinct words (as above), ignoring case.
+        sentences - the number of sentences, using the Unicode sentence boundaries of
+          Unicode Standard Annex #29 (e.g. after . ! or ? followed by whitespace),
+          ignoring whitespace-only sentences.
+        chars - the number of user-perceived characters (Unicode extended grapheme clusters).
   * censor: profanity filter. Add additional comma-delimited profanities with --comparand.
   * censor_check: check if profanity is detected (boolean).
       Add additional comma-delimited profanities with -comparand.
@@ -67,6 +96,88 @@ It has 36 supported operations:
       with --comparand. Automatically rounds values to two decimal places. Specify
       "euro" formatting (e.g. 1.000,00 instead of 1,000.00 ) by setting --formatstr to "euro".
       Specify conversion rate by setting --replacement to a number.
+  * tz_convert: Convert a datetime from the --from timezone to the --to timezone, formatting
+      it with --formatstr (default: RFC 3339, e.g. 2023-03-03T14:30:00-05:00).
+      Datetimes without a timezone are assumed to be in the --from timezone.
+      Values that cannot be parsed as datetimes are left unchanged.
+  * parse_timestamp: Parse a timestamp in the --timestamp-from format, and return it as an
+      ISO 8601 UTC datetime (e.g. 2023-03-03T14:30:00Z, with fractional seconds if any).
+      The accepted formats are:
+        epoch_s - seconds since the Unix epoch, possibly with a fraction (e.g. 1677853800.25)
+        epoch_ms - milliseconds since the Unix epoch, possibly with a fraction
+          (e.g. 1677853800250 or 1677853800250.5)
+        rfc2822 - e.g. Fri, 03 Mar 2023 09:30:00 -0500
+        http - the HTTP date formats - IMF-fixdate (e.g. Fri, 03 Mar 2023 14:30:00 GMT) and the
+          obsolete RFC 850 (e.g. Friday, 03-Mar-23 14:30:00 GMT) & asctime
+          (e.g. Fri Mar  3 14:30:00 2023) formats
+        auto - any of the above. Epoch numbers of 100,000,000,000 or more (in March 1973 as
+          milliseconds, but in the year 5138 as seconds) are milliseconds, and seconds otherwise.
+      Values that cannot be parsed are blanked out.
+  * fiscal: Compute the fiscal period of a date, with the fiscal year starting on the
+      --fy-start month. Fiscal years are named after the calendar year they end in
+      (e.g. with --fy-start 10, 2023-10-15 is in FY2024-Q1).
+      The label format can be set with --fiscal-format, using the {fy} (fiscal year),
+      {q} (fiscal quarter) & {p} (fiscal period/month, 1 to 12) placeholders
+      (default: FY{fy}-Q{q}).
+  * checkdigit: Check if the check digit of an identifier is valid (boolean), using the
+      --algo algorithm. Spaces and hyphens are ignored. With --blank-invalid, valid values
+      are returned as-is and invalid values are blanked instead. The number of invalid
+      values is sent to <stderr>. The available algorithms are:
+        luhn - credit card and other identification numbers. Doubling every second digit
+          from the right (subtracting 9 if the result is over 9), the sum of the digits
+          must be a multiple of 10.
+        isbn - ISBN-10 or ISBN-13. For ISBN-10, the sum of each digit multiplied by its
+          weight (10 down to 1, with a trailing X as 10) must be a multiple of 11.
+          For ISBN-13, the sum of the digits, alternately multiplied by 1 and 3, must be
+          a multiple of 10.
+        iban - International Bank Account Numbers (ISO 13616). The first four characters
+          are moved to the end, letters are replaced by numbers (A=10 ... Z=35), and the
+          resulting number modulo 97 must be 1.
+  * coord: Parse a latitude/longitude coordinate in decimal degrees or in degrees, minutes
+      & seconds (DMS) notation, and return it in decimal degrees (rounded to 6 decimal places).
+      The accepted formats are:
+        decimal degrees - e.g. 40.446111, -79.982222, 40.446111N, W 79.982222
+        degrees & decimal minutes - e.g. 40°26.767'N, 40 26.767 N
+        degrees, minutes & seconds - e.g. 40°26′46″N, 40°26'46"N, 40:26:46N, N 40 26 46.5
+      Degrees, minutes & seconds can be separated by °, º, ′, ', ’, ″, ", ”, : or whitespace.
+      The N/S/E/W hemisphere can be set before or after the coordinate, with S & W
+      returning negative values. Otherwise, a leading minus sign marks a negative value.
+      Latitudes (N/S) must be within 90 degrees and other coordinates within 180 degrees,
+      and minutes & seconds must be less than 60. Invalid coordinates are blanked out.
+  * iso_code: Normalize a country or currency (set with --type) to its ISO code, using a bundled
+      copy of the ISO 3166-1 & ISO 4217 code lists. Codes, names & common variants are recognized,
+      ignoring case, accents, whitespace & punctuation - e.g. US, USA, 840, "United States" &
+      "U.S.A." are all US, and $, "US$", usd & "US Dollar" are all USD. Countries are returned
+      in the --iso-output format - alpha2 (default), alpha3, numeric or name, and currencies as
+      alpha3 (default), numeric or name. Unmappable values are blanked out, and their number
+      is sent to <stderr>.
+  * tokenize: Pseudonymize a value with a format-preserving token, keyed by the --token-key
+      secret. Only the characters of the --token-format are replaced - digits (0-9),
+      alpha (a-z, keeping the case of each letter) or alnum (0-9, A-Z & a-z) - with everything
+      else left in place, so tokens have the same length & format as their values
+      (e.g. the SSN 123-45-6789 becomes another 000-00-0000 formatted value).
+      The same value is always mapped to the same token with the same key, and different values
+      to different tokens, so tokenized columns can still be joined on.
+      Tokens are encrypted, not hashed - they can be reversed with the detokenize operation,
+      but only with the same --token-key & --token-format.
+  * detokenize: Reverse the tokenize operation, with the same --token-key & --token-format.
+  * ab_bucket: Assign the value to one of the --buckets (e.g. control:50,treatment:50), with a
+      probability proportional to the bucket's weight, returning the bucket name.
+      The assignment is deterministic - the same value (and --salt) is always assigned to the
+      same bucket, across runs & platforms - by hashing the --salt & the value with SHA-256.
+      Empty values are not assigned.
+  * domain: Extract the domain of an email address (e.g. jane@mail.example.com) or of a URL
+      (e.g. https://www.example.com:8080/path) or host name, lowercased. With --domain-part
+      registrable, only the registrable domain is returned instead, using a bundled copy of the
+      ICANN section of the Public Suffix List (https://publicsuffix.org) - i.e. the public suffix
+      & the label before it, so a.b.co.uk becomes b.co.uk and mail.example.com becomes example.com.
+      Empty & invalid values (including IP addresses and public suffixes) are blanked out.
+  * mac: Parse a MAC address (EUI-48) in colon (aa:bb:cc:dd:ee:ff), dash (AA-BB-CC-DD-EE-FF),
+      dot (aabb.ccdd.eeff) or bare (aabbccddeeff) notation, and return it in the --mac-format
+      notation - colon (default), dash, dot or bare. The hex digits are lowercased, unless the
+      notation is set in uppercase (e.g. DASH => AA-BB-CC-DD-EE-FF).
+      Invalid values are returned as-is, unless --blank-invalid is set. The number of invalid
+      values is sent to <stderr>.
   * copy: Mark a column for copying
   * simdl: Damerau-Levenshtein similarity to --comparand
   * simdln: Normalized Damerau-Levenshtein similarity to --comparand (between 0.0 & 1.0)
@@ -132,6 +243,63 @@ and save it to a new column named dln_roxbury_score.
 
   $ qsv apply operations lower,simdln neighborhood --comparand roxbury -c dln_roxbury_score boston311.csv
 
+Convert the UTC timestamps of the created_at column to New York time, into a new column:
+
+  $ qsv apply operations tz_convert created_at --from UTC --to America/New_York -c created_at_ny file.csv
+
+Convert the naive Tokyo timestamps of the logged column to UTC, formatted as '%Y-%m-%d %H:%M':
+
+  $ qsv apply operations tz_convert logged --from Asia/Tokyo --to UTC -f '%Y-%m-%d %H:%M' file.csv
+
+Convert the epoch or HTTP date timestamps of the logged column to ISO 8601, into a new column:
+
+  $ qsv apply operations parse_timestamp logged -c logged_iso file.csv
+
+Compute the fiscal quarter of the invoice_date column, for a fiscal year starting in July,
+into a new column named fiscal_quarter.
+
+  $ qsv apply operations fiscal invoice_date --fy-start 7 -c fiscal_quarter file.csv
+
+Generate a slug from the title column, using underscores as separator, into a new column named key.
+
+  $ qsv apply operations slug title --sep _ -c key file.csv
+
+Check the card_number column against its Luhn check digit, into a new column named valid_card.
+
+  $ qsv apply operations checkdigit card_number --algo luhn -c valid_card file.csv
+
+Blank the invalid IBANs of the iban column.
+
+  $ qsv apply operations checkdigit iban --algo iban --blank-invalid file.csv
+
+Convert the DMS coordinates of the latitude column to decimal degrees, into a new column named lat.
+
+  $ qsv apply operations coord latitude -c lat file.csv
+
+Count the words of the comments column, into a new column named comment_words.
+
+  $ qsv apply operations tokencount comments -c comment_words file.csv
+
+Count the sentences of the description and notes columns.
+
+  $ qsv apply operations tokencount description,notes --mode sentences file.csv
+
+Pseudonymize the ssn column with a --token-key, keeping its format:
+
+  $ qsv apply operations tokenize ssn --token-key 'my secret' file.csv
+
+Reverse it, with the same --token-key:
+
+  $ qsv apply operations detokenize ssn --token-key 'my secret' tokenized.csv
+
+Assign each user_id to the control or treatment group of an experiment, 50/50, into a new column:
+
+  $ qsv apply operations ab_bucket user_id --buckets control:50,treatment:50 --salt exp42 -c group file.csv
+
+Extract the registrable domain of the email column into a new column, to group by company:
+
+  $ qsv apply operations domain email --domain-part registrable -c company_domain file.csv
+
 You can also use this subcommand command to make a copy of a column:
 
   $ qsv apply operations copy col_to_copy -c col_copy file.csv
@@ -259,6 +427,203 @@ $ qsv apply calcconv --formatstr '10% of abs(sin(pi)) horsepower to watts' -c wa
 And use very large numbers:
 $ qsv apply calcconv --formatstr '{col1} Billion Trillion * {col2} quadrillion vigintillion' -c num_atoms file.csv 
 
+RANK
+Ranks each row by the numeric value of <column> into a new column. If --groupby is set,
+rows are ranked within their group, otherwise, they're ranked across the whole file.
+Rows are buffered per group, so the input need not be sorted by the --groupby columns,
+but the whole file is loaded into memory. The rows are written in their original order.
+Empty and non-numeric values are not ranked and are given an empty rank.
+
+Examples:
+Rank players by score within each team, with the highest score ranked first:
+$ qsv apply rank score --groupby team --order desc -c score_rank file.csv
+
+Dense rank players by score, so there are no gaps in the ranking after ties:
+$ qsv apply rank score --method dense -c score_rank file.csv
+
+PCT_RANK
+Computes the empirical percentile rank (0 to 100) of the numeric value of <column> of each
+row into a new column - the percentage of the values of its --groupby group (or of the
+whole file if --groupby is not set) that are below it, with the --ties method used for
+the values equal to it:
+  mean - half of the equal values are counted as below (e.g. 1,2,2,4 => 12.5,50,50,87.5)
+  weak - all the equal values are counted as below, i.e. the percentage of values that
+    are less than or equal to it (1,2,2,4 => 25,75,75,100)
+  strict - none of the equal values are counted as below, i.e. the percentage of values
+    that are strictly less than it (1,2,2,4 => 0,25,25,75)
+Like RANK, the whole file is loaded into memory, the rows are written in their original order,
+and empty and non-numeric values are given an empty percentile rank. Percentile ranks are
+rounded to 4 decimal places.
+
+Examples:
+Get the percentile rank of each student's score within their class:
+$ qsv apply pct_rank score --groupby class -c score_pct_rank file.csv
+
+Get the percentage of incomes that are less than or equal to each income:
+$ qsv apply pct_rank income --ties weak -c income_pct_rank file.csv
+
+WINSORIZE
+Caps the extreme numeric values of <column> at its --lower & --upper percentiles - values
+below the --lower percentile are replaced by the --lower percentile, and values above the
+--upper percentile by the --upper percentile. The percentiles are computed within each
+--groupby group (or across the whole file if --groupby is not set), interpolating linearly
+between the two closest values. Like RANK, the whole file is loaded into memory and the rows
+are written in their original order. Empty and non-numeric values are left as-is, as are
+the values within the percentiles. The number of clipped values is sent to <stderr>.
+
+Examples:
+Cap the response times at their 1st & 99th percentiles:
+$ qsv apply winsorize response_time file.csv
+
+Cap the prices of each category at their 5th & 95th percentiles, in a new column:
+$ qsv apply winsorize price --groupby category --lower 5 --upper 95 -c capped_price file.csv
+
+USERAGENT
+Parses a user-agent column and returns the --part components of the agent - its browser
+name, os and/or device category (pc, smartphone, mobilephone, crawler, appliance or misc).
+Agents that cannot be recognized are returned as "unknown".
+
+To keep the parsing cost down with log files that repeat the same agents, the parsed
+results are cached, keyed on the raw user-agent string.
+
+If more than one --part is requested, --new-column should list the names of the new
+columns, one for each part.
+
+Examples:
+Get the browser name of the user_agent column into a new column named browser:
+$ qsv apply useragent user_agent --new-column browser weblog.csv
+
+Get the os and the device category of the user_agent column into new columns:
+$ qsv apply useragent user_agent --part os,device -c ua_os,ua_device weblog.csv
+
+CALENDAR
+Parses a date column, like DATEFMT, and returns the --part calendar attributes of the date:
+  isoweek - the ISO 8601 week number, from 1 to 53. ISO weeks start on Monday, and the first
+            week of a year is the one with its first Thursday (e.g. 2021-01-01 is in week 53
+            of 2020).
+  yearweek - the ISO 8601 year & week number (e.g. 2020-W53), which sorts chronologically.
+  dow - the English name of the day of the week (e.g. Monday).
+  quarter - the calendar quarter, from 1 to 4.
+  weekend - true if the date is a Saturday or a Sunday, false otherwise.
+Empty values and values that cannot be parsed as dates return empty attributes.
+
+If more than one --part is requested, --new-column should list the names of the new
+columns, one for each part.
+
+Examples:
+Get the ISO week of the order_date column into a new column named week:
+$ qsv apply calendar order_date --new-column week orders.csv
+
+Get the year-week, the day of the week & the weekend flag of the order_date column:
+$ qsv apply calendar order_date --part yearweek,dow,weekend -c yw,dow,weekend orders.csv
+
+KV_PARSE
+Parses a column of key/value pairs (e.g. "k1=v1;k2=v2"), separated by --pair-sep, with the
+key & value of each pair separated by --kv-sep, into a new column for each key. The new columns
+are named after the keys and appended to the end of the row. Keys & values are trimmed, and
+if a key is repeated in a cell, its last value is used. Rows without a key get an empty value.
+
+If --keys is set, only those keys are returned, in that order, and the rows are streamed.
+Otherwise, the whole file is loaded into memory to discover the keys of all the rows first,
+and the columns are in the order the keys are first seen.
+
+Examples:
+Parse the attributes column of a log file, with pairs like "user=bob;status=200":
+$ qsv apply kv_parse attributes weblog.csv
+
+Only get the user and status keys of "user: bob, status: 200" pairs, streaming the rows:
+$ qsv apply kv_parse attributes --pair-sep , --kv-sep : --keys user,status weblog.csv
+
+MATH
+Computes <column> --op --operand into a new column, where --op is one of + - * or /
+and --operand is either the name of another column or a numeric constant. If a column
+is named like a number, the column is used. Empty and non-numeric values return an
+empty result, and division by zero returns the --div-zero value (default: empty).
+Results are rounded to 10 decimal places, removing floating point artifacts
+(e.g. 0.1 + 0.2 is 0.3, not 0.30000000000000004).
+
+Examples:
+Compute the margin of each product:
+$ qsv apply math price --op - --operand cost -c margin products.csv
+
+Add 8.5% of sales tax to each price, with the tax-inclusive price in a new column:
+$ qsv apply math price --op '*' --operand 1.085 -c price_with_tax products.csv
+
+Compute the conversion rate, with an "N/A" rate for pages without visits:
+$ qsv apply math signups --op / --operand visits --div-zero N/A -c conversion pages.csv
+
+SORTKEY
+Builds a composite sort key of the <column> columns into a new column, so sorting the rows
+by the key alone (e.g. with the sort command, without --numeric) sorts them by all the
+columns in turn. Each column is formatted to a fixed width using the comma-separated --spec
+entries, one for each column in the <column> order:
+  s<width> - text, left-aligned and padded with spaces to <width> characters, or truncated
+             to <width> characters if longer.
+  n<width> - a number, right-aligned and zero-padded to <width> digits. Decimals can be kept
+             with n<width>.<decimals> (e.g. n8.2 formats 12.5 as 00001250). Negative numbers
+             are encoded so that they sort before the positive numbers, in numeric order.
+             Numbers too large to fit in <width> digits are capped to the largest value.
+Each column of the key starts with a marker character that positions the null values (empty
+cells, and non-numeric cells of number columns) before all the other values of the column,
+or after them with --nulls last.
+
+Examples:
+Sort by last name, then by age, with a single key:
+$ qsv apply sortkey last_name,age --spec s20,n3 -c key people.csv | qsv sort -s key
+
+NEAREST
+Finds the nearest location of the --reference CSV file to the location of <column> - a
+WGS84 latitude, longitude coordinate (e.g. "40.7128, -74.006" or "(40.7128, -74.006)", like
+GEOCODE) - and returns its label into the --new-column, with the great-circle (Haversine)
+distance to it in kilometers, rounded to 3 decimal places, in a <new-column>_distance column.
+The first three columns of the --reference file are the latitude, longitude & label of its
+locations, and it must have a header row.
+
+Every row is compared against every reference location, so the cost grows with the number
+of rows times the number of reference locations. That is fast for small reference sets (e.g.
+a few thousand stores or sites), but consider a spatial database for large reference sets.
+Invalid and empty locations get an empty label & distance.
+
+Examples:
+Tag each delivery with its nearest store and the distance to it:
+$ qsv apply nearest location --reference stores.csv -c nearest_store deliveries.csv
+
+COMPLETENESS
+Computes the completeness score of each row into a new column - the fraction of its
+<column> fields that are not empty, from 0 (all empty) to 1 (all filled in), rounded to
+4 decimal places. With --percent, the score is a percentage from 0 to 100 instead, rounded
+to 2 decimal places. Fields that only have whitespace are not empty, unless
+--whitespace-empty is set.
+
+Examples:
+Score the completeness of all the columns of each row:
+$ qsv apply completeness 1- -c completeness contacts.csv
+
+Score the completeness of the contact details, as a percentage, ignoring blank fields:
+$ qsv apply completeness email,phone,address --percent --whitespace-empty -c pct contacts.csv
+
+CUM_DISTINCT
+Computes the running count of the distinct values of <column> seen so far into a new column,
+within each --groupby group (or across the whole file if --groupby is not set). Empty values
+are not counted. The rows are streamed, so the rows of each group must be contiguous (e.g.
+sorted by the --groupby columns), and in the order to count them in. It is an error if a
+group reappears after the rows of another group.
+
+Examples:
+Get the cumulative unique users per day, with the rows sorted by day:
+$ qsv apply cum_distinct user_id --groupby day -c cum_users visits.csv
+
+PARTITION_VALID
+Splits the rows by whether their <column> fields match the --pattern regex, in one pass.
+The valid rows - whose <column> fields all match - are written to the output, and the
+invalid rows to the --invalid-output file, both with the header row. The regex matches
+anywhere in a field, so anchor it with ^ and $ to validate whole fields. The number of
+invalid rows is sent to <stderr>.
+
+Examples:
+Keep the rows with a valid US zip code, writing the others to bad_zips.csv:
+$ qsv apply partition_valid zip --pattern '^\d{5}(-\d{4})?$' --invalid-output bad_zips.csv file.csv
+
 For more extensive examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_apply.rs.
 
 Usage:
@@ -268,9 +633,22 @@ qsv apply datefmt [--formatstr=<string>] [options] <column> [<input>]
 qsv apply dynfmt --formatstr=<string> [options] --new-column=<name> [<input>]
 qsv apply geocode [--formatstr=<string>] [options] <column> [<input>]
 qsv apply calcconv --formatstr=<string> [options] --new-column=<name> [<input>]
+qsv apply rank [options] --new-column=<name> <column> [<input>]
+qsv apply pct_rank [options] --new-column=<name> <column> [<input>]
+qsv apply winsorize [options] <column> [<input>]
+qsv apply useragent [--part=<parts>] [options] <column> [<input>]
+qsv apply calendar [--part=<parts>] [options] <column> [<input>]
+qsv apply kv_parse [--keys=<keys>] [options] <column> [<input>]
+qsv apply math --op=<op> --operand=<operand> [options] --new-column=<name> <column> [<input>]
+qsv apply sortkey --spec=<spec> [options] --new-column=<name> <column> [<input>]
+qsv apply nearest --reference=<file> [options] --new-column=<name> <column> [<input>]
+qsv apply completeness [options] --new-column=<name> <column> [<input>]
+qsv apply cum_distinct [options] --new-column=<name> <column> [<input>]
+qsv apply partition_valid --pattern=<regex> --invalid-output=<file> [options] <column> [<input>]
 qsv apply --help
 
-The <column> argument can be a list of columns for the operations and datefmt subcommands.
+The <column> argument can be a list of columns for the operations, datefmt, completeness
+& partition_valid subcommands.
 See 'qsv select --help' for the format details.
 
 apply options:
@@ -281,7 +659,7 @@ apply options:
     -R, --replacement=<string>  The string to use for the replace & emptyreplace operations.
                                 Also used with numtocurrency operation to conversion rate.
     --prefer-dmy                Prefer to parse dates in dmy format. Otherwise, use mdy format.
-                                Only used with the DATEFMT subcommand.
+                                Only used with the DATEFMT & CALENDAR subcommands.
     --keep-zero-time            If a formatted date ends with "T00:00:00+00:00", keep the time
                                 instead of removing it. Only used with the DATEFMT subcommand.
     -f, --formatstr=<string>    This option is used by several subcommands:
@@ -300,6 +678,11 @@ apply options:
                                   round
                                     The number of decimal places to round to (default: 3)
 
+                                  tz_convert
+                                    The date format to use for the converted datetime. For formats, see
+                                    https://docs.rs/chrono/latest/chrono/format/strftime/
+                                    Default to ISO 8601 / RFC 3339 date & time format.
+
                                 DATEFMT: The date format to use. For formats, see
                                   https://docs.rs/chrono/latest/chrono/format/strftime/
                                   Default to ISO 8601 / RFC 3339 date & time format.
@@ -318,6 +701,88 @@ apply options:
                                   - 'county-country' | 'admin2-country' - Kings County, US
                                   - 'county-state-country' | 'admin2-admin1-country' - Kings County, New York US
                                   - 'country' - US
+    --from <tz>                 The IANA timezone name (e.g. America/New_York) to convert from
+                                with the tz_convert operation. [default: UTC]
+    --to <tz>                   The IANA timezone name to convert to with the tz_convert
+                                operation. [default: UTC]
+    --timestamp-from <format>   The timestamp format of the parse_timestamp operation - auto,
+                                epoch_s, epoch_ms, rfc2822 or http. [default: auto]
+    --fy-start <month>          The month (1-12) the fiscal year starts on, for the
+                                fiscal operation. [default: 1]
+    --fiscal-format <template>  The fiscal period label template of the fiscal operation,
+                                using the {fy}, {q} & {p} placeholders.
+                                [default: FY{fy}-Q{q}]
+    --sep <string>              The separator to use with the slug operation. [default: -]
+    --mode <mode>               The tokenization rule of the tokencount operation -
+                                words, unique, sentences or chars. [default: words]
+    --domain-part <part>        The part of the domain returned by the domain operation -
+                                full or registrable. [default: full]
+    --algo <algo>               The check digit algorithm to use with the checkdigit
+                                operation - luhn, isbn or iban. [default: luhn]
+    --blank-invalid             With the checkdigit operation, return valid values as-is
+                                and blank invalid values, instead of returning a boolean.
+                                With the mac operation, blank invalid values instead of
+                                returning them as-is.
+    --mac-format <notation>     The MAC address notation returned by the mac operation -
+                                colon, dash, dot or bare, in lowercase or UPPERCASE.
+                                [default: colon]
+    --keep-plus                 With the digits_only operation, keep the leading + of values.
+    --type <type>               The kind of code of the iso_code operation - country or
+                                currency. [default: country]
+    --iso-output <format>       The code format returned by the iso_code operation -
+                                alpha2, alpha3, numeric or name for countries (default: alpha2),
+                                and alpha3, numeric or name for currencies (default: alpha3).
+    --token-key <key>           The secret key of the tokenize & detokenize operations.
+    --token-format <format>     The characters replaced by the tokenize & detokenize operations -
+                                digits, alpha or alnum. [default: digits]
+    --buckets <buckets>         The comma-separated buckets of the ab_bucket operation, as
+                                name:weight pairs, with positive whole number weights.
+    --salt <salt>               The salt of the ab_bucket operation, so the assignments of
+                                different experiments are independent.
+    -g, --groupby <cols>        The columns to group by for the RANK, PCT_RANK, WINSORIZE
+                                & CUM_DISTINCT subcommands.
+                                See 'qsv select --help' for the format details.
+    --order <arg>               The sort order of the RANK subcommand - asc or desc.
+                                [default: asc]
+    --method <arg>              The ranking method of the RANK subcommand:
+                                  standard - ties get the same rank, leaving gaps (1,2,2,4)
+                                  dense - ties get the same rank, without gaps (1,2,2,3)
+                                [default: standard]
+    --ties <method>             The tie handling method of the PCT_RANK subcommand -
+                                mean, weak or strict. [default: mean]
+    --lower <pct>               The lower percentile (0-100) of the WINSORIZE subcommand.
+                                [default: 1]
+    --upper <pct>               The upper percentile (0-100) of the WINSORIZE subcommand.
+                                [default: 99]
+    --part <parts>              The comma-separated components to return with the USERAGENT
+                                subcommand - browser (default), os and/or device, or with the
+                                CALENDAR subcommand - isoweek (default), yearweek, dow, quarter
+                                and/or weekend.
+    --pair-sep <sep>            The separator between the key/value pairs of the KV_PARSE
+                                subcommand. [default: ;]
+    --kv-sep <sep>              The separator between the key & the value of a pair of the
+                                KV_PARSE subcommand. [default: =]
+    --keys <keys>               The comma-separated keys to return with the KV_PARSE subcommand.
+                                If not set, all the keys found in the column are returned.
+    --op <op>                   The arithmetic operator of the MATH subcommand - +, -, * or /.
+    --operand <operand>         The column name or numeric constant that is the right-hand side
+                                of the MATH subcommand's --op.
+    --div-zero <value>          The value returned by the MATH subcommand when dividing by zero.
+                                If not set, an empty value is returned.
+    --spec <spec>               The comma-separated type & width of each column of the SORTKEY
+                                subcommand - s<width> for text, n<width>[.<decimals>] for numbers.
+    --nulls <pos>               Whether the null values are sorted first or last by the SORTKEY
+                                subcommand. [default: first]
+    --reference <file>          The CSV file of the labeled locations of the NEAREST subcommand,
+                                with latitude, longitude & label columns.
+    --percent                   Return the score of the COMPLETENESS subcommand as a percentage
+                                (0-100) instead of a fraction (0-1).
+    --whitespace-empty          Consider the fields that only have whitespace as empty with the
+                                COMPLETENESS subcommand.
+    --pattern <regex>           The regex the <column> fields of the valid rows match with the
+                                PARTITION_VALID subcommand.
+    --invalid-output <file>     The file the invalid rows are written to by the PARTITION_VALID
+                                subcommand.
     -j, --jobs <arg>            The number of jobs to run in parallel.
                                 When not set, the number of jobs is set to the number of CPUs detected.
     -b, --batch <size>          The number of rows per batch to load into memory, before running in parallel.
@@ -333,23 +798,33 @@ Common options:
     -p, --progressbar           Show progress bars. Not valid for stdin.
 "#;
 
-use std::str::FromStr;
+use std::{
+    io,
+    str::FromStr,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
 
+use ahash::{AHashMap, AHashSet};
 use cached::proc_macro::cached;
 use censor::{Censor, Sex, Zealous};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
 use cpc::{eval, units::Unit};
 use data_encoding::BASE64;
+use deunicode::deunicode;
 use dynfmt::Format;
 use eudex::Hash;
+use hmac::{Hmac, Mac};
 use indicatif::{ProgressBar, ProgressDrawTarget};
 use log::debug;
 use once_cell::sync::OnceCell;
 use qsv_currency::Currency;
-use qsv_dateparser::parse_with_preference;
+use qsv_dateparser::{parse_with_preference, parse_with_timezone};
 use rayon::prelude::*;
 use regex::Regex;
 use reverse_geocoder::{Locations, ReverseGeocoder};
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
 use strsim::{
     damerau_levenshtein, hamming, jaro_winkler, normalized_damerau_levenshtein, osa_distance,
     sorensen_dice,
@@ -357,6 +832,7 @@ use strsim::{
 use strum_macros::EnumString;
 use thousands::{policies, Separable, SeparatorPolicy};
 use titlecase::titlecase;
+use unicode_segmentation::UnicodeSegmentation;
 use vader_sentiment::SentimentIntensityAnalyzer;
 use whatlang::detect;
 
@@ -364,7 +840,7 @@ use crate::{
     clitypes::CliError,
     config::{Config, Delimiter},
     regex_once_cell,
-    select::SelectColumns,
+    select::{SelectColumns, Selection},
     util, CliResult,
 };
 
@@ -372,22 +848,32 @@ use crate::{
 #[strum(ascii_case_insensitive)]
 #[allow(non_camel_case_types)]
 enum Operations {
+    Ab_Bucket,
     Censor,
     Censor_Check,
     Censor_Count,
+    Checkdigit,
+    Coord,
     Copy,
     Currencytonum,
     Decode,
+    Detokenize,
+    Digits_Only,
+    Domain,
     Encode,
     Escape,
     Eudex,
+    Fiscal,
+    Iso_Code,
     Len,
     Lower,
     Ltrim,
+    Mac,
     Mltrim,
     Mrtrim,
     Mtrim,
     Numtocurrency,
+    Parse_Timestamp,
     Regex_Replace,
     Replace,
     Round,
@@ -399,41 +885,95 @@ enum Operations {
     Simjw,
     Simod,
     Simsd,
+    Slug,
     Squeeze,
     Squeeze0,
     Strip_Prefix,
     Strip_Suffix,
     Thousands,
     Titlecase,
+    Tokencount,
+    Tokenize,
     Trim,
+    Tz_Convert,
     Upper,
     Whatlang,
 }
 
 #[derive(Deserialize)]
 struct Args {
-    arg_column:          SelectColumns,
-    cmd_operations:      bool,
-    arg_operations:      String,
-    cmd_datefmt:         bool,
-    cmd_dynfmt:          bool,
-    cmd_emptyreplace:    bool,
-    cmd_geocode:         bool,
-    cmd_calcconv:        bool,
-    arg_input:           Option<String>,
-    flag_rename:         Option<String>,
-    flag_comparand:      String,
-    flag_replacement:    String,
-    flag_prefer_dmy:     bool,
-    flag_keep_zero_time: bool,
-    flag_formatstr:      String,
-    flag_batch:          u32,
-    flag_jobs:           Option<usize>,
-    flag_new_column:     Option<String>,
-    flag_output:         Option<String>,
-    flag_no_headers:     bool,
-    flag_delimiter:      Option<Delimiter>,
-    flag_progressbar:    bool,
+    arg_column:            SelectColumns,
+    cmd_operations:        bool,
+    arg_operations:        String,
+    cmd_datefmt:           bool,
+    cmd_dynfmt:            bool,
+    cmd_emptyreplace:      bool,
+    cmd_geocode:           bool,
+    cmd_calcconv:          bool,
+    cmd_rank:              bool,
+    cmd_pct_rank:          bool,
+    cmd_winsorize:         bool,
+    cmd_useragent:         bool,
+    cmd_calendar:          bool,
+    cmd_kv_parse:          bool,
+    cmd_math:              bool,
+    cmd_sortkey:           bool,
+    cmd_nearest:           bool,
+    cmd_completeness:      bool,
+    cmd_cum_distinct:      bool,
+    cmd_partition_valid:   bool,
+    arg_input:             Option<String>,
+    flag_rename:           Option<String>,
+    flag_comparand:        String,
+    flag_replacement:      String,
+    flag_prefer_dmy:       bool,
+    flag_keep_zero_time:   bool,
+    flag_formatstr:        String,
+    flag_from:             String,
+    flag_to:               String,
+    flag_timestamp_from:   String,
+    flag_fy_start:         u32,
+    flag_fiscal_format:    String,
+    flag_sep:              String,
+    flag_mode:             String,
+    flag_domain_part:      String,
+    flag_algo:             String,
+    flag_blank_invalid:    bool,
+    flag_mac_format:       String,
+    flag_keep_plus:        bool,
+    flag_type:             String,
+    flag_iso_output:       Option<String>,
+    flag_token_key:        Option<String>,
+    flag_token_format:     String,
+    flag_buckets:          Option<String>,
+    flag_salt:             Option<String>,
+    flag_groupby:          Option<SelectColumns>,
+    flag_order:            String,
+    flag_method:           String,
+    flag_ties:             String,
+    flag_lower:            f64,
+    flag_upper:            f64,
+    flag_part:             String,
+    flag_pair_sep:         String,
+    flag_kv_sep:           String,
+    flag_keys:             Option<String>,
+    flag_op:               Option<String>,
+    flag_operand:          Option<String>,
+    flag_div_zero:         Option<String>,
+    flag_spec:             Option<String>,
+    flag_nulls:            String,
+    flag_reference:        Option<String>,
+    flag_percent:          bool,
+    flag_whitespace_empty: bool,
+    flag_pattern:          Option<String>,
+    flag_invalid_output:   Option<String>,
+    flag_batch:            u32,
+    flag_jobs:             Option<usize>,
+    flag_new_column:       Option<String>,
+    flag_output:           Option<String>,
+    flag_no_headers:       bool,
+    flag_delimiter:        Option<Delimiter>,
+    flag_progressbar:      bool,
 }
 
 static CENSOR: OnceCell<Censor> = OnceCell::new();
@@ -445,6 +985,29 @@ static SENTIMENT_ANALYZER: OnceCell<SentimentIntensityAnalyzer> = OnceCell::new(
 static THOUSANDS_POLICY: OnceCell<SeparatorPolicy> = OnceCell::new();
 static ROUND_PLACES: OnceCell<u32> = OnceCell::new();
 static WHATLANG_CONFIDENCE_THRESHOLD: OnceCell<f64> = OnceCell::new();
+static TZ_CONVERT_FROM: OnceCell<Tz> = OnceCell::new();
+static TZ_CONVERT_TO: OnceCell<Tz> = OnceCell::new();
+static FISCAL_FY_START: OnceCell<u32> = OnceCell::new();
+static FISCAL_FORMAT: OnceCell<String> = OnceCell::new();
+static TIMESTAMP_FROM: OnceCell<TimestampFormat> = OnceCell::new();
+static SLUG_SEPARATOR: OnceCell<String> = OnceCell::new();
+static PREFER_DMY: AtomicBool = AtomicBool::new(false);
+static TOKENCOUNT_MODE: OnceCell<TokenCountMode> = OnceCell::new();
+static DOMAIN_MODE: OnceCell<DomainMode> = OnceCell::new();
+static PUBLIC_SUFFIXES: OnceCell<PublicSuffixes> = OnceCell::new();
+static CHECKDIGIT_ALGO: OnceCell<CheckDigitAlgo> = OnceCell::new();
+static CHECKDIGIT_BLANK_INVALID: AtomicBool = AtomicBool::new(false);
+static CHECKDIGIT_INVALID_COUNT: AtomicUsize = AtomicUsize::new(0);
+static DIGITS_ONLY_KEEP_PLUS: AtomicBool = AtomicBool::new(false);
+static MAC_FORMAT: OnceCell<(MacFormat, bool)> = OnceCell::new();
+static MAC_BLANK_INVALID: AtomicBool = AtomicBool::new(false);
+static MAC_INVALID_COUNT: AtomicUsize = AtomicUsize::new(0);
+static ISO_CODE_LOOKUP: OnceCell<AHashMap<String, String>> = OnceCell::new();
+static ISO_CODE_UNMAPPED_COUNT: AtomicUsize = AtomicUsize::new(0);
+static TOKEN_KEY: OnceCell<Vec<u8>> = OnceCell::new();
+static TOKEN_FORMAT: OnceCell<TokenFormat> = OnceCell::new();
+static AB_BUCKETS: OnceCell<(Vec<(String, u64)>, u64)> = OnceCell::new();
+static AB_SALT: OnceCell<String> = OnceCell::new();
 
 // default confidence threshold for whatlang language detection - 90% confidence
 const DEFAULT_THRESHOLD: f64 = 0.9;
@@ -452,6 +1015,44 @@ const DEFAULT_THRESHOLD: f64 = 0.9;
 // default number of decimal places to round to
 const DEFAULT_ROUND_PLACES: u32 = 3;
 
+// number of decimal places of the results of the MATH subcommand
+const MATH_DECIMAL_PLACES: u32 = 10;
+
+// number of decimal places of the percentiles of the WINSORIZE subcommand
+const WINSORIZE_DECIMAL_PLACES: u32 = 10;
+
+// number of decimal places of the percentile ranks of the PCT_RANK subcommand
+const PCT_RANK_DECIMAL_PLACES: u32 = 4;
+
+// epoch timestamps of --timestamp-from auto with this magnitude or more are in milliseconds
+const EPOCH_MS_THRESHOLD: f64 = 100_000_000_000.0;
+
+// number of Feistel rounds of the tokenize & detokenize operations
+const TOKEN_FEISTEL_ROUNDS: u8 = 10;
+
+// the mean radius of the Earth, for the Haversine distances of the NEAREST subcommand
+const EARTH_RADIUS_KM: f64 = 6_371.008_8;
+
+// number of decimal places of the distances of the NEAREST subcommand - 1 meter precision
+const NEAREST_DECIMAL_PLACES: u32 = 3;
+
+// number of decimal places of the scores of the COMPLETENESS subcommand,
+// as fractions & as percentages (with --percent)
+const COMPLETENESS_DECIMAL_PLACES: u32 = 4;
+const COMPLETENESS_PERCENT_DECIMAL_PLACES: u32 = 2;
+
+// number of decimal places of the coord operation - about 0.1 meter precision
+const COORD_DECIMAL_PLACES: u32 = 6;
+
+// the bundled ISO 3166-1 country & ISO 4217 currency code lists of the iso_code operation,
+// with the codes & names first, followed by the |-separated aliases
+static ISO_3166_1_CSV: &str = include_str!("../../resources/iso/iso3166-1.csv");
+static ISO_4217_CSV: &str = include_str!("../../resources/iso/iso4217.csv");
+
+// the bundled ICANN section of the Public Suffix List of the domain operation
+static PUBLIC_SUFFIX_LIST: &str =
+    include_str!("../../resources/publicsuffix/public_suffix_list.dat");
+
 // for thousands operator
 static INDIANCOMMA_POLICY: SeparatorPolicy = SeparatorPolicy {
     separator: ",",
@@ -467,6 +1068,118 @@ enum ApplySubCmd {
     Geocode,
     EmptyReplace,
     CalcConv,
+    UserAgent,
+    Calendar,
+    Math,
+    SortKey,
+    Nearest,
+    Completeness,
+}
+
+// the format of a column of the SORTKEY subcommand's key
+enum SortKeySpec {
+    Text { width: usize },
+    Number { width: usize, decimals: u32 },
+}
+
+// the arithmetic operators of the MATH subcommand
+#[derive(Clone, Copy)]
+enum MathOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+// the right-hand side of the MATH subcommand's operator
+enum MathOperand {
+    Column(usize),
+    Constant(f64),
+}
+
+// the components of a user-agent string returned by the USERAGENT subcommand,
+// with their index in the array returned by useragent_cached
+#[derive(Clone, Copy)]
+enum UserAgentPart {
+    Browser = 0,
+    Os      = 1,
+    Device  = 2,
+}
+
+// the calendar attributes of a date returned by the CALENDAR subcommand
+#[derive(Clone, Copy)]
+enum CalendarPart {
+    IsoWeek,
+    YearWeek,
+    Dow,
+    Quarter,
+    Weekend,
+}
+
+// how the PCT_RANK subcommand counts the values equal to a value
+#[derive(Clone, Copy)]
+enum PctRankTies {
+    Mean,
+    Weak,
+    Strict,
+}
+
+// the tokenization rules of the tokencount operation
+enum TokenCountMode {
+    Words,
+    Unique,
+    Sentences,
+    Chars,
+}
+
+// the parts of the domain returned by the domain operation
+#[derive(Clone, Copy, PartialEq)]
+enum DomainMode {
+    Full,
+    Registrable,
+}
+
+// the rules of the Public Suffix List. Wildcard rules (*.ck) are stored
+// without their "*." prefix, and exception rules (!www.ck) without their "!"
+#[derive(Default)]
+struct PublicSuffixes {
+    rules:      AHashSet<&'static str>,
+    wildcards:  AHashSet<&'static str>,
+    exceptions: AHashSet<&'static str>,
+}
+
+// the input formats of the parse_timestamp operation
+#[derive(Clone, Copy)]
+enum TimestampFormat {
+    Auto,
+    EpochS,
+    EpochMs,
+    Rfc2822,
+    Http,
+}
+
+// the output alphabets of the tokenize & detokenize operations
+#[derive(Clone, Copy)]
+enum TokenFormat {
+    Digits,
+    Alpha,
+    Alnum,
+}
+
+// the check digit algorithms of the checkdigit operation
+enum CheckDigitAlgo {
+    Luhn,
+    Isbn,
+    Iban,
+}
+
+// the MAC address notations of the mac operation
+#[derive(Clone, Copy)]
+enum MacFormat {
+    Colon,
+    Dash,
+    Dot,
+    Bare,
 }
 
 #[inline]
@@ -509,12 +1222,193 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         }
     }
 
+    let mut ua_parts: Vec<UserAgentPart> = Vec::new();
+    if args.cmd_useragent {
+        let flag_part = if args.flag_part.is_empty() {
+            "browser"
+        } else {
+            &args.flag_part
+        };
+        for part in flag_part.split(',') {
+            ua_parts.push(match part.trim().to_lowercase().as_str() {
+                "browser" => UserAgentPart::Browser,
+                "os" => UserAgentPart::Os,
+                "device" => UserAgentPart::Device,
+                _ => return fail_clierror!("Invalid --part: {part}"),
+            });
+        }
+    }
+    let mut calendar_parts: Vec<CalendarPart> = Vec::new();
+    if args.cmd_calendar {
+        let flag_part = if args.flag_part.is_empty() {
+            "isoweek"
+        } else {
+            &args.flag_part
+        };
+        for part in flag_part.split(',') {
+            calendar_parts.push(match part.trim().to_lowercase().as_str() {
+                "isoweek" => CalendarPart::IsoWeek,
+                "yearweek" => CalendarPart::YearWeek,
+                "dow" => CalendarPart::Dow,
+                "quarter" => CalendarPart::Quarter,
+                "weekend" => CalendarPart::Weekend,
+                _ => return fail_clierror!("Invalid --part: {part}"),
+            });
+        }
+    }
+    let part_count = ua_parts.len().max(calendar_parts.len());
+
+    if args.cmd_kv_parse {
+        if args.flag_pair_sep.is_empty() || args.flag_kv_sep.is_empty() {
+            return fail!("--pair-sep and --kv-sep cannot be empty.");
+        }
+        let keys = match args.flag_keys {
+            Some(ref keys) => Some(util::ColumnNameParser::new(keys).parse()?),
+            None => {
+                // we're loading the entire file into memory, we need to check avail mem
+                if let Some(path) = rconfig.path.clone() {
+                    util::mem_file_check(&path, false)?;
+                }
+                None
+            }
+        };
+        return kv_parse_rows(
+            &mut rdr,
+            &mut wtr,
+            (!rconfig.no_headers).then_some(headers),
+            column_index,
+            keys,
+            &args.flag_pair_sep,
+            &args.flag_kv_sep,
+        );
+    }
+
+    if args.cmd_partition_valid {
+        let flag_pattern = args.flag_pattern.unwrap_or_default();
+        let pattern = match Regex::new(&flag_pattern) {
+            Ok(pattern) => pattern,
+            Err(e) => return fail_clierror!("Invalid --pattern {flag_pattern}: {e}"),
+        };
+        let mut invalid_wtr = Config::new(&args.flag_invalid_output).writer()?;
+        if !rconfig.no_headers {
+            wtr.write_record(&headers)?;
+            invalid_wtr.write_record(&headers)?;
+        }
+        let invalid_count =
+            partition_valid_rows(&mut rdr, &mut wtr, &mut invalid_wtr, &sel, &pattern)?;
+        eprintln!("{invalid_count} invalid rows.");
+        return Ok(());
+    }
+
     if !rconfig.no_headers {
         if let Some(new_column) = &args.flag_new_column {
-            headers.push_field(new_column);
+            if args.cmd_useragent || args.cmd_calendar {
+                let new_col_names = util::ColumnNameParser::new(new_column).parse()?;
+                if new_col_names.len() != part_count {
+                    return fail!("Number of new columns does not match the number of --part.");
+                }
+                for new_col_name in &new_col_names {
+                    headers.push_field(new_col_name);
+                }
+            } else if args.cmd_nearest {
+                headers.push_field(new_column);
+                headers.push_field(&format!("{new_column}_distance"));
+            } else {
+                headers.push_field(new_column);
+            }
         }
         wtr.write_record(&headers)?;
     }
+    if part_count > 1 && args.flag_new_column.is_none() {
+        return fail!("--new-column (-c) is required when returning more than one --part.");
+    }
+
+    if args.cmd_rank {
+        // we're loading the entire file into memory, we need to check avail mem
+        if let Some(path) = rconfig.path.clone() {
+            util::mem_file_check(&path, false)?;
+        }
+        let groupby_sel = match args.flag_groupby {
+            Some(ref groupby) => Some(groupby.selection(rdr.byte_headers()?, !rconfig.no_headers)?),
+            None => None,
+        };
+        let descending = match args.flag_order.to_lowercase().as_str() {
+            "asc" => false,
+            "desc" => true,
+            _ => return fail_clierror!("Invalid --order: {}", args.flag_order),
+        };
+        let dense = match args.flag_method.to_lowercase().as_str() {
+            "standard" => false,
+            "dense" => true,
+            _ => return fail_clierror!("Invalid --method: {}", args.flag_method),
+        };
+        return rank_rows(
+            &mut rdr,
+            &mut wtr,
+            column_index,
+            groupby_sel.as_ref(),
+            descending,
+            dense,
+        );
+    }
+
+    if args.cmd_pct_rank {
+        // we're loading the entire file into memory, we need to check avail mem
+        if let Some(path) = rconfig.path.clone() {
+            util::mem_file_check(&path, false)?;
+        }
+        let groupby_sel = match args.flag_groupby {
+            Some(ref groupby) => Some(groupby.selection(rdr.byte_headers()?, !rconfig.no_headers)?),
+            None => None,
+        };
+        let ties = match args.flag_ties.to_lowercase().as_str() {
+            "mean" => PctRankTies::Mean,
+            "weak" => PctRankTies::Weak,
+            "strict" => PctRankTies::Strict,
+            _ => return fail_clierror!("Invalid --ties: {}", args.flag_ties),
+        };
+        return pct_rank_rows(&mut rdr, &mut wtr, column_index, groupby_sel.as_ref(), ties);
+    }
+
+    if args.cmd_cum_distinct {
+        let groupby_sel = match args.flag_groupby {
+            Some(ref groupby) => Some(groupby.selection(rdr.byte_headers()?, !rconfig.no_headers)?),
+            None => None,
+        };
+        return cum_distinct_rows(&mut rdr, &mut wtr, column_index, groupby_sel.as_ref());
+    }
+
+    if args.cmd_winsorize {
+        if !(0.0..=100.0).contains(&args.flag_lower)
+            || !(0.0..=100.0).contains(&args.flag_upper)
+            || args.flag_lower >= args.flag_upper
+        {
+            return fail_clierror!(
+                "--lower ({}) & --upper ({}) must be percentiles from 0 to 100, with --lower less \
+                 than --upper.",
+                args.flag_lower,
+                args.flag_upper
+            );
+        }
+        // we're loading the entire file into memory, we need to check avail mem
+        if let Some(path) = rconfig.path.clone() {
+            util::mem_file_check(&path, false)?;
+        }
+        let groupby_sel = match args.flag_groupby {
+            Some(ref groupby) => Some(groupby.selection(rdr.byte_headers()?, !rconfig.no_headers)?),
+            None => None,
+        };
+        let clipped_count = winsorize_rows(
+            &mut rdr,
+            &mut wtr,
+            column_index,
+            groupby_sel.as_ref(),
+            (args.flag_lower, args.flag_upper),
+            args.flag_new_column.is_some(),
+        )?;
+        eprintln!("{clipped_count} values clipped.");
+        return Ok(());
+    }
 
     // for dynfmt, safe_headers are the "safe" version of colnames - alphanumeric only,
     // all other chars replaced with underscore
@@ -546,16 +1440,83 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         debug!("dynfmt_fields: {dynfmt_fields:?}  dynfmt_template: {dynfmt_template}");
     }
 
+    let mut math_op = MathOp::Add;
+    let mut math_operand = MathOperand::Constant(0.0);
+    if args.cmd_math {
+        let op = args.flag_op.as_deref().unwrap_or_default();
+        math_op = match op.trim() {
+            "+" => MathOp::Add,
+            "-" => MathOp::Subtract,
+            "*" => MathOp::Multiply,
+            "/" => MathOp::Divide,
+            _ => return fail_clierror!("Invalid --op: {op}. Valid operators are + - * or /"),
+        };
+        let operand = args.flag_operand.as_deref().unwrap_or_default();
+        let operand_index = if rconfig.no_headers {
+            None
+        } else {
+            rdr.byte_headers()?
+                .iter()
+                .position(|header| header == operand.as_bytes())
+        };
+        math_operand = if let Some(operand_index) = operand_index {
+            MathOperand::Column(operand_index)
+        } else if let Ok(constant) = operand.trim().parse::<f64>() {
+            MathOperand::Constant(constant)
+        } else {
+            return fail_clierror!("--operand {operand} is neither a column nor a number.");
+        };
+    }
+    let math_div_zero = args.flag_div_zero.clone().unwrap_or_default();
+
+    let mut sortkey_specs: Vec<SortKeySpec> = Vec::new();
+    let mut sortkey_nulls_last = false;
+    if args.cmd_sortkey {
+        sortkey_specs = parse_sortkey_specs(args.flag_spec.as_deref().unwrap_or_default())?;
+        if sortkey_specs.len() != sel.len() {
+            return fail!("Number of --spec entries does not match the number of columns.");
+        }
+        sortkey_nulls_last = match args.flag_nulls.to_lowercase().as_str() {
+            "first" => false,
+            "last" => true,
+            _ => return fail_clierror!("Invalid --nulls: {}", args.flag_nulls),
+        };
+    }
+
+    let nearest_locations = if args.cmd_nearest {
+        read_nearest_locations(args.flag_reference.as_deref().unwrap_or_default())?
+    } else {
+        vec![]
+    };
+
     let mut ops_vec: Vec<Operations> = Vec::new();
 
     let apply_cmd = if args.cmd_operations {
-        match validate_operations(
-            &args.arg_operations.split(',').collect(),
-            &args.flag_comparand,
-            &args.flag_replacement,
-            &args.flag_new_column,
-            &args.flag_formatstr,
-        ) {
+        let options = OperationOptions {
+            comparand:      &args.flag_comparand,
+            replacement:    &args.flag_replacement,
+            new_column:     &args.flag_new_column,
+            formatstr:      &args.flag_formatstr,
+            from:           &args.flag_from,
+            to:             &args.flag_to,
+            timestamp_from: &args.flag_timestamp_from,
+            fy_start:       args.flag_fy_start,
+            fiscal_format:  &args.flag_fiscal_format,
+            sep:            &args.flag_sep,
+            mode:           &args.flag_mode,
+            domain_part:    &args.flag_domain_part,
+            algo:           &args.flag_algo,
+            blank_invalid:  args.flag_blank_invalid,
+            mac_format:     &args.flag_mac_format,
+            keep_plus:      args.flag_keep_plus,
+            iso_type:       &args.flag_type,
+            iso_output:     &args.flag_iso_output,
+            token_key:      &args.flag_token_key,
+            token_format:   &args.flag_token_format,
+            buckets:        &args.flag_buckets,
+            salt:           &args.flag_salt,
+        };
+        match validate_operations(&args.arg_operations.split(',').collect(), &options) {
             Ok(operations_vec) => ops_vec = operations_vec,
             Err(e) => return Err(e),
         }
@@ -570,6 +1531,18 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         ApplySubCmd::EmptyReplace
     } else if args.cmd_calcconv {
         ApplySubCmd::CalcConv
+    } else if args.cmd_useragent {
+        ApplySubCmd::UserAgent
+    } else if args.cmd_calendar {
+        ApplySubCmd::Calendar
+    } else if args.cmd_math {
+        ApplySubCmd::Math
+    } else if args.cmd_sortkey {
+        ApplySubCmd::SortKey
+    } else if args.cmd_nearest {
+        ApplySubCmd::Nearest
+    } else if args.cmd_completeness {
+        ApplySubCmd::Completeness
     } else {
         return fail!("Unknown apply subcommand.");
     };
@@ -586,6 +1559,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     }
 
     let prefer_dmy = args.flag_prefer_dmy || rconfig.get_dmy_preference();
+    PREFER_DMY.store(prefer_dmy, Ordering::Relaxed);
 
     // amortize memory allocation by reusing record
     #[allow(unused_assignments)]
@@ -644,6 +1618,39 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                             record = replace_column_value(&record, column_index, &cell);
                         }
                     }
+                    ApplySubCmd::UserAgent => {
+                        let agent_parts = useragent_cached(&record[column_index]);
+                        if args.flag_new_column.is_some() {
+                            for part in &ua_parts {
+                                record.push_field(&agent_parts[*part as usize]);
+                            }
+                        } else {
+                            record = replace_column_value(
+                                &record,
+                                column_index,
+                                &agent_parts[ua_parts[0] as usize],
+                            );
+                        }
+                    }
+                    ApplySubCmd::Calendar => {
+                        let date = parse_with_preference(&record[column_index], prefer_dmy)
+                            .ok()
+                            .map(|datetime| datetime.naive_utc().date());
+                        let mut values = calendar_parts.iter().map(|part| {
+                            date.map_or_else(String::new, |date| calendar_part(date, *part))
+                        });
+                        if args.flag_new_column.is_some() {
+                            for value in values {
+                                record.push_field(&value);
+                            }
+                        } else {
+                            record = replace_column_value(
+                                &record,
+                                column_index,
+                                &values.next().unwrap_or_default(),
+                            );
+                        }
+                    }
                     ApplySubCmd::Operations => {
                         let mut cell = String::new();
                         for col_index in sel.iter() {
@@ -759,6 +1766,67 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                             record = replace_column_value(&record, column_index, &result);
                         }
                     }
+                    ApplySubCmd::Math => {
+                        let right = match math_operand {
+                            MathOperand::Column(operand_index) => {
+                                record[operand_index].trim().parse::<f64>().ok()
+                            }
+                            MathOperand::Constant(constant) => Some(constant),
+                        };
+                        let result = match (record[column_index].trim().parse::<f64>(), right) {
+                            (Ok(left), Some(right)) => {
+                                math_result(left, right, math_op, &math_div_zero)
+                            }
+                            _ => String::new(),
+                        };
+                        record.push_field(&result);
+                    }
+                    ApplySubCmd::SortKey => {
+                        let mut key = String::new();
+                        for (col_index, spec) in sel.iter().zip(&sortkey_specs) {
+                            push_sortkey_column(
+                                &mut key,
+                                &record[*col_index],
+                                spec,
+                                sortkey_nulls_last,
+                            );
+                        }
+                        record.push_field(&key);
+                    }
+                    ApplySubCmd::Nearest => {
+                        match parse_location(&record[column_index])
+                            .and_then(|location| nearest_location(location, &nearest_locations))
+                        {
+                            Some((label, distance)) => {
+                                record.push_field(label);
+                                record
+                                    .push_field(&util::round_num(distance, NEAREST_DECIMAL_PLACES));
+                            }
+                            None => {
+                                record.push_field("");
+                                record.push_field("");
+                            }
+                        }
+                    }
+                    ApplySubCmd::Completeness => {
+                        let filled = sel
+                            .iter()
+                            .filter(|col_index| {
+                                let field = &record[**col_index];
+                                if args.flag_whitespace_empty {
+                                    !field.trim().is_empty()
+                                } else {
+                                    !field.is_empty()
+                                }
+                            })
+                            .count();
+                        let score = filled as f64 / sel.len() as f64;
+                        record.push_field(&if args.flag_percent {
+                            util::round_num(score * 100.0, COMPLETENESS_PERCENT_DECIMAL_PLACES)
+                        } else {
+                            util::round_num(score, COMPLETENESS_DECIMAL_PLACES)
+                        });
+                    }
                 }
 
                 record
@@ -780,70 +1848,629 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     if show_progress {
         if args.cmd_geocode {
             util::update_cache_info!(progress, SEARCH_CACHED);
+        } else if args.cmd_useragent {
+            util::update_cache_info!(progress, USERAGENT_CACHED);
         }
         util::finish_progress(&progress);
     }
+    if CHECKDIGIT_ALGO.get().is_some() {
+        eprintln!(
+            "{} invalid {} check digits.",
+            CHECKDIGIT_INVALID_COUNT.load(Ordering::Relaxed),
+            args.flag_algo.to_lowercase()
+        );
+    }
+    if MAC_FORMAT.get().is_some() {
+        eprintln!(
+            "{} invalid MAC addresses.",
+            MAC_INVALID_COUNT.load(Ordering::Relaxed)
+        );
+    }
+    if ISO_CODE_LOOKUP.get().is_some() {
+        eprintln!(
+            "{} unmappable ISO {} codes.",
+            ISO_CODE_UNMAPPED_COUNT.load(Ordering::Relaxed),
+            args.flag_type.to_lowercase()
+        );
+    }
     Ok(wtr.flush()?)
 }
 
-// validate apply operations for required options
-// and prepare operations enum vec
-fn validate_operations(
-    operations: &Vec<&str>,
-    flag_comparand: &str,
-    flag_replacement: &str,
-    flag_new_column: &Option<String>,
-    flag_formatstr: &str,
-) -> Result<Vec<Operations>, CliError> {
-    let mut censor_invokes = 0_u8;
-    let mut copy_invokes = 0_u8;
-    let mut eudex_invokes = 0_u8;
-    let mut regex_replace_invokes = 0_u8;
-    let mut replace_invokes = 0_u8;
-    let mut sentiment_invokes = 0_u8;
-    let mut sim_invokes = 0_u8;
-    let mut strip_invokes = 0_u8;
-    let mut whatlang_invokes = 0_u8;
+// parses a "lat, long" or "(lat, long)" WGS84 location of the GEOCODE & NEAREST subcommands
+fn parse_location(cell: &str) -> Option<(f64, f64)> {
+    // regex for Location field. Accepts (lat, long) & lat, long
+    let locregex: &'static Regex = regex_once_cell!(
+        r"(?-u)([+-]?[0-9]+\.?[0-9]*|\.[0-9]+),\s*([+-]?[0-9]+\.?[0-9]*|\.[0-9]+)"
+    );
 
-    let mut ops_vec: Vec<Operations> = Vec::with_capacity(operations.len());
+    let loccaps = locregex.captures(cell)?;
+    let lat = loccaps[1].parse::<f64>().unwrap_or_default();
+    let long = loccaps[2].parse::<f64>().unwrap_or_default();
+    if (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&long) {
+        Some((lat, long))
+    } else {
+        None
+    }
+}
 
-    for op in operations {
-        let Ok(operation) = Operations::from_str(op) else {
-            return fail_clierror!("Unknown '{op}' operation");
+// reads the latitude, longitude & label columns of the --reference file of the NEAREST
+// subcommand
+fn read_nearest_locations(reference: &str) -> CliResult<Vec<(f64, f64, String)>> {
+    let mut rdr = Config::new(&Some(reference.to_string())).reader()?;
+    let mut locations = Vec::new();
+    for (row, record) in rdr.records().enumerate() {
+        let record = record?;
+        let (Some(lat), Some(long), Some(label)) = (record.get(0), record.get(1), record.get(2))
+        else {
+            return fail_clierror!(
+                "--reference row {} does not have latitude, longitude & label columns.",
+                row + 1
+            );
         };
-        match operation {
-            Operations::Censor | Operations::Censor_Check | Operations::Censor_Count => {
-                if flag_new_column.is_none() {
-                    return fail!("--new_column (-c) is required for censor operations.");
-                }
-                if censor_invokes == 0
-                    && CENSOR
-                        .set({
-                            let mut censored_words = Censor::Standard + Zealous + Sex;
-                            for word in flag_comparand.split(',') {
-                                censored_words += word.trim();
-                            }
-                            censored_words
-                        })
-                        .is_err()
-                {
-                    return fail!("Cannot initialize Censor engine.");
-                }
-                censor_invokes = censor_invokes.saturating_add(1);
-            }
-            Operations::Copy => {
-                if flag_new_column.is_none() {
-                    return fail!("--new_column (-c) is required for copy operation.");
-                }
-                copy_invokes = copy_invokes.saturating_add(1);
+        let location = parse_location(&format!("{lat},{long}"));
+        let Some((lat, long)) = location else {
+            return fail_clierror!(
+                "--reference row {} has an invalid location: {lat}, {long}",
+                row + 1
+            );
+        };
+        locations.push((lat, long, label.to_string()));
+    }
+    if locations.is_empty() {
+        return fail!("--reference has no locations.");
+    }
+    Ok(locations)
+}
+
+// returns the label of the nearest of the locations to location, with its distance in km
+fn nearest_location(location: (f64, f64), locations: &[(f64, f64, String)]) -> Option<(&str, f64)> {
+    locations
+        .iter()
+        .map(|(lat, long, label)| (label.as_str(), haversine_km(location, (*lat, *long))))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+// the great-circle distance between two locations in km, with the Haversine formula
+fn haversine_km((lat1, long1): (f64, f64), (lat2, long2): (f64, f64)) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let delta_lat = lat2 - lat1;
+    let delta_long = (long2 - long1).to_radians();
+    let a = (delta_lat / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * (delta_long / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+}
+
+// computes `left op right` for the MATH subcommand
+fn math_result(left: f64, right: f64, op: MathOp, div_zero: &str) -> String {
+    let result = match op {
+        MathOp::Add => left + right,
+        MathOp::Subtract => left - right,
+        MathOp::Multiply => left * right,
+        MathOp::Divide => {
+            if right == 0.0 {
+                return div_zero.to_string();
             }
-            Operations::Eudex => {
-                if flag_comparand.is_empty() || flag_new_column.is_none() {
+            left / right
+        }
+    };
+    if result.is_finite() {
+        util::round_num(result, MATH_DECIMAL_PLACES)
+    } else {
+        String::new()
+    }
+}
+
+// parses the --spec of the SORTKEY subcommand, e.g. s20,n5,n8.2
+fn parse_sortkey_specs(spec: &str) -> CliResult<Vec<SortKeySpec>> {
+    let mut specs = Vec::new();
+    for entry in spec.split(',').map(str::trim) {
+        let parsed = match entry.split_at(entry.len().min(1)) {
+            ("s", width) => match width.parse::<usize>() {
+                Ok(width) if width > 0 => Some(SortKeySpec::Text { width }),
+                _ => None,
+            },
+            ("n", format) => {
+                let (width, decimals) = format.split_once('.').unwrap_or((format, "0"));
+                match (width.parse::<usize>(), decimals.parse::<u32>()) {
+                    // the width of numbers is limited by the 38 digits of u128
+                    (Ok(width), Ok(decimals))
+                        if width > 0 && width <= 38 && decimals as usize <= width =>
+                    {
+                        Some(SortKeySpec::Number { width, decimals })
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        let Some(parsed) = parsed else {
+            return fail_clierror!("Invalid --spec entry: {entry:?}");
+        };
+        specs.push(parsed);
+    }
+    Ok(specs)
+}
+
+// appends a column to a SORTKEY key. Each column starts with a marker character
+// ordering the null values first or last - for numbers, the marker also orders
+// the negative numbers before the other numbers
+fn push_sortkey_column(key: &mut String, value: &str, spec: &SortKeySpec, nulls_last: bool) {
+    let (null_marker, value_marker) = if nulls_last { ('2', '1') } else { ('0', '1') };
+    match *spec {
+        SortKeySpec::Text { width } => {
+            if value.is_empty() {
+                key.push(null_marker);
+                key.extend(std::iter::repeat(' ').take(width));
+            } else {
+                key.push(value_marker);
+                let truncated: String = value.chars().take(width).collect();
+                key.push_str(&format!("{truncated:<width$}"));
+            }
+        }
+        SortKeySpec::Number { width, decimals } => {
+            let Some(number) = value.trim().parse::<f64>().ok().filter(|n| !n.is_nan()) else {
+                key.push(null_marker);
+                key.extend(std::iter::repeat(' ').take(width));
+                return;
+            };
+            #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+            let (max, scale) = (10_u128.pow(width as u32) - 1, 10_f64.powi(decimals as i32));
+            let scaled = (number.abs() * scale).round();
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let digits = if scaled >= max as f64 {
+                max
+            } else {
+                scaled as u128
+            };
+            // negative numbers use the complement of their digits, so the larger
+            // the magnitude, the smaller the key. Their marker sorts before the
+            // other numbers, which use the next character up
+            if number < 0.0 && digits > 0 {
+                key.push(if nulls_last { '0' } else { '1' });
+                key.push_str(&format!("{:0width$}", max - digits));
+            } else {
+                key.push(if nulls_last { '1' } else { '2' });
+                key.push_str(&format!("{digits:0width$}"));
+            }
+        }
+    }
+}
+
+// the RANK subcommand. As rows are ranked within their group, all the rows are
+// buffered before they're written out in their original order, each with its rank
+fn rank_rows<R: io::Read, W: io::Write>(
+    rdr: &mut csv::Reader<R>,
+    wtr: &mut csv::Writer<W>,
+    value_index: usize,
+    groupby_sel: Option<&Selection>,
+    descending: bool,
+    dense: bool,
+) -> CliResult<()> {
+    let (records, mut groups) = read_rank_groups(rdr, value_index, groupby_sel)?;
+
+    let mut ranks: Vec<Option<usize>> = vec![None; records.len()];
+    for group in groups.values_mut() {
+        // sort_by is stable, so tied rows stay in their original order
+        if descending {
+            group.sort_by(|a, b| b.0.total_cmp(&a.0));
+        } else {
+            group.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+        let mut rank = 0_usize;
+        let mut prev_value: Option<f64> = None;
+        for (pos, &(value, row_idx)) in group.iter().enumerate() {
+            if prev_value != Some(value) {
+                rank = if dense { rank + 1 } else { pos + 1 };
+                prev_value = Some(value);
+            }
+            ranks[row_idx] = Some(rank);
+        }
+    }
+
+    let mut buffer = itoa::Buffer::new();
+    for (mut record, rank) in records.into_iter().zip(ranks) {
+        match rank {
+            Some(r) => record.push_field(buffer.format(r)),
+            None => record.push_field(""),
+        }
+        wtr.write_record(&record)?;
+    }
+    Ok(wtr.flush()?)
+}
+
+// the PCT_RANK subcommand. Like RANK, all the rows are buffered in their groups,
+// with each group sorted so the values below & equal to a value are contiguous
+fn pct_rank_rows<R: io::Read, W: io::Write>(
+    rdr: &mut csv::Reader<R>,
+    wtr: &mut csv::Writer<W>,
+    value_index: usize,
+    groupby_sel: Option<&Selection>,
+    ties: PctRankTies,
+) -> CliResult<()> {
+    let (records, mut groups) = read_rank_groups(rdr, value_index, groupby_sel)?;
+
+    let mut pct_ranks: Vec<Option<f64>> = vec![None; records.len()];
+    for group in groups.values_mut() {
+        group.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let group_len = group.len() as f64;
+        // below is the number of values less than the current run of equal values
+        let mut below = 0_usize;
+        while below < group.len() {
+            let value = group[below].0;
+            let equal = group[below..].iter().take_while(|v| v.0 == value).count();
+            let counted_below = match ties {
+                PctRankTies::Mean => below as f64 + equal as f64 / 2.0,
+                PctRankTies::Weak => (below + equal) as f64,
+                PctRankTies::Strict => below as f64,
+            };
+            for &(_, row_idx) in &group[below..below + equal] {
+                pct_ranks[row_idx] = Some(counted_below / group_len * 100.0);
+            }
+            below += equal;
+        }
+    }
+
+    for (mut record, pct_rank) in records.into_iter().zip(pct_ranks) {
+        match pct_rank {
+            Some(p) => record.push_field(&util::round_num(p, PCT_RANK_DECIMAL_PLACES)),
+            None => record.push_field(""),
+        }
+        wtr.write_record(&record)?;
+    }
+    Ok(wtr.flush()?)
+}
+
+// the CUM_DISTINCT subcommand. The rows are streamed, with the distinct values of the
+// current group only, failing if an earlier group reappears
+fn cum_distinct_rows<R: io::Read, W: io::Write>(
+    rdr: &mut csv::Reader<R>,
+    wtr: &mut csv::Writer<W>,
+    value_index: usize,
+    groupby_sel: Option<&Selection>,
+) -> CliResult<()> {
+    let mut current_group: Option<Vec<String>> = None;
+    let mut finished_groups: AHashSet<Vec<String>> = AHashSet::new();
+    let mut seen: AHashSet<String> = AHashSet::new();
+    let mut buffer = itoa::Buffer::new();
+
+    let mut record = csv::StringRecord::new();
+    while rdr.read_record(&mut record)? {
+        let group_key = groupby_sel.map_or_else(Vec::new, |sel| {
+            sel.iter().map(|&i| record[i].to_string()).collect()
+        });
+        if current_group.as_ref() != Some(&group_key) {
+            if finished_groups.contains(&group_key) {
+                return fail_clierror!(
+                    "The rows of the {group_key:?} group are not contiguous. Sort the input by \
+                     the --groupby columns first."
+                );
+            }
+            if let Some(previous_group) = current_group.replace(group_key) {
+                finished_groups.insert(previous_group);
+            }
+            seen.clear();
+        }
+        let value = &record[value_index];
+        if !value.is_empty() && !seen.contains(value) {
+            seen.insert(value.to_string());
+        }
+        record.push_field(buffer.format(seen.len()));
+        wtr.write_record(&record)?;
+    }
+    Ok(wtr.flush()?)
+}
+
+// the PARTITION_VALID subcommand. Returns the number of invalid rows
+fn partition_valid_rows<R: io::Read, W: io::Write, V: io::Write>(
+    rdr: &mut csv::Reader<R>,
+    wtr: &mut csv::Writer<W>,
+    invalid_wtr: &mut csv::Writer<V>,
+    sel: &Selection,
+    pattern: &Regex,
+) -> CliResult<usize> {
+    let mut invalid_count = 0_usize;
+    let mut record = csv::StringRecord::new();
+    while rdr.read_record(&mut record)? {
+        if sel.iter().all(|&i| pattern.is_match(&record[i])) {
+            wtr.write_record(&record)?;
+        } else {
+            invalid_wtr.write_record(&record)?;
+            invalid_count += 1;
+        }
+    }
+    wtr.flush()?;
+    invalid_wtr.flush()?;
+    Ok(invalid_count)
+}
+
+// the WINSORIZE subcommand. Like RANK, all the rows are buffered in their groups,
+// so the percentiles of each group are known before the rows are written out.
+// Returns the number of clipped values
+fn winsorize_rows<R: io::Read, W: io::Write>(
+    rdr: &mut csv::Reader<R>,
+    wtr: &mut csv::Writer<W>,
+    value_index: usize,
+    groupby_sel: Option<&Selection>,
+    (lower, upper): (f64, f64),
+    new_column: bool,
+) -> CliResult<usize> {
+    let (records, mut groups) = read_rank_groups(rdr, value_index, groupby_sel)?;
+
+    // the clipped value of the rows that are beyond their group's percentiles
+    let mut clipped: Vec<Option<f64>> = vec![None; records.len()];
+    let mut clipped_count = 0_usize;
+    for group in groups.values_mut() {
+        group.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let lower_bound = interpolated_percentile(group, lower);
+        let upper_bound = interpolated_percentile(group, upper);
+        for &(value, row_idx) in group.iter() {
+            if value < lower_bound {
+                clipped[row_idx] = Some(lower_bound);
+            } else if value > upper_bound {
+                clipped[row_idx] = Some(upper_bound);
+            } else {
+                continue;
+            }
+            clipped_count += 1;
+        }
+    }
+
+    for (mut record, clipped_value) in records.into_iter().zip(clipped) {
+        let value = match clipped_value {
+            Some(v) => util::round_num(v, WINSORIZE_DECIMAL_PLACES),
+            None => record[value_index].to_string(),
+        };
+        if new_column {
+            record.push_field(&value);
+        } else {
+            record = replace_column_value(&record, value_index, &value);
+        }
+        wtr.write_record(&record)?;
+    }
+    wtr.flush()?;
+    Ok(clipped_count)
+}
+
+// the percentile (0-100) of the (value, row index) pairs sorted by value,
+// interpolating linearly between the two closest values
+fn interpolated_percentile(sorted: &[(f64, usize)], percentile: f64) -> f64 {
+    let rank = percentile / 100.0 * (sorted.len() - 1) as f64;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let (lower_idx, upper_idx) = (rank.floor() as usize, rank.ceil() as usize);
+    let (lower_value, upper_value) = (sorted[lower_idx].0, sorted[upper_idx].0);
+    lower_value + (upper_value - lower_value) * (rank - lower_idx as f64)
+}
+
+// buffers all the rows for the RANK, PCT_RANK & WINSORIZE subcommands, with the
+// (value, row index) of the rankable rows of each group
+#[allow(clippy::type_complexity)]
+fn read_rank_groups<R: io::Read>(
+    rdr: &mut csv::Reader<R>,
+    value_index: usize,
+    groupby_sel: Option<&Selection>,
+) -> CliResult<(
+    Vec<csv::StringRecord>,
+    AHashMap<Vec<String>, Vec<(f64, usize)>>,
+)> {
+    let mut records: Vec<csv::StringRecord> = Vec::new();
+    let mut groups: AHashMap<Vec<String>, Vec<(f64, usize)>> = AHashMap::new();
+
+    for result in rdr.records() {
+        let record = result?;
+        if let Ok(value) = record[value_index].trim().parse::<f64>() {
+            if !value.is_nan() {
+                let group_key = groupby_sel.map_or_else(Vec::new, |sel| {
+                    sel.iter().map(|&i| record[i].to_string()).collect()
+                });
+                groups
+                    .entry(group_key)
+                    .or_default()
+                    .push((value, records.len()));
+            }
+        }
+        records.push(record);
+    }
+    Ok((records, groups))
+}
+
+// the KV_PARSE subcommand. Without explicit keys, all the rows are buffered
+// so the keys of all the rows are known before the header row is written
+fn kv_parse_rows<R: io::Read, W: io::Write>(
+    rdr: &mut csv::Reader<R>,
+    wtr: &mut csv::Writer<W>,
+    headers: Option<csv::StringRecord>,
+    column_index: usize,
+    keys: Option<Vec<String>>,
+    pair_sep: &str,
+    kv_sep: &str,
+) -> CliResult<()> {
+    let (keys, records) = if let Some(keys) = keys {
+        (keys, None)
+    } else {
+        let records = rdr.records().collect::<Result<Vec<_>, _>>()?;
+        let mut keys: Vec<String> = Vec::new();
+        let mut seen_keys: AHashSet<String> = AHashSet::new();
+        for record in &records {
+            for (key, _) in parse_kv_pairs(&record[column_index], pair_sep, kv_sep) {
+                if seen_keys.insert(key.clone()) {
+                    keys.push(key);
+                }
+            }
+        }
+        (keys, Some(records))
+    };
+
+    if let Some(mut headers) = headers {
+        for key in &keys {
+            headers.push_field(key);
+        }
+        wtr.write_record(&headers)?;
+    }
+
+    let mut write_row = |mut record: csv::StringRecord| -> CliResult<()> {
+        // later pairs with the same key overwrite earlier ones
+        let pairs: AHashMap<String, String> =
+            parse_kv_pairs(&record[column_index], pair_sep, kv_sep).collect();
+        for key in &keys {
+            record.push_field(pairs.get(key).map_or("", String::as_str));
+        }
+        wtr.write_record(&record)?;
+        Ok(())
+    };
+    if let Some(records) = records {
+        for record in records {
+            write_row(record)?;
+        }
+    } else {
+        for record in rdr.records() {
+            write_row(record?)?;
+        }
+    }
+    Ok(wtr.flush()?)
+}
+
+// returns the trimmed (key, value) pairs of a cell, skipping pairs without a key.
+// A pair without a kv_sep is a key with an empty value
+fn parse_kv_pairs<'a>(
+    cell: &'a str,
+    pair_sep: &'a str,
+    kv_sep: &'a str,
+) -> impl Iterator<Item = (String, String)> + 'a {
+    cell.split(pair_sep).filter_map(move |pair| {
+        let (key, value) = pair.split_once(kv_sep).unwrap_or((pair, ""));
+        let key = key.trim();
+        if key.is_empty() {
+            None
+        } else {
+            Some((key.to_string(), value.trim().to_string()))
+        }
+    })
+}
+
+// the options used by the operations, for validate_operations
+struct OperationOptions<'a> {
+    comparand:      &'a str,
+    replacement:    &'a str,
+    new_column:     &'a Option<String>,
+    formatstr:      &'a str,
+    from:           &'a str,
+    to:             &'a str,
+    timestamp_from: &'a str,
+    fy_start:       u32,
+    fiscal_format:  &'a str,
+    sep:            &'a str,
+    mode:           &'a str,
+    domain_part:    &'a str,
+    algo:           &'a str,
+    blank_invalid:  bool,
+    mac_format:     &'a str,
+    keep_plus:      bool,
+    iso_type:       &'a str,
+    iso_output:     &'a Option<String>,
+    token_key:      &'a Option<String>,
+    token_format:   &'a str,
+    buckets:        &'a Option<String>,
+    salt:           &'a Option<String>,
+}
+
+// validate apply operations for required options
+// and prepare operations enum vec
+fn validate_operations(
+    operations: &Vec<&str>,
+    options: &OperationOptions,
+) -> Result<Vec<Operations>, CliError> {
+    let mut censor_invokes = 0_u8;
+    let mut copy_invokes = 0_u8;
+    let mut eudex_invokes = 0_u8;
+    let mut regex_replace_invokes = 0_u8;
+    let mut replace_invokes = 0_u8;
+    let mut sentiment_invokes = 0_u8;
+    let mut sim_invokes = 0_u8;
+    let mut strip_invokes = 0_u8;
+    let mut whatlang_invokes = 0_u8;
+
+    let mut ops_vec: Vec<Operations> = Vec::with_capacity(operations.len());
+
+    for op in operations {
+        let Ok(operation) = Operations::from_str(op) else {
+            return fail_clierror!("Unknown '{op}' operation");
+        };
+        match operation {
+            Operations::Ab_Bucket => {
+                let Some(buckets) = options.buckets else {
+                    return fail!("--buckets is required for the ab_bucket operation.");
+                };
+                let buckets = parse_ab_buckets(buckets)?;
+                let total_weight = buckets.iter().map(|(_, weight)| weight).sum();
+                if AB_BUCKETS.set((buckets, total_weight)).is_err()
+                    || AB_SALT
+                        .set(options.salt.clone().unwrap_or_default())
+                        .is_err()
+                {
+                    return fail!("Cannot initialize ab_bucket buckets.");
+                }
+            }
+            Operations::Censor | Operations::Censor_Check | Operations::Censor_Count => {
+                if options.new_column.is_none() {
+                    return fail!("--new_column (-c) is required for censor operations.");
+                }
+                if censor_invokes == 0
+                    && CENSOR
+                        .set({
+                            let mut censored_words = Censor::Standard + Zealous + Sex;
+                            for word in options.comparand.split(',') {
+                                censored_words += word.trim();
+                            }
+                            censored_words
+                        })
+                        .is_err()
+                {
+                    return fail!("Cannot initialize Censor engine.");
+                }
+                censor_invokes = censor_invokes.saturating_add(1);
+            }
+            Operations::Checkdigit => {
+                let algo = match options.algo.to_lowercase().as_str() {
+                    "luhn" => CheckDigitAlgo::Luhn,
+                    "isbn" => CheckDigitAlgo::Isbn,
+                    "iban" => CheckDigitAlgo::Iban,
+                    _ => return fail_clierror!("Invalid --algo: {}", options.algo),
+                };
+                if CHECKDIGIT_ALGO.set(algo).is_err() {
+                    return fail!("Cannot initialize checkdigit algorithm.");
+                }
+                CHECKDIGIT_BLANK_INVALID.store(options.blank_invalid, Ordering::Relaxed);
+            }
+            Operations::Digits_Only => {
+                DIGITS_ONLY_KEEP_PLUS.store(options.keep_plus, Ordering::Relaxed);
+            }
+            Operations::Mac => {
+                let mac_format = match options.mac_format.to_lowercase().as_str() {
+                    "colon" => MacFormat::Colon,
+                    "dash" => MacFormat::Dash,
+                    "dot" => MacFormat::Dot,
+                    "bare" => MacFormat::Bare,
+                    _ => return fail_clierror!("Invalid --mac-format: {}", options.mac_format),
+                };
+                let uppercase = options.mac_format.bytes().all(|b| b.is_ascii_uppercase());
+                if MAC_FORMAT.set((mac_format, uppercase)).is_err() {
+                    return fail!("Cannot initialize mac format.");
+                }
+                MAC_BLANK_INVALID.store(options.blank_invalid, Ordering::Relaxed);
+            }
+            Operations::Copy => {
+                if options.new_column.is_none() {
+                    return fail!("--new_column (-c) is required for copy operation.");
+                }
+                copy_invokes = copy_invokes.saturating_add(1);
+            }
+            Operations::Eudex => {
+                if options.comparand.is_empty() || options.new_column.is_none() {
                     return fail!("--comparand (-C) and --new_column (-c) is required for eudex.");
                 }
                 if eudex_invokes == 0
                     && EUDEX_COMPARAND_HASH
-                        .set(eudex::Hash::new(flag_comparand))
+                        .set(eudex::Hash::new(options.comparand))
                         .is_err()
                 {
                     return fail!("Cannot initialize Eudex.");
@@ -851,19 +2478,19 @@ fn validate_operations(
                 eudex_invokes = eudex_invokes.saturating_add(1);
             }
             Operations::Mtrim | Operations::Mltrim | Operations::Mrtrim => {
-                if flag_comparand.is_empty() {
+                if options.comparand.is_empty() {
                     return fail!("--comparand (-C) is required for match trim operations.");
                 }
             }
             Operations::Regex_Replace => {
-                if flag_comparand.is_empty() || flag_replacement.is_empty() {
+                if options.comparand.is_empty() || options.replacement.is_empty() {
                     return fail!(
                         "--comparand (-C) and --replacement (-R) are required for regex_replace \
                          operation."
                     );
                 }
                 if regex_replace_invokes == 0 {
-                    let re = match regex::Regex::new(flag_comparand) {
+                    let re = match regex::Regex::new(options.comparand) {
                         Ok(re) => re,
                         Err(err) => {
                             return fail_clierror!("regex_replace expression error: {err:?}");
@@ -874,7 +2501,7 @@ fn validate_operations(
                 regex_replace_invokes = regex_replace_invokes.saturating_add(1);
             }
             Operations::Replace => {
-                if flag_comparand.is_empty() || flag_replacement.is_empty() {
+                if options.comparand.is_empty() || options.replacement.is_empty() {
                     return fail!(
                         "--comparand (-C) and --replacement (-R) are required for replace \
                          operation."
@@ -883,7 +2510,7 @@ fn validate_operations(
                 replace_invokes = replace_invokes.saturating_add(1);
             }
             Operations::Sentiment => {
-                if flag_new_column.is_none() {
+                if options.new_column.is_none() {
                     return fail!("--new_column (-c) is required for sentiment operation.");
                 }
                 sentiment_invokes = sentiment_invokes.saturating_add(1);
@@ -894,7 +2521,7 @@ fn validate_operations(
             | Operations::Simsd
             | Operations::Simhm
             | Operations::Simod => {
-                if flag_comparand.is_empty() || flag_new_column.is_none() {
+                if options.comparand.is_empty() || options.new_column.is_none() {
                     return fail!(
                         "--comparand (-C) and --new_column (-c) is required for similarity \
                          operations."
@@ -902,14 +2529,42 @@ fn validate_operations(
                 }
                 sim_invokes = sim_invokes.saturating_add(1);
             }
+            Operations::Slug => {
+                let _ = SLUG_SEPARATOR.set(options.sep.to_string());
+            }
             Operations::Strip_Prefix | Operations::Strip_Suffix => {
-                if flag_comparand.is_empty() {
+                if options.comparand.is_empty() {
                     return fail!("--comparand (-C) is required for strip operations.");
                 }
                 strip_invokes = strip_invokes.saturating_add(1);
             }
+            Operations::Tokencount => {
+                let mode = match options.mode.to_lowercase().as_str() {
+                    "words" => TokenCountMode::Words,
+                    "unique" => TokenCountMode::Unique,
+                    "sentences" => TokenCountMode::Sentences,
+                    "chars" => TokenCountMode::Chars,
+                    _ => return fail_clierror!("Invalid --mode: {}", options.mode),
+                };
+                if TOKENCOUNT_MODE.set(mode).is_err() {
+                    return fail!("Cannot initialize tokencount mode.");
+                }
+            }
+            Operations::Domain => {
+                let mode = match options.domain_part.to_lowercase().as_str() {
+                    "full" => DomainMode::Full,
+                    "registrable" => DomainMode::Registrable,
+                    _ => return fail_clierror!("Invalid --domain-part: {}", options.domain_part),
+                };
+                if DOMAIN_MODE.set(mode).is_err() {
+                    return fail!("Cannot initialize domain mode.");
+                }
+                if mode == DomainMode::Registrable {
+                    let _ = PUBLIC_SUFFIXES.set(public_suffixes());
+                }
+            }
             Operations::Thousands => {
-                let separator_policy = match flag_formatstr {
+                let separator_policy = match options.formatstr {
                     "dot" => policies::DOT_SEPARATOR,
                     "space" => policies::SPACE_SEPARATOR,
                     "underscore" => policies::UNDERSCORE_SEPARATOR,
@@ -924,7 +2579,8 @@ fn validate_operations(
             Operations::Round => {
                 if ROUND_PLACES
                     .set(
-                        flag_formatstr
+                        options
+                            .formatstr
                             .parse::<u32>()
                             .unwrap_or(DEFAULT_ROUND_PLACES),
                     )
@@ -933,22 +2589,95 @@ fn validate_operations(
                     return fail!("Cannot initialize Round precision.");
                 };
             }
+            Operations::Fiscal => {
+                if !(1..=12).contains(&options.fy_start) {
+                    return fail_clierror!(
+                        "--fy-start must be a month from 1 to 12, not {}.",
+                        options.fy_start
+                    );
+                }
+                if FISCAL_FY_START.set(options.fy_start).is_err()
+                    || FISCAL_FORMAT
+                        .set(options.fiscal_format.to_string())
+                        .is_err()
+                {
+                    return fail!("Cannot initialize fiscal year start.");
+                }
+            }
+            Operations::Parse_Timestamp => {
+                let timestamp_format = match options.timestamp_from.to_lowercase().as_str() {
+                    "auto" => TimestampFormat::Auto,
+                    "epoch_s" => TimestampFormat::EpochS,
+                    "epoch_ms" => TimestampFormat::EpochMs,
+                    "rfc2822" => TimestampFormat::Rfc2822,
+                    "http" => TimestampFormat::Http,
+                    _ => {
+                        return fail_clierror!(
+                            "Invalid --timestamp-from: {}",
+                            options.timestamp_from
+                        )
+                    }
+                };
+                if TIMESTAMP_FROM.set(timestamp_format).is_err() {
+                    return fail!("Cannot initialize parse_timestamp format.");
+                }
+            }
+            Operations::Iso_Code => {
+                let lookup = iso_code_lookup(options.iso_type, options.iso_output.as_deref())?;
+                if ISO_CODE_LOOKUP.set(lookup).is_err() {
+                    return fail!("Cannot initialize iso_code lookup.");
+                }
+            }
+            Operations::Tokenize | Operations::Detokenize => {
+                let Some(token_key) = options.token_key.as_deref().filter(|key| !key.is_empty())
+                else {
+                    return fail!(
+                        "--token-key is required for the tokenize & detokenize operations."
+                    );
+                };
+                let token_format = match options.token_format.to_lowercase().as_str() {
+                    "digits" => TokenFormat::Digits,
+                    "alpha" => TokenFormat::Alpha,
+                    "alnum" => TokenFormat::Alnum,
+                    _ => return fail_clierror!("Invalid --token-format: {}", options.token_format),
+                };
+                // tokenize & detokenize can both be in the series, sharing the same key
+                if TOKEN_KEY.get().is_none()
+                    && (TOKEN_KEY.set(token_key.as_bytes().to_vec()).is_err()
+                        || TOKEN_FORMAT.set(token_format).is_err())
+                {
+                    return fail!("Cannot initialize tokenize key.");
+                }
+            }
+            Operations::Tz_Convert => {
+                let from_tz = match options.from.parse::<Tz>() {
+                    Ok(tz) => tz,
+                    Err(e) => return fail_clierror!("Invalid --from timezone: {e}"),
+                };
+                let to_tz = match options.to.parse::<Tz>() {
+                    Ok(tz) => tz,
+                    Err(e) => return fail_clierror!("Invalid --to timezone: {e}"),
+                };
+                if TZ_CONVERT_FROM.set(from_tz).is_err() || TZ_CONVERT_TO.set(to_tz).is_err() {
+                    return fail!("Cannot initialize tz_convert timezones.");
+                }
+            }
             Operations::Whatlang => {
-                if flag_new_column.is_none() {
+                if options.new_column.is_none() {
                     return fail!("--new_column (-c) is required for whatlang language detection.");
                 }
 
                 if whatlang_invokes == 0
                     && WHATLANG_CONFIDENCE_THRESHOLD
-                        .set(if flag_comparand.is_empty() {
+                        .set(if options.comparand.is_empty() {
                             DEFAULT_THRESHOLD
                         } else {
                             let preparsed_threshold;
-                            let show_confidence = if flag_comparand.ends_with('?') {
-                                preparsed_threshold = flag_comparand.trim_end_matches('?');
+                            let show_confidence = if options.comparand.ends_with('?') {
+                                preparsed_threshold = options.comparand.trim_end_matches('?');
                                 true
                             } else {
-                                preparsed_threshold = flag_comparand;
+                                preparsed_threshold = options.comparand;
                                 false
                             };
                             let desired_threshold = preparsed_threshold
@@ -1027,6 +2756,18 @@ fn apply_operations(
                 let squeezer: &'static Regex = regex_once_cell!(r"\s+");
                 *cell = squeezer.replace_all(cell, "").to_string();
             }
+            Operations::Digits_Only => {
+                let digits: String = cell.chars().filter(char::is_ascii_digit).collect();
+                *cell = if digits.is_empty() {
+                    digits
+                } else if DIGITS_ONLY_KEEP_PLUS.load(Ordering::Relaxed)
+                    && cell.trim_start().starts_with('+')
+                {
+                    format!("+{digits}")
+                } else {
+                    digits
+                };
+            }
             Operations::Trim => {
                 *cell = String::from(cell.trim());
             }
@@ -1072,6 +2813,31 @@ fn apply_operations(
             Operations::Titlecase => {
                 *cell = titlecase(cell);
             }
+            Operations::Tokencount => {
+                let count = match TOKENCOUNT_MODE.get().unwrap() {
+                    TokenCountMode::Words => cell.unicode_words().count(),
+                    TokenCountMode::Unique => cell
+                        .unicode_words()
+                        .map(str::to_lowercase)
+                        .collect::<AHashSet<_>>()
+                        .len(),
+                    TokenCountMode::Sentences => cell
+                        .unicode_sentences()
+                        .filter(|sentence| !sentence.trim().is_empty())
+                        .count(),
+                    TokenCountMode::Chars => cell.graphemes(true).count(),
+                };
+                *cell = count.to_string();
+            }
+            Operations::Slug => {
+                let slug_separator = SLUG_SEPARATOR.get().unwrap();
+                *cell = deunicode(cell)
+                    .to_lowercase()
+                    .split(|c: char| !c.is_ascii_alphanumeric())
+                    .filter(|word| !word.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(slug_separator);
+            }
             Operations::Replace => {
                 *cell = cell.replace(comparand, replacement);
             }
@@ -1079,6 +2845,36 @@ fn apply_operations(
                 let regexreplace = REGEX_REPLACE.get().unwrap();
                 *cell = regexreplace.replace_all(cell, replacement).to_string();
             }
+            Operations::Domain => {
+                let domain =
+                    cell_domain(cell).and_then(|domain| match DOMAIN_MODE.get().unwrap() {
+                        DomainMode::Full => Some(domain),
+                        DomainMode::Registrable => {
+                            registrable_domain(&domain, PUBLIC_SUFFIXES.get().unwrap())
+                        }
+                    });
+                *cell = domain.unwrap_or_default();
+            }
+            Operations::Mac => {
+                if !cell.is_empty() {
+                    let (mac_format, uppercase) = *MAC_FORMAT.get().unwrap();
+                    match mac_address(cell, mac_format, uppercase) {
+                        Some(mac) => *cell = mac,
+                        None => {
+                            MAC_INVALID_COUNT.fetch_add(1, Ordering::Relaxed);
+                            if MAC_BLANK_INVALID.load(Ordering::Relaxed) {
+                                cell.clear();
+                            }
+                        }
+                    }
+                }
+            }
+            Operations::Ab_Bucket => {
+                if !cell.is_empty() {
+                    let (buckets, total_weight) = AB_BUCKETS.get().unwrap();
+                    *cell = ab_bucket(cell, AB_SALT.get().unwrap(), buckets, *total_weight);
+                }
+            }
             Operations::Censor => {
                 let censor = CENSOR.get().unwrap();
                 *cell = censor.censor(cell);
@@ -1219,11 +3015,766 @@ fn apply_operations(
                     }
                 }
             }
+            Operations::Fiscal => {
+                if let Ok(parsed_date) =
+                    parse_with_preference(cell, PREFER_DMY.load(Ordering::Relaxed))
+                {
+                    let fy_start = *FISCAL_FY_START.get().unwrap();
+                    let month = parsed_date.month();
+                    // fiscal years are named after the calendar year they end in
+                    let fiscal_year = if fy_start > 1 && month >= fy_start {
+                        parsed_date.year() + 1
+                    } else {
+                        parsed_date.year()
+                    };
+                    let period = (month + 12 - fy_start) % 12 + 1;
+                    let quarter = (period - 1) / 3 + 1;
+                    *cell = FISCAL_FORMAT
+                        .get()
+                        .unwrap()
+                        .replace("{fy}", &fiscal_year.to_string())
+                        .replace("{q}", &quarter.to_string())
+                        .replace("{p}", &period.to_string());
+                }
+            }
+            Operations::Tz_Convert => {
+                // naive datetimes are parsed in the --from timezone
+                if let Ok(parsed_date) = parse_with_timezone(cell, TZ_CONVERT_FROM.get().unwrap()) {
+                    let to_tz = TZ_CONVERT_TO.get().unwrap();
+                    *cell = parsed_date
+                        .with_timezone(to_tz)
+                        .format(formatstr)
+                        .to_string();
+                }
+            }
+            Operations::Parse_Timestamp => {
+                if !cell.is_empty() {
+                    match parse_timestamp(cell.trim(), *TIMESTAMP_FROM.get().unwrap()) {
+                        Some(timestamp) => {
+                            *cell = timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+                        }
+                        None => cell.clear(),
+                    }
+                }
+            }
+            Operations::Checkdigit => {
+                if !cell.is_empty() {
+                    let valid = match CHECKDIGIT_ALGO.get().unwrap() {
+                        CheckDigitAlgo::Luhn => luhn_valid(cell),
+                        CheckDigitAlgo::Isbn => isbn_valid(cell),
+                        CheckDigitAlgo::Iban => iban_valid(cell),
+                    };
+                    if !valid {
+                        CHECKDIGIT_INVALID_COUNT.fetch_add(1, Ordering::Relaxed);
+                    }
+                    if CHECKDIGIT_BLANK_INVALID.load(Ordering::Relaxed) {
+                        if !valid {
+                            cell.clear();
+                        }
+                    } else {
+                        *cell = valid.to_string();
+                    }
+                }
+            }
+            Operations::Tokenize | Operations::Detokenize => {
+                if !cell.is_empty() {
+                    *cell = tokenize_value(
+                        cell,
+                        TOKEN_KEY.get().unwrap(),
+                        *TOKEN_FORMAT.get().unwrap(),
+                        matches!(op, Operations::Detokenize),
+                    );
+                }
+            }
+            Operations::Coord => {
+                if !cell.is_empty() {
+                    match parse_coordinate(cell) {
+                        Some(degrees) => *cell = util::round_num(degrees, COORD_DECIMAL_PLACES),
+                        None => cell.clear(),
+                    }
+                }
+            }
+            Operations::Iso_Code => {
+                if !cell.is_empty() {
+                    let lookup = ISO_CODE_LOOKUP.get().unwrap();
+                    let code = lookup
+                        .get(&cell.trim().to_lowercase())
+                        .or_else(|| lookup.get(&iso_code_key(cell)));
+                    match code {
+                        Some(code) => *cell = code.clone(),
+                        None => {
+                            ISO_CODE_UNMAPPED_COUNT.fetch_add(1, Ordering::Relaxed);
+                            cell.clear();
+                        }
+                    }
+                }
+            }
             Operations::Copy => {} // copy is a noop
         }
     }
 }
 
+// extracts the lowercased domain of an email address, a URL or a host name,
+// returning None if it is not a valid domain name
+fn cell_domain(value: &str) -> Option<String> {
+    let value = value.trim();
+    let host = if let Some((_, rest)) = value.split_once("://") {
+        // a URL - the host is the part of the authority after the userinfo & before the port
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let host = authority.rsplit('@').next().unwrap_or_default();
+        host.split(':').next().unwrap_or_default()
+    } else if let Some((_, domain)) = value.rsplit_once('@') {
+        // an email address
+        domain
+    } else {
+        // a host name, possibly followed by a port or a path
+        value.split(['/', '?', '#', ':']).next().unwrap_or_default()
+    };
+    let domain = host.trim_end_matches('.').to_lowercase();
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    let valid = labels.len() > 1
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        })
+        // IPv4 addresses are not domains, and TLDs are never numeric
+        && !labels[labels.len() - 1].bytes().all(|b| b.is_ascii_digit());
+    valid.then_some(domain)
+}
+
+// parses a MAC address in colon, dash, dot or bare notation, returning it in the
+// mac_format notation, or None if it is not a valid MAC address
+fn mac_address(value: &str, mac_format: MacFormat, uppercase: bool) -> Option<String> {
+    let value = value.trim();
+    // the separator & the number of hex digits per group of each notation
+    let (separator, group_len) = if value.contains(':') {
+        (':', 2)
+    } else if value.contains('-') {
+        ('-', 2)
+    } else if value.contains('.') {
+        ('.', 4)
+    } else {
+        (' ', 12)
+    };
+    let groups: Vec<&str> = if group_len == 12 {
+        vec![value]
+    } else {
+        value.split(separator).collect()
+    };
+    if groups.len() != 12 / group_len
+        || !groups
+            .iter()
+            .all(|group| group.len() == group_len && group.bytes().all(|b| b.is_ascii_hexdigit()))
+    {
+        return None;
+    }
+    let digits = if uppercase {
+        groups.concat().to_ascii_uppercase()
+    } else {
+        groups.concat().to_ascii_lowercase()
+    };
+
+    let (separator, group_len) = match mac_format {
+        MacFormat::Colon => (":", 2),
+        MacFormat::Dash => ("-", 2),
+        MacFormat::Dot => (".", 4),
+        MacFormat::Bare => return Some(digits),
+    };
+    Some(
+        (0..digits.len())
+            .step_by(group_len)
+            .map(|start| &digits[start..start + group_len])
+            .collect::<Vec<_>>()
+            .join(separator),
+    )
+}
+
+// parses the bundled Public Suffix List
+fn public_suffixes() -> PublicSuffixes {
+    let mut suffixes = PublicSuffixes::default();
+    for rule in PUBLIC_SUFFIX_LIST
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("//"))
+    {
+        if let Some(exception) = rule.strip_prefix('!') {
+            suffixes.exceptions.insert(exception);
+        } else if let Some(wildcard) = rule.strip_prefix("*.") {
+            suffixes.wildcards.insert(wildcard);
+        } else {
+            suffixes.rules.insert(rule);
+        }
+    }
+    suffixes
+}
+
+// returns the registrable domain of domain - its public suffix & the label before it,
+// following the algorithm of https://publicsuffix.org/list/, with the implicit "*" rule
+// making unlisted TLDs public suffixes. Returns None if domain is itself a public suffix
+fn registrable_domain(domain: &str, suffixes: &PublicSuffixes) -> Option<String> {
+    let labels: Vec<&str> = domain.split('.').collect();
+    // the index of the first label of the longest matching public suffix
+    let mut suffix_start = labels.len() - 1;
+    for start in 0..labels.len() {
+        let candidate = labels[start..].join(".");
+        if suffixes.exceptions.contains(candidate.as_str()) {
+            // exception rules take precedence, and their suffix is their parent
+            suffix_start = start + 1;
+            break;
+        }
+        let wildcard_match = start + 1 < labels.len()
+            && suffixes
+                .wildcards
+                .contains(labels[start + 1..].join(".").as_str());
+        if wildcard_match || suffixes.rules.contains(candidate.as_str()) {
+            suffix_start = start;
+            break;
+        }
+    }
+    (suffix_start > 0).then(|| labels[suffix_start - 1..].join("."))
+}
+
+// parses the name:weight pairs of the --buckets of the ab_bucket operation
+fn parse_ab_buckets(buckets: &str) -> Result<Vec<(String, u64)>, CliError> {
+    let mut parsed_buckets = Vec::new();
+    for bucket in buckets.split(',') {
+        let Some((name, weight)) = bucket.rsplit_once(':') else {
+            return fail_clierror!("Invalid --buckets bucket, expected name:weight: {bucket}");
+        };
+        let weight = match weight.trim().parse::<u64>() {
+            Ok(weight) if weight > 0 => weight,
+            _ => return fail_clierror!("Invalid --buckets weight of {name}: {weight}"),
+        };
+        parsed_buckets.push((name.trim().to_string(), weight));
+    }
+    Ok(parsed_buckets)
+}
+
+// assigns value to a bucket, with the SHA-256 hash of the salt & the value
+// modulo the total weight of the buckets
+fn ab_bucket(value: &str, salt: &str, buckets: &[(String, u64)], total_weight: u64) -> String {
+    let digest = Sha256::new()
+        .chain_update(salt.as_bytes())
+        .chain_update([0_u8])
+        .chain_update(value.as_bytes())
+        .finalize();
+    let mut hash_bytes = [0_u8; 8];
+    hash_bytes.copy_from_slice(&digest[..8]);
+    let mut point = u64::from_be_bytes(hash_bytes) % total_weight;
+    for (name, weight) in buckets {
+        if point < *weight {
+            return name.clone();
+        }
+        point -= weight;
+    }
+    // unreachable, as point is less than the total weight
+    String::new()
+}
+
+// parses a timestamp of the parse_timestamp operation in the --timestamp-from format
+fn parse_timestamp(value: &str, format: TimestampFormat) -> Option<DateTime<Utc>> {
+    match format {
+        TimestampFormat::EpochS => parse_epoch(value, false),
+        TimestampFormat::EpochMs => parse_epoch(value, true),
+        TimestampFormat::Rfc2822 => DateTime::parse_from_rfc2822(value)
+            .ok()
+            .map(|datetime| datetime.with_timezone(&Utc)),
+        TimestampFormat::Http => parse_http_date(value),
+        TimestampFormat::Auto => match value.parse::<f64>() {
+            Ok(epoch) if epoch.is_finite() => parse_epoch(value, epoch.abs() >= EPOCH_MS_THRESHOLD),
+            Ok(_) => None,
+            // rfc2822 is also tried by parse_http_date
+            Err(_) => parse_http_date(value),
+        },
+    }
+}
+
+// parses epoch seconds or epoch milliseconds, possibly with a fraction,
+// rounded to the nanosecond
+fn parse_epoch(value: &str, millis: bool) -> Option<DateTime<Utc>> {
+    if let Ok(epoch) = value.parse::<i64>() {
+        return if millis {
+            Utc.timestamp_millis_opt(epoch).single()
+        } else {
+            Utc.timestamp_opt(epoch, 0).single()
+        };
+    }
+    let epoch = value.parse::<f64>().ok()?;
+    if !epoch.is_finite() {
+        return None;
+    }
+    // the fraction is split from the whole units before converting them to seconds,
+    // so the sub-millisecond fraction of millisecond epochs is kept exactly
+    let (units_per_second, nanos_per_unit) = if millis {
+        (1_000_i64, 1_000_000.0)
+    } else {
+        (1_i64, 1_000_000_000.0)
+    };
+    let whole_units = epoch.floor();
+    let fraction_nanos = ((epoch - whole_units) * nanos_per_unit).round() as i64;
+    let whole_units = whole_units as i64;
+    let nanos = whole_units.rem_euclid(units_per_second) * (1_000_000_000 / units_per_second)
+        + fraction_nanos;
+    Utc.timestamp_opt(
+        whole_units.div_euclid(units_per_second),
+        nanos.min(999_999_999) as u32,
+    )
+    .single()
+}
+
+// parses the HTTP date formats of RFC 7231 - IMF-fixdate, which is a subset of
+// RFC 2822, and the obsolete RFC 850 & asctime formats, which are always in GMT
+fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(datetime) = DateTime::parse_from_rfc2822(value) {
+        return Some(datetime.with_timezone(&Utc));
+    }
+    ["%A, %d-%b-%y %H:%M:%S GMT", "%a %b %e %H:%M:%S %Y"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|naive| Utc.from_utc_datetime(&naive))
+}
+
+// replaces the --token-format characters of value with their format-preserving token,
+// or with the value of their token when reversing. The other characters are left in place.
+fn tokenize_value(value: &str, key: &[u8], format: TokenFormat, reverse: bool) -> String {
+    let mut chars: Vec<char> = value.chars().collect();
+    let mut positions: Vec<usize> = Vec::with_capacity(chars.len());
+    let mut numerals: Vec<u8> = Vec::with_capacity(chars.len());
+    for (pos, c) in chars.iter().enumerate() {
+        if let Some(numeral) = token_numeral(*c, format) {
+            positions.push(pos);
+            numerals.push(numeral);
+        }
+    }
+    if numerals.is_empty() {
+        return value.to_string();
+    }
+
+    let numerals = token_feistel(&numerals, key, format, reverse);
+    for (pos, numeral) in positions.into_iter().zip(numerals) {
+        chars[pos] = token_char(numeral, format, chars[pos]);
+    }
+    chars.into_iter().collect()
+}
+
+fn token_radix(format: TokenFormat) -> u8 {
+    match format {
+        TokenFormat::Digits => 10,
+        TokenFormat::Alpha => 26,
+        TokenFormat::Alnum => 62,
+    }
+}
+
+fn token_numeral(c: char, format: TokenFormat) -> Option<u8> {
+    match (format, c) {
+        (TokenFormat::Digits | TokenFormat::Alnum, '0'..='9') => Some(c as u8 - b'0'),
+        (TokenFormat::Alpha, 'a'..='z') => Some(c as u8 - b'a'),
+        (TokenFormat::Alpha, 'A'..='Z') => Some(c as u8 - b'A'),
+        (TokenFormat::Alnum, 'A'..='Z') => Some(c as u8 - b'A' + 10),
+        (TokenFormat::Alnum, 'a'..='z') => Some(c as u8 - b'a' + 36),
+        _ => None,
+    }
+}
+
+// alpha tokens keep the case of the letter they replace
+fn token_char(numeral: u8, format: TokenFormat, replaced: char) -> char {
+    match format {
+        TokenFormat::Digits => (b'0' + numeral) as char,
+        TokenFormat::Alpha if replaced.is_ascii_uppercase() => (b'A' + numeral) as char,
+        TokenFormat::Alpha => (b'a' + numeral) as char,
+        TokenFormat::Alnum => match numeral {
+            0..=9 => (b'0' + numeral) as char,
+            10..=35 => (b'A' + numeral - 10) as char,
+            _ => (b'a' + numeral - 36) as char,
+        },
+    }
+}
+
+// a balanced Feistel network over the numerals, with HMAC-SHA256 keystreams as its
+// round function, adding (or subtracting when reversing) them modulo the radix.
+// This is a permutation of the numeral strings of each length, so distinct values
+// always get distinct tokens, and it cannot be reversed without the key.
+fn token_feistel(numerals: &[u8], key: &[u8], format: TokenFormat, reverse: bool) -> Vec<u8> {
+    let radix = token_radix(format);
+    let len = numerals.len();
+    if len == 1 {
+        // a single numeral cannot be split, so it is just shifted
+        let shift = token_keystream(key, 0, radix, len, &[], 1)[0];
+        let numeral = if reverse {
+            (numerals[0] + radix - shift) % radix
+        } else {
+            (numerals[0] + shift) % radix
+        };
+        return vec![numeral];
+    }
+
+    let (left, right) = numerals.split_at(len / 2);
+    let (mut left, mut right) = (left.to_vec(), right.to_vec());
+    if reverse {
+        for round in (0..TOKEN_FEISTEL_ROUNDS).rev() {
+            let keystream = token_keystream(key, round, radix, len, &left, right.len());
+            let prev_left = right
+                .iter()
+                .zip(keystream)
+                .map(|(numeral, k)| (numeral + radix - k) % radix)
+                .collect();
+            right = left;
+            left = prev_left;
+        }
+    } else {
+        for round in 0..TOKEN_FEISTEL_ROUNDS {
+            let keystream = token_keystream(key, round, radix, len, &right, left.len());
+            let next_right = left
+                .iter()
+                .zip(keystream)
+                .map(|(numeral, k)| (numeral + k) % radix)
+                .collect();
+            left = right;
+            right = next_right;
+        }
+    }
+    left.append(&mut right);
+    left
+}
+
+// the HMAC-SHA256 keystream of count numerals of a Feistel round, from the other half
+fn token_keystream(
+    key: &[u8],
+    round: u8,
+    radix: u8,
+    len: usize,
+    half: &[u8],
+    count: usize,
+) -> Vec<u8> {
+    let mut keystream = Vec::with_capacity(count + 16);
+    let mut block = 0_u32;
+    while keystream.len() < count {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC can take a key of any size");
+        mac.update(&[round, radix]);
+        mac.update(&(len as u64).to_be_bytes());
+        mac.update(&block.to_be_bytes());
+        mac.update(half);
+        let digest = mac.finalize().into_bytes();
+        keystream.extend(
+            digest
+                .chunks_exact(2)
+                .map(|pair| (u16::from_be_bytes([pair[0], pair[1]]) % u16::from(radix)) as u8),
+        );
+        block += 1;
+    }
+    keystream.truncate(count);
+    keystream
+}
+
+// builds the lookup of the iso_code operation from the bundled code list of --type,
+// mapping the codes, names & aliases of each entry to its --iso-output code.
+// Everything is keyed by its iso_code_key, and symbols, which have no such key,
+// are also keyed by their lowercased value. Earlier entries take precedence.
+fn iso_code_lookup(
+    flag_type: &str,
+    flag_iso_output: Option<&str>,
+) -> Result<AHashMap<String, String>, CliError> {
+    let iso_output = flag_iso_output.map(str::to_lowercase);
+    let (code_list, codes_len, output_index) = match flag_type.to_lowercase().as_str() {
+        "country" => (
+            ISO_3166_1_CSV,
+            4,
+            match iso_output.as_deref() {
+                None | Some("alpha2") => 0,
+                Some("alpha3") => 1,
+                Some("numeric") => 2,
+                Some("name") => 3,
+                Some(_) => {
+                    return fail_clierror!(
+                        "Invalid --iso-output for countries: {}",
+                        flag_iso_output.unwrap_or_default()
+                    )
+                }
+            },
+        ),
+        "currency" => (
+            ISO_4217_CSV,
+            3,
+            match iso_output.as_deref() {
+                None | Some("alpha3") => 0,
+                Some("numeric") => 1,
+                Some("name") => 2,
+                Some(_) => {
+                    return fail_clierror!(
+                        "Invalid --iso-output for currencies: {}",
+                        flag_iso_output.unwrap_or_default()
+                    )
+                }
+            },
+        ),
+        _ => return fail_clierror!("Invalid --type: {flag_type}"),
+    };
+
+    let mut codes_rdr = csv::Reader::from_reader(code_list.as_bytes());
+    let mut entries = Vec::new();
+    for record in codes_rdr.records() {
+        entries.push(record?);
+    }
+
+    let mut lookup = AHashMap::with_capacity(entries.len() * 8);
+    // the codes & names of all the entries come first, so an alias never
+    // shadows the actual code or name of another entry
+    for pass in 0..2 {
+        for entry in &entries {
+            let output = &entry[output_index];
+            let values: Vec<&str> = if pass == 0 {
+                entry.iter().take(codes_len).collect()
+            } else {
+                entry[codes_len].split('|').collect()
+            };
+            for value in values.into_iter().filter(|v| !v.is_empty()) {
+                lookup
+                    .entry(iso_code_key(value))
+                    .or_insert_with(|| output.to_string());
+                lookup
+                    .entry(value.to_lowercase())
+                    .or_insert_with(|| output.to_string());
+            }
+        }
+    }
+    lookup.remove("");
+    Ok(lookup)
+}
+
+// the key of a code, name or alias for the iso_code operation - transliterated to ASCII,
+// lowercased & only keeping alphanumeric characters, so "U.S.A." is "usa".
+// The leading zeros of numeric codes are removed, so 4 & 004 are the same code
+fn iso_code_key(value: &str) -> String {
+    let key: String = deunicode(value)
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if key.bytes().all(|b| b.is_ascii_digit()) {
+        key.trim_start_matches('0').to_string()
+    } else {
+        key
+    }
+}
+
+// parses a coordinate in decimal degrees or DMS notation to decimal degrees.
+// See the coord operation in USAGE for the accepted formats
+fn parse_coordinate(coordinate: &str) -> Option<f64> {
+    let mut value = coordinate.trim();
+    let mut sign = 1.0_f64;
+    let mut max_degrees = 180.0_f64;
+
+    // the hemisphere can be before or after the coordinate
+    let hemisphere = match value.chars().last() {
+        Some(c) if "NSEWnsew".contains(c) => {
+            value = &value[..value.len() - 1];
+            Some(c)
+        }
+        _ => match value.chars().next() {
+            Some(c) if "NSEWnsew".contains(c) => {
+                value = &value[1..];
+                Some(c)
+            }
+            _ => None,
+        },
+    };
+    if let Some(hemisphere) = hemisphere {
+        let hemisphere = hemisphere.to_ascii_uppercase();
+        if hemisphere == 'S' || hemisphere == 'W' {
+            sign = -1.0;
+        }
+        if hemisphere == 'N' || hemisphere == 'S' {
+            max_degrees = 90.0;
+        }
+    }
+
+    value = value.trim();
+    if let Some(unsigned) = value.strip_prefix('-') {
+        // a coordinate can't have both a hemisphere and a minus sign
+        if hemisphere.is_some() {
+            return None;
+        }
+        sign = -1.0;
+        value = unsigned;
+    } else if let Some(unsigned) = value.strip_prefix('+') {
+        value = unsigned;
+    }
+
+    let parts = value
+        .split(|c: char| {
+            c.is_whitespace() || matches!(c, '°' | 'º' | '′' | '\'' | '’' | '″' | '"' | '”' | ':')
+        })
+        .filter(|part| !part.is_empty())
+        .map(str::parse::<f64>)
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    if parts.is_empty() || parts.len() > 3 {
+        return None;
+    }
+
+    let mut degrees = 0.0_f64;
+    let mut divisor = 1.0_f64;
+    for (i, &part) in parts.iter().enumerate() {
+        // only the last part can have a fractional component,
+        // and minutes & seconds must be less than 60
+        if !part.is_finite()
+            || part < 0.0
+            || (i < parts.len() - 1 && part.fract() != 0.0)
+            || (i > 0 && part >= 60.0)
+        {
+            return None;
+        }
+        degrees += part / divisor;
+        divisor *= 60.0;
+    }
+    if degrees > max_degrees {
+        return None;
+    }
+    Some(sign * degrees)
+}
+
+// the digits of an identifier, ignoring spaces & hyphens.
+// Returns None if there are other characters
+fn checkdigit_digits(value: &str) -> Option<Vec<u32>> {
+    value
+        .chars()
+        .filter(|c| *c != ' ' && *c != '-')
+        .map(|c| c.to_digit(10))
+        .collect()
+}
+
+fn luhn_valid(value: &str) -> bool {
+    let Some(digits) = checkdigit_digits(value) else {
+        return false;
+    };
+    if digits.len() < 2 {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+fn isbn_valid(value: &str) -> bool {
+    let isbn: Vec<char> = value.chars().filter(|c| *c != ' ' && *c != '-').collect();
+    match isbn.len() {
+        10 => {
+            let mut sum = 0_u32;
+            for (i, c) in isbn.iter().enumerate() {
+                let digit = match c.to_digit(10) {
+                    Some(d) => d,
+                    // X is only valid as the check digit
+                    None if i == 9 && (*c == 'X' || *c == 'x') => 10,
+                    None => return false,
+                };
+                sum += digit * (10 - i as u32);
+            }
+            sum % 11 == 0
+        }
+        13 => {
+            let Some(digits) = checkdigit_digits(&isbn.iter().collect::<String>()) else {
+                return false;
+            };
+            let sum: u32 = digits
+                .iter()
+                .enumerate()
+                .map(|(i, &d)| if i % 2 == 0 { d } else { d * 3 })
+                .sum();
+            sum % 10 == 0
+        }
+        _ => false,
+    }
+}
+
+fn iban_valid(value: &str) -> bool {
+    let iban: Vec<char> = value
+        .chars()
+        .filter(|c| *c != ' ' && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if !(15..=34).contains(&iban.len())
+        || !iban[..2].iter().all(char::is_ascii_uppercase)
+        || !iban[2..4].iter().all(char::is_ascii_digit)
+    {
+        return false;
+    }
+    // move the country code & check digits to the end, and compute the
+    // remainder piecewise, as the number is too large for any integer type
+    let mut remainder = 0_u32;
+    for c in iban[4..].iter().chain(&iban[..4]) {
+        let Some(value) = c.to_digit(36) else {
+            return false;
+        };
+        remainder = if value < 10 {
+            (remainder * 10 + value) % 97
+        } else {
+            (remainder * 100 + value) % 97
+        };
+    }
+    remainder == 1
+}
+
+// returns the part calendar attribute of date, for the CALENDAR subcommand
+fn calendar_part(date: NaiveDate, part: CalendarPart) -> String {
+    match part {
+        CalendarPart::IsoWeek => date.iso_week().week().to_string(),
+        CalendarPart::YearWeek => {
+            let iso_week = date.iso_week();
+            format!("{}-W{:02}", iso_week.year(), iso_week.week())
+        }
+        CalendarPart::Dow => date.format("%A").to_string(),
+        CalendarPart::Quarter => (date.month0() / 3 + 1).to_string(),
+        CalendarPart::Weekend => matches!(date.weekday(), Weekday::Sat | Weekday::Sun).to_string(),
+    }
+}
+
+// the cache is bounded, as user-agent columns can have a lot of distinct values.
+// returns the browser name, os & device category, in UserAgentPart order
+#[cached(
+    size = 100_000,
+    key = "String",
+    convert = r#"{ format!("{}", agent) }"#,
+    sync_writes = false
+)]
+fn useragent_cached(agent: &str) -> [String; 3] {
+    let ua_parser = woothee::parser::Parser::new();
+    let Some(parsed) = ua_parser.parse(agent) else {
+        return [
+            "unknown".to_string(),
+            "unknown".to_string(),
+            "unknown".to_string(),
+        ];
+    };
+    let known = |value: &str| {
+        if value.is_empty() || value == woothee::woothee::VALUE_UNKNOWN {
+            "unknown".to_string()
+        } else {
+            value.to_string()
+        }
+    };
+    [known(parsed.name), known(parsed.os), known(parsed.category)]
+}
+
 #[cached(
     key = "String",
     convert = r#"{ format!("{}", cell) }"#,
@@ -1234,58 +3785,46 @@ fn search_cached(cell: &str, formatstr: &str) -> Option<String> {
     let geocoder =
         GEOCODER.get_or_init(|| ReverseGeocoder::new(LOCS.get_or_init(Locations::from_memory)));
 
-    // regex for Location field. Accepts (lat, long) & lat, long
-    let locregex: &'static Regex = regex_once_cell!(
-        r"(?-u)([+-]?[0-9]+\.?[0-9]*|\.[0-9]+),\s*([+-]?[0-9]+\.?[0-9]*|\.[0-9]+)"
-    );
-
-    let loccaps = locregex.captures(cell);
-    loccaps.and_then(|loccaps| {
-        let lat = loccaps[1].to_string().parse::<f64>().unwrap_or_default();
-        let long = loccaps[2].to_string().parse::<f64>().unwrap_or_default();
-        if (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&long) {
-            let search_result = geocoder.search((lat, long));
-            search_result.map(|locdetails| {
-                #[allow(clippy::match_same_arms)]
-                // match arms are evaluated in order,
-                // so we're optimizing for the most common cases first
-                match formatstr {
-                    "%+" | "city-state" => format!(
-                        "{name}, {admin1}",
-                        name = locdetails.record.name,
-                        admin1 = locdetails.record.admin1,
-                    ),
-                    "city-country" => format!(
-                        "{name}, {cc}",
-                        name = locdetails.record.name,
-                        cc = locdetails.record.cc
-                    ),
-                    "city-state-country" | "city-admin1-country" => format!(
-                        "{name}, {admin1} {cc}",
-                        name = locdetails.record.name,
-                        admin1 = locdetails.record.admin1,
-                        cc = locdetails.record.cc
-                    ),
-                    "city" => locdetails.record.name.to_string(),
-                    "county" | "admin2" => locdetails.record.admin2.to_string(),
-                    "state" | "admin1" => locdetails.record.admin1.to_string(),
-                    "county-country" | "admin2-country" => format!(
-                        "{admin2}, {cc}",
-                        admin2 = locdetails.record.admin2,
-                        cc = locdetails.record.cc
-                    ),
-                    "county-state-country" | "admin2-admin1-country" => format!(
-                        "{admin2}, {admin1} {cc}",
-                        admin2 = locdetails.record.admin2,
-                        admin1 = locdetails.record.admin1,
-                        cc = locdetails.record.cc
-                    ),
-                    "country" => locdetails.record.cc.to_string(),
-                    _ => locdetails.record.name.to_string(),
-                }
-            })
-        } else {
-            None
-        }
+    parse_location(cell).and_then(|(lat, long)| {
+        let search_result = geocoder.search((lat, long));
+        search_result.map(|locdetails| {
+            #[allow(clippy::match_same_arms)]
+            // match arms are evaluated in order,
+            // so we're optimizing for the most common cases first
+            match formatstr {
+                "%+" | "city-state" => format!(
+                    "{name}, {admin1}",
+                    name = locdetails.record.name,
+                    admin1 = locdetails.record.admin1,
+                ),
+                "city-country" => format!(
+                    "{name}, {cc}",
+                    name = locdetails.record.name,
+                    cc = locdetails.record.cc
+                ),
+                "city-state-country" | "city-admin1-country" => format!(
+                    "{name}, {admin1} {cc}",
+                    name = locdetails.record.name,
+                    admin1 = locdetails.record.admin1,
+                    cc = locdetails.record.cc
+                ),
+                "city" => locdetails.record.name.to_string(),
+                "county" | "admin2" => locdetails.record.admin2.to_string(),
+                "state" | "admin1" => locdetails.record.admin1.to_string(),
+                "county-country" | "admin2-country" => format!(
+                    "{admin2}, {cc}",
+                    admin2 = locdetails.record.admin2,
+                    cc = locdetails.record.cc
+                ),
+                "county-state-country" | "admin2-admin1-country" => format!(
+                    "{admin2}, {admin1} {cc}",
+                    admin2 = locdetails.record.admin2,
+                    admin1 = locdetails.record.admin1,
+                    cc = locdetails.record.cc
+                ),
+                "country" => locdetails.record.cc.to_string(),
+                _ => locdetails.record.name.to_string(),
+            }
+        })
     })
 }
@@ -12,6 +12,10 @@ If you need to rearrange the columns or fix the lengths of records, use the
 data given are used. Headers in subsequent inputs are ignored. (This behavior
 can be disabled with --no-headers.)
 
+Header rows that got into the data of the inputs, e.g. when concatenating
+exports that were themselves concatenated, or whose headers were not dropped
+because of --no-headers, can be removed with --skip-repeated-headers.
+
 Usage:
     qsv cat rows    [options] [<input>...]
     qsv cat columns [options] [<input>...]
@@ -21,6 +25,12 @@ cat options:
     -p, --pad              When concatenating columns, this flag will cause
                            all records to appear. It will pad each row if
                            other CSV data isn't long enough.
+    --skip-repeated-headers
+                           When concatenating rows, drop any row that is
+                           identical to the headers of the first CSV data
+                           (ignoring leading & trailing whitespace).
+                           With --no-headers, the first row of the first
+                           CSV data is used as the headers, and is kept.
 
 Common options:
     -h, --help             Display this message
@@ -41,13 +51,14 @@ use crate::{
 
 #[derive(Deserialize)]
 struct Args {
-    cmd_rows:        bool,
-    cmd_columns:     bool,
-    arg_input:       Vec<String>,
-    flag_pad:        bool,
-    flag_output:     Option<String>,
-    flag_no_headers: bool,
-    flag_delimiter:  Option<Delimiter>,
+    cmd_rows:                   bool,
+    cmd_columns:                bool,
+    arg_input:                  Vec<String>,
+    flag_pad:                   bool,
+    flag_skip_repeated_headers: bool,
+    flag_output:                Option<String>,
+    flag_no_headers:            bool,
+    flag_delimiter:             Option<Delimiter>,
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
@@ -70,12 +81,24 @@ impl Args {
     fn cat_rows(&self) -> CliResult<()> {
         let mut row = csv::ByteRecord::new();
         let mut wtr = Config::new(&self.flag_output).writer()?;
+        // the trimmed headers to compare the rows against with --skip-repeated-headers
+        let mut headers: Option<csv::ByteRecord> = None;
         for (i, conf) in self.configs()?.into_iter().enumerate() {
             let mut rdr = conf.reader()?;
             if i == 0 {
                 conf.write_headers(&mut rdr, &mut wtr)?;
+                if self.flag_skip_repeated_headers && !self.flag_no_headers {
+                    headers = Some(trimmed(rdr.byte_headers()?));
+                }
             }
             while rdr.read_byte_record(&mut row)? {
+                if self.flag_skip_repeated_headers {
+                    match headers {
+                        Some(ref headers) if trimmed(&row) == *headers => continue,
+                        Some(_) => {}
+                        None => headers = Some(trimmed(&row)),
+                    }
+                }
                 wtr.write_byte_record(&row)?;
             }
         }
@@ -131,3 +154,10 @@ impl Args {
         wtr.flush().map_err(From::from)
     }
 }
+
+/// A copy of `record` without the whitespace around its fields
+fn trimmed(record: &csv::ByteRecord) -> csv::ByteRecord {
+    let mut trimmed = record.clone();
+    trimmed.trim();
+    trimmed
+}
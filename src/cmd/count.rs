@@ -12,6 +12,16 @@ count options:
     -H, --human-readable   Comma separate row count.
     --width                Also return the length of the longest record.
                            The count and width are separated by a semicolon.
+    --duplicates           Also return the number of distinct records and of duplicate
+                           records (i.e. the records that are not distinct), comparing
+                           the whole records, or only their --keys columns.
+                           The count, distinct & duplicate counts are separated by
+                           semicolons. Duplicates are found with a set of the hashes of
+                           the distinct records, so memory use grows with the number of
+                           distinct records (or keys) - about 32 bytes per distinct record.
+                           Cannot be used with --width.
+    --keys <cols>          The columns to compare with --duplicates.
+                           See 'qsv select --help' for the format details.
 
 Common options:
     -h, --help             Display this message
@@ -19,21 +29,32 @@ Common options:
                            the count.
 "#;
 
+use std::hash::{BuildHasher, Hash, Hasher};
+
+use ahash::{AHashSet, RandomState};
 use log::info;
 use serde::Deserialize;
 
-use crate::{config::Config, util, CliResult};
+use crate::{config::Config, select::SelectColumns, util, CliResult};
 
 #[derive(Deserialize)]
 struct Args {
     arg_input:           Option<String>,
     flag_human_readable: bool,
     flag_width:          bool,
+    flag_duplicates:     bool,
+    flag_keys:           Option<SelectColumns>,
     flag_no_headers:     bool,
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
+    if args.flag_width && args.flag_duplicates {
+        return fail_clierror!("--width and --duplicates cannot be used together.");
+    }
+    if args.flag_keys.is_some() && !args.flag_duplicates {
+        return fail_clierror!("--keys can only be used with --duplicates.");
+    }
     let conf = Config::new(&args.arg_input)
         .no_headers(args.flag_no_headers)
         // we also want to count the quotes when computing width
@@ -48,6 +69,24 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     //     &args.flag_no_headers,
     // );
 
+    if args.flag_duplicates {
+        let (count, distinct) = count_distinct(&conf, args.flag_keys.as_ref())?;
+        let duplicates = count - distinct;
+        if args.flag_human_readable {
+            use thousands::Separable;
+
+            woutinfo!(
+                "{};{};{}",
+                count.separate_with_commas(),
+                distinct.separate_with_commas(),
+                duplicates.separate_with_commas()
+            );
+        } else {
+            woutinfo!("{count};{distinct};{duplicates}");
+        }
+        return Ok(());
+    }
+
     let (count, width) = if args.flag_width {
         count_input(&conf, args.flag_width)?
     } else {
@@ -113,3 +152,48 @@ fn count_input(
     // which we also want to count when returning width
     Ok((count, max_width + record_numfields))
 }
+
+/// counts the records & the distinct records (or --keys), keeping the 128-bit
+/// hashes of the distinct records in a set
+fn count_distinct(
+    conf: &Config,
+    keys: Option<&SelectColumns>,
+) -> Result<(u64, u64), crate::clitypes::CliError> {
+    info!("counting distinct records...");
+    let mut rdr = conf.reader()?;
+    let sel = match keys {
+        Some(keys) => Some(keys.selection(rdr.byte_headers()?, !conf.no_headers)?),
+        None => None,
+    };
+
+    // two hashers with different fixed seeds, for a 128-bit hash
+    // that makes collisions between distinct records negligible
+    let hashers = (
+        RandomState::with_seeds(0x243f_6a88, 0x85a3_08d3, 0x1319_8a2e, 0x0370_7344),
+        RandomState::with_seeds(0xa409_3822, 0x299f_31d0, 0x082e_fa98, 0xec4e_6c89),
+    );
+    let mut seen: AHashSet<u128> = AHashSet::new();
+    let mut count = 0u64;
+    let mut record = csv::ByteRecord::new();
+    while rdr.read_byte_record(&mut record)? {
+        count += 1;
+        let mut hasher_hi = hashers.0.build_hasher();
+        let mut hasher_lo = hashers.1.build_hasher();
+        match sel {
+            Some(ref sel) => {
+                for field in sel.select(&record) {
+                    field.hash(&mut hasher_hi);
+                    field.hash(&mut hasher_lo);
+                }
+            }
+            None => {
+                for field in &record {
+                    field.hash(&mut hasher_hi);
+                    field.hash(&mut hasher_lo);
+                }
+            }
+        }
+        seen.insert((u128::from(hasher_hi.finish()) << 64) | u128::from(hasher_lo.finish()));
+    }
+    Ok((count, seen.len() as u64))
+}
@@ -12,6 +12,15 @@ Either way, the output will not only be deduplicated, it will also be sorted.
 
 A duplicate count will also be sent to <stderr>.
 
+With --fuzzy, rows whose selected columns only differ by case, whitespace or
+punctuation are considered duplicates, and the first row of each such group
+(in input order) is kept. The number of collapsed groups is also sent to
+<stderr>, after the duplicate count.
+
+With --count, the first row of each group of duplicates (in input order) is kept,
+with a --count-column appended to it with the number of rows in the group - i.e.
+1 for the rows without duplicates.
+
 For examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_dedup.rs.
 
 Usage:
@@ -24,6 +33,14 @@ dedup options:
                                of the CSV.
                                See 'qsv select --help' for the format details.
     -i, --ignore-case          Compare strings disregarding case.
+    --fuzzy                    Normalize the selected columns before comparing them,
+                               by lowercasing them and removing all punctuation and
+                               whitespace. The original rows are written out, keeping
+                               the first row of each group of near-duplicates.
+                               Cannot be used with --sorted.
+    --count                    Append the number of rows sharing the key of each
+                               written row to the row, in a --count-column.
+    --count-column <name>      The name of the --count column. [default: count]
     --sorted                   The input is already sorted. Do not load the CSV into
                                memory to sort it first. Meant to be used in tandem and
                                after an extsort.
@@ -48,9 +65,10 @@ Common options:
     -Q, --quiet                Do not print duplicate count to stderr.
 "#;
 
-use std::cmp;
+use std::{cmp, io};
 
 use csv::ByteRecord;
+use log::info;
 use rayon::prelude::*;
 use serde::Deserialize;
 
@@ -65,6 +83,9 @@ struct Args {
     arg_input:           Option<String>,
     flag_select:         SelectColumns,
     flag_ignore_case:    bool,
+    flag_fuzzy:          bool,
+    flag_count:          bool,
+    flag_count_column:   String,
     flag_sorted:         bool,
     flag_dupes_output:   Option<String>,
     flag_output:         Option<String>,
@@ -77,6 +98,9 @@ struct Args {
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
+    if args.flag_fuzzy && args.flag_sorted {
+        return fail_clierror!("--fuzzy cannot be used with --sorted.");
+    }
     let ignore_case = args.flag_ignore_case;
     let rconfig = Config::new(&args.arg_input)
         .delimiter(args.flag_delimiter)
@@ -94,18 +118,75 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     }
     let sel = rconfig.selection(&headers)?;
 
-    rconfig.write_headers(&mut rdr, &mut wtr)?;
+    if args.flag_count {
+        if !rconfig.no_headers {
+            let mut count_headers = headers.clone();
+            count_headers.push_field(args.flag_count_column.as_bytes());
+            wtr.write_byte_record(&count_headers)?;
+        }
+    } else {
+        rconfig.write_headers(&mut rdr, &mut wtr)?;
+    }
+    let count_flag = args.flag_count;
     let mut dupe_count = 0_usize;
+    let mut collapsed_groups = 0_usize;
+
+    if args.flag_fuzzy {
+        if let Some(path) = rconfig.path.clone() {
+            util::mem_file_check(&path, false)?;
+        }
+        util::njobs(args.flag_jobs);
+
+        let all = rdr.byte_records().collect::<Result<Vec<_>, _>>()?;
+        let mut keyed: Vec<(Vec<String>, ByteRecord)> = all
+            .into_par_iter()
+            .map(|record| (fuzzy_key(sel.select(&record)), record))
+            .collect();
+        // par_sort_by is stable, so the first row of each group
+        // is still the first one in input order
+        keyed.par_sort_by(|a, b| a.0.cmp(&b.0));
 
-    if args.flag_sorted {
+        let mut group_size = 0_usize;
+        for (current, (key, record)) in keyed.iter().enumerate() {
+            if current > 0 && keyed[current - 1].0 == *key {
+                if group_size == 1 {
+                    collapsed_groups += 1;
+                }
+                group_size += 1;
+                dupe_count += 1;
+                info!(
+                    "fuzzy duplicate of {:?}: {record:?}",
+                    keyed[current - group_size + 1].1
+                );
+                if dupes_output {
+                    dupewtr.write_byte_record(record)?;
+                }
+            } else {
+                if count_flag && current > 0 {
+                    write_counted(&mut wtr, &keyed[current - group_size].1, group_size)?;
+                } else if !count_flag {
+                    wtr.write_byte_record(record)?;
+                }
+                group_size = 1;
+            }
+        }
+        if count_flag && !keyed.is_empty() {
+            write_counted(&mut wtr, &keyed[keyed.len() - group_size].1, group_size)?;
+        }
+    } else if args.flag_sorted {
         let mut record = ByteRecord::new();
         let mut next_record = ByteRecord::new();
+        let mut group_size = 1_usize;
 
         rdr.read_byte_record(&mut record)?;
         loop {
             let more_records = rdr.read_byte_record(&mut next_record)?;
             if !more_records {
-                wtr.write_byte_record(&record)?;
+                if count_flag {
+                    write_counted(&mut wtr, &record, group_size)?;
+                } else {
+                    wtr.write_byte_record(&record)?;
+                }
                 break;
             };
             let a = sel.select(&record);
@@ -118,13 +199,19 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             match comparison {
                 cmp::Ordering::Equal => {
                     dupe_count += 1;
+                    group_size += 1;
                     if dupes_output {
                         dupewtr.write_byte_record(&record)?;
                     }
                 }
                 cmp::Ordering::Less => {
-                    wtr.write_byte_record(&record)?;
+                    if count_flag {
+                        write_counted(&mut wtr, &record, group_size)?;
+                    } else {
+                        wtr.write_byte_record(&record)?;
+                    }
                     record.clone_from(&next_record);
+                    group_size = 1;
                 }
                 cmp::Ordering::Greater => {
                     return fail_clierror!(
@@ -149,26 +236,34 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             iter_cmp(a, b)
         });
 
+        // the index of the first row of the current group of duplicates
+        let mut group_start = 0_usize;
         for (current, current_record) in all.iter().enumerate() {
             let a = sel.select(current_record);
-            if let Some(next_record) = all.get(current + 1) {
+            let dupe_of_next = all.get(current + 1).map_or(false, |next_record| {
                 let b = sel.select(next_record);
                 if ignore_case {
-                    if iter_cmp_ignore_case(a, b) == cmp::Ordering::Equal {
-                        dupe_count += 1;
-                        if dupes_output {
-                            dupewtr.write_byte_record(current_record)?;
-                        }
-                    } else {
-                        wtr.write_byte_record(current_record)?;
-                    }
-                } else if iter_cmp(a, b) == cmp::Ordering::Equal {
+                    iter_cmp_ignore_case(a, b) == cmp::Ordering::Equal
+                } else {
+                    iter_cmp(a, b) == cmp::Ordering::Equal
+                }
+            });
+            if count_flag {
+                // with --count, the first row of the group is kept instead of the last
+                if current > group_start {
                     dupe_count += 1;
                     if dupes_output {
                         dupewtr.write_byte_record(current_record)?;
                     }
-                } else {
-                    wtr.write_byte_record(current_record)?;
+                }
+                if !dupe_of_next {
+                    write_counted(&mut wtr, &all[group_start], current - group_start + 1)?;
+                    group_start = current + 1;
+                }
+            } else if dupe_of_next {
+                dupe_count += 1;
+                if dupes_output {
+                    dupewtr.write_byte_record(current_record)?;
                 }
             } else {
                 wtr.write_byte_record(current_record)?;
@@ -187,13 +282,47 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         use thousands::Separable;
 
         eprintln!("{}", dupe_count.separate_with_commas());
+        if args.flag_fuzzy {
+            eprintln!("{}", collapsed_groups.separate_with_commas());
+        }
     } else {
         eprintln!("{dupe_count}");
+        if args.flag_fuzzy {
+            eprintln!("{collapsed_groups}");
+        }
     }
 
     Ok(())
 }
 
+/// Write `record` with its --count appended
+fn write_counted<W: io::Write>(
+    wtr: &mut csv::Writer<W>,
+    record: &ByteRecord,
+    count: usize,
+) -> CliResult<()> {
+    let mut counted_record = record.clone();
+    counted_record.push_field(itoa::Buffer::new().format(count).as_bytes());
+    Ok(wtr.write_byte_record(&counted_record)?)
+}
+
+/// Normalize the selected fields for --fuzzy, lowercasing them and
+/// removing everything that is not alphanumeric
+fn fuzzy_key<'a, I>(fields: I) -> Vec<String>
+where
+    I: Iterator<Item = &'a [u8]>,
+{
+    fields
+        .map(|field| {
+            String::from_utf8_lossy(field)
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect()
+        })
+        .collect()
+}
+
 /// Try comparing `a` and `b` ignoring the case
 #[inline]
 pub fn iter_cmp_ignore_case<'a, L, R>(mut a: L, mut b: R) -> cmp::Ordering
@@ -1,6 +1,6 @@
 static USAGE: &str = r#"
 Exports a specified Excel/ODS sheet to a CSV file.
-The first row of a sheet is assumed to be the header row.
+The first row of a sheet is assumed to be the header row, unless --detect-header-row is set.
 
 NOTE: Excel stores dates as number of days since 1900.
 https://support.microsoft.com/en-us/office/date-systems-in-excel-e7fe7167-48a9-4b96-bb53-5612a800b487
@@ -18,21 +18,89 @@ not all columns with numeric values.
 Note however that with XLSX files, qsv will automatically process a cell as a date, even if its
 not its not in the --dates-whitelist, if the cell's format has been explicitly set to date.
 
+Excel (xls, xlsx, xlsm & xlsb) and ODS workbooks are supported, as are their templates
+(xlt, xltx, xltm & ots), which are read like the workbooks of the same format.
+
+If <input> is - or is not set, the workbook is read from stdin, with its format detected
+from its content.
+
+Several <input> workbooks (e.g. data/*.xlsx) are exported into a single output, in the
+order they are given, like the sheets of a --sheet list: the --sheet of each workbook is
+exported, and the header row is only written once, from the first workbook. Workbooks whose
+header row is not the same as the first workbook's are an error, unless --flexible is set.
+Several workbooks cannot be used with --all-sheets, --split-by, --summary-json, --schema,
+--count-only, --dump-types, the listing options (--metadata, --list-sheets,
+--list-named-ranges & --properties), the {sheet} placeholder of --output or stdin.
+
+NOTE: each exported sheet is read into memory in full before it is exported, as calamine
+(the library used to read the workbooks) doesn't stream the cells of a sheet. --dimension,
+--range & --transpose move the cells of the sheet into the reshaped sheet instead of copying
+them, but the peak memory use still grows with the size of the sheet.
+
 For examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_excel.rs.
 
 Usage:
-    qsv excel [options] [<input>]
+    qsv excel [options] [<input>...]
     qsv excel --help
 
 Excel options:
     -s, --sheet <name/index>   Name or zero-based index of sheet to export.
+                               Names are matched ignoring case, with a sheet named
+                               exactly <name> taking priority.
                                Negative indices start from the end (-1 = last sheet). 
-                               If the sheet cannot be found, qsv will read the first sheet.
-                               [default: 0]
+                               It is an error if the index is out of range.
+                               If the sheet cannot be found, qsv will read the first sheet,
+                               unless --strict-sheet is set.
+                               Several sheets can be exported as a comma-separated list of
+                               names and/or indices (e.g. Sheet1,Sheet3,-1), with the sheets
+                               concatenated in the list order, and only the header row of
+                               the first sheet exported. Sheets whose header row is not the
+                               same as the first sheet's are an error, unless --flexible is
+                               set. If --output has a {sheet} placeholder, each sheet is
+                               exported to its own file instead.
+                               If not set, the first sheet (index 0) is exported.
+    --sheet-regex <pattern>    Export all the sheets whose name matches the regex <pattern>
+                               (e.g. "^2023-Q[1-4]$"), in workbook order, concatenated like
+                               a --sheet list. The source_sheet column of --with-sheet-name
+                               is always added, so the rows can be traced back to their
+                               sheet. It is an error if no sheet matches.
+                               Cannot be used with --sheet, --all-sheets or a named --range.
+    --with-sheet-name          Add a source_sheet first column, with the name of the sheet
+                               each row was exported from.
+    --with-filename            Add a source_file first column, with the path of the <input>
+                               workbook each row was exported from, as it was given (e.g.
+                               with several workbooks). It comes before the source_sheet
+                               column of --with-sheet-name.
+    --strict-sheet             Fail if the --sheet name cannot be found or if the --sheet
+                               index is out of range, listing the available sheet names,
+                               instead of reading the first sheet.
+    --on-duplicate-sheet-name <action>  What to do when more than one sheet has the --sheet
+                               name (ignoring case), which can happen with merged or
+                               imported workbooks:
+                                 error - fail, listing the indices of the sheets, so one of
+                                         them can be exported with its --sheet index.
+                                 first - export the first of the sheets.
+                               With --metadata, a warning is sent to stderr for each
+                               duplicate sheet name.
+                               [default: error]
+    --all-sheets               Export all the sheets of the workbook, including the hidden
+                               sheets, instead of the --sheet sheet. --output is then the
+                               existing directory to write the sheets to, with one CSV file
+                               per sheet, named after the sheet (e.g. "Sales Data.csv").
+                               Sheet names are sanitized for the filesystem like the {sheet}
+                               placeholder of --output, with a numeric suffix added to the
+                               names that would otherwise be the same (e.g. Data_2.csv).
+                               Cannot be used with --range or --summary-json.
+    -j, --jobs <arg>           The number of sheets to export concurrently with --all-sheets,
+                               each with its own handle on the workbook. If not set, or set
+                               to 1, the sheets are exported one after the other.
+                               Set to 0 to use the number of CPUs detected.
     --metadata <c|j|J>         Outputs workbook metadata in CSV or JSON format: 
                                  index, sheet_name, headers, num_columns, num_rows, safe_headers,
-                                 safe_headers_count, unsafe_headers, unsafe_headers_count and
-                                 duplicate_headers_count.
+                                 safe_headers_count, unsafe_headers, unsafe_headers_count,
+                                 duplicate_headers_count and hidden.
+                               hidden is true if the sheet is hidden. Sheet visibility is only
+                               detected for xlsx/xlsm files, and is always false otherwise.
                                headers is a list of the first row which is presumed to be the header row.
                                num_rows includes all rows, including the first row.
                                safe_headers is a list of header with "safe"(database-ready) names.
@@ -48,10 +116,294 @@ Excel options:
                                
                                All other Excel options are ignored.
                                [default: none]
+    --range <range>            The A1-style range of the sheet to export (e.g. C3:T25), whose
+                               first row is the header row. Leave out the end of the range
+                               (e.g. C3:) to export from that cell to the end of the sheet.
+                               It is an error if the range lies entirely outside of the
+                               sheet's data. Set to @<name> to export a named
+                               range of the workbook (e.g. @SalesData), using the sheet the
+                               name refers to instead of --sheet. Named ranges are matched
+                               case-insensitively, and must refer to a single range.
+    --list-named-ranges        List the named ranges (defined names) of the workbook in CSV
+                               format, with the name, sheet & range (e.g. A1:D10) of each,
+                               instead of exporting a sheet. Names that do not refer to a single
+                               range (e.g. constants & formulas) have an empty sheet & range.
+    --list-sheets              List the sheets of the workbook in CSV format, with the index,
+                               sheet_name, rows, columns & hidden status of each, instead of
+                               exporting a sheet, to quickly find the sheet to export. Like
+                               with --metadata, sheet visibility is only detected for
+                               xlsx/xlsm files, and is always false otherwise.
+    --json                     With --list-sheets, list the sheets as a JSON array of
+                               {index, name, hidden, rows, cols} objects instead of CSV.
+    --properties               List the document properties of the workbook in CSV format, as
+                               property & value pairs, instead of exporting a sheet: its format,
+                               sheet_count & date_system, followed by the properties set in the
+                               workbook (e.g. title, creator, lastModifiedBy, created, modified,
+                               Application & Company), for a quick provenance check.
+                               Document properties are only read from xlsx/xlsm files.
+                               Cannot be used with --list-sheets, --list-named-ranges or
+                               --metadata.
+    --count-only               Only count the data rows that would be exported (e.g. with
+                               --skip-empty or --row-filter), without writing them, and output
+                               the count instead - or with several sheets (e.g. --all-sheets),
+                               a CSV with the sheet & rows of each sheet. With --all-sheets,
+                               --output is not needed, and is the file to write the counts to.
+                               Cannot be used with --split-by, --append or --dump-types.
+    --dump-types               Instead of exporting the sheet, output a CSV with the number of
+                               cells of each type of each column, to spot the stray values of
+                               a column before exporting it, with the column_name, ints,
+                               floats, strings, dates, bools, errors & empties columns.
+                               The header row options are used to name the columns, and only
+                               the data rows are counted. With several sheets, a first sheet
+                               column has the name of the sheet of each column.
+                               Cannot be used with --jsonl, --split-by, --all-sheets, --schema
+                               or the {sheet} placeholder of --output.
+    --dimension <arg>          The extent of the sheet to export:
+                                 used - the range of the cells found in the sheet.
+                                 full - the sheet's declared dimension. Only xlsx/xlsm files
+                                        declare their dimension. For the other formats, the
+                                        range from the first cell (A1) to the end of the used
+                                        range, including leading empty rows & columns.
+                                 auto - the tightest range that contains all the non-empty
+                                        cells, dropping formatted but empty rows & columns.
+                               [default: used]
+    --skip-hidden-rows         Do not export the rows that are hidden in the sheet (e.g. rows
+                               hidden manually or by a filter), like what is seen on screen.
+                               The header row is always exported.
+    --skip-hidden-cols         Do not export the columns that are hidden in the sheet.
+                               Hidden rows & columns are only detected for xlsx/xlsm files,
+                               and everything is exported for the other formats.
+    --merge-fill               Fill the cells of each merged cell region with the value of its
+                               top-left cell, instead of exporting them empty (e.g. a category
+                               label merged across several rows). Merged cells are only read
+                               from xlsx/xlsm files, and this is a no-op for the other formats
+                               (e.g. ODS).
+    --hyperlinks <mode>        How the cells with a hyperlink are exported:
+                                 keep - the displayed text of the cell.
+                                 url  - the hyperlink target (e.g. https://qsv.dathere.com),
+                                        or the location for links within the workbook
+                                        (e.g. Sheet2!A1).
+                                 both - the displayed text followed by the target in
+                                        parentheses (e.g. qsv (https://qsv.dathere.com)).
+                               The cells without a hyperlink are exported as their text.
+                               Hyperlinks are only read from xlsx/xlsm files.
+                               [default: keep]
+    --comments-column <name>   Add a last column named <name> with the comments (notes) of
+                               the cells of each data row, prefixed by their cell reference
+                               and separated by "; " (e.g. B5: looks wrong; D5: check).
+                               The column is empty for the rows without comments.
+                               Comments are only read from xlsx/xlsm files, so the column
+                               is always empty for the other formats. Threaded comments are
+                               exported from the notes Excel keeps for them.
+                               Cannot be used with --transpose.
+    --keep-text-format         Export the numeric cells whose number format only has zeros
+                               (e.g. 00000 for ZIP codes or 000000 for account numbers) with
+                               their leading zeros, like they are displayed (e.g. 07030
+                               instead of 7030). Number formats are only read from xlsx/xlsm
+                               files, as calamine doesn't expose them for the other formats
+                               (e.g. ODS). Numbers entered in text-formatted cells are already
+                               exported as their text.
+    --percent-format <mode>    How the numeric cells with a percentage number format (e.g. 0%
+                               or 0.00%) are exported:
+                                 raw      - the stored value (e.g. 0.25).
+                                 times100 - the value multiplied by 100 (e.g. 25).
+                                 sign     - the value like it is displayed, multiplied by 100
+                                            and rounded to the decimals of the number format,
+                                            followed by a percent sign (e.g. 25%).
+                               Number formats are only read from xlsx/xlsm files, so all the
+                               values are exported raw for the other formats (e.g. ODS).
+                               [default: raw]
+    --elapsed-time             Export the date cells that are times of day (serials below 1)
+                               and the cells with an elapsed time number format
+                               (e.g. [h]:mm:ss) as HH:MM:SS (e.g. 36:00:00), instead of a date
+                               in 1899/1900. Elapsed time formats are only read from xlsx/xlsm
+                               files, but times of day are detected for all the formats.
+    --max-rows <n>             Stop exporting each sheet after <n> data rows, not counting the
+                               header row, to quickly preview large sheets. The rows that are
+                               not exported (e.g. with --skip-empty or --row-filter) don't
+                               count toward the limit.
+    --skip-empty               Do not export the rows whose cells are all empty or only
+                               whitespace (e.g. blank spacer rows), instead of exporting them
+                               as rows of empty fields. The header row is always exported.
+    --apply-autofilter         Only export the rows matching the active AutoFilter of the sheet,
+                               like what its author sees, by evaluating its criteria:
+                                 value lists - the cell is one of the checked values (ignoring
+                                               case), or is blank if (Blanks) is checked.
+                                 custom filters - one or two comparisons (equals, does not
+                                               equal, greater than, less than or equal...),
+                                               combined with And or Or. Numbers are compared
+                                               as numbers, and text ignoring case, with the
+                                               * and ? wildcards for (does not) equal.
+                               Only the rows of the AutoFilter range are filtered. The other
+                               criteria (e.g. date groups, top 10, dynamic, color & icon
+                               filters) cannot be interpreted and are ignored, with a warning.
+                               AutoFilters are only read from xlsx/xlsm files, and all the rows
+                               are exported with a warning for the other formats.
     --flexible                 Continue even if the number of columns is different 
                                from the previous record.
+    --pad-rows                 Right-pad the data rows that are shorter than the header row
+                               with empty fields (or the --empty-token), and truncate the ones
+                               that are longer, so every row has the header's field count.
+                               Useful with --flexible, to export the concatenated sheets of
+                               different widths as a rectangular CSV.
     --trim                     Trim all fields so that leading & trailing whitespaces are removed.
-                               Also removes embedded linebreaks.
+                               Also removes embedded linebreaks, unless --keep-newlines is set.
+    --keep-newlines            With --trim or --trim-columns, keep the embedded linebreaks of
+                               the trimmed fields (e.g. multiline notes), which are quoted in
+                               the CSV output, instead of replacing them with spaces. Only
+                               the leading & trailing whitespace is removed.
+    --trim-columns <arg>       Only trim the selected columns (e.g. "notes,comment"), like
+                               --trim, leaving the whitespace of the other columns untouched.
+                               The columns are resolved against the trimmed names of the
+                               header row, which is always trimmed.
+                               See 'qsv select --help' for the format details.
+    --jsonl                    Export newline-delimited JSON instead of CSV, with one JSON
+                               object per data row, keyed by the names of the header row.
+                               Numeric cells are JSON numbers, boolean cells JSON booleans,
+                               dates ISO 8601 strings (or --date-format strings) & empty
+                               cells null. The other cells are JSON strings.
+                               With --all-sheets, the sheets are exported to .jsonl files.
+    --detect-header-row        Scan the first 20 rows of the sheet for the header row, skipping
+                               any preamble rows (titles, notes, etc.) above it.
+                               The header row is the first row whose cells are all non-empty
+                               strings, followed by a non-empty row that is not wider than it.
+                               If no such row is found, the first row is used.
+    --no-headers               The sheet has no header row, so its first row is exported as
+                               data, under a synthesized header row with the 1-based column
+                               numbers of the columns (col_1, col_2, etc.).
+                               The name patterns of --dates-whitelist are then ignored, as
+                               there are no column names to match, but its column indices
+                               still apply. Cannot be used with --header-row or
+                               --detect-header-row.
+    --auto-widen               Give a header name to the trailing columns that have data but no
+                               header cell, after their 1-based column number (e.g. col_8,
+                               col_9), instead of leaving their header names empty. The
+                               rows are always as wide as the widest row of the sheet, so the
+                               output is rectangular. The synthesized headers are logged.
+    --header-row <n>           The zero-based index of the header row, skipping all the
+                               preamble rows (titles, logos, etc.) above it. It is relative
+                               to the --range, if set. Cannot be used with --detect-header-row.
+                               [default: 0]
+    --dedup-headers            Make the exported header names unique, by appending _2, _3, etc.
+                               to the names that are repeated in the header row (e.g. Amount,
+                               Amount => Amount, Amount_2). The renames are logged.
+    --header-case <case>       Normalize the case of the exported header names - lower, upper,
+                               snake (e.g. "First Name" => first_name) or camel
+                               (e.g. "First Name" => firstName), or none to keep the names as
+                               they are, like when not set. For snake & camel, the names
+                               are split into words at non-alphanumeric characters and at
+                               lowercase to uppercase transitions. The --dates-whitelist
+                               matches the original names, while --row-filter & --concat
+                               use the normalized names.
+    --select <arg>             Only export the selected columns, resolved against the header
+                               row - by name, 1-based index or range (e.g. "name,3-5").
+                               See 'qsv select --help' for the format details.
+                               Named columns match the exported (--header-case) names, and
+                               it is an error if they're not in the header row. The columns
+                               are exported in the order of the sheet, once each. The
+                               --dates-whitelist is only checked for the selected columns.
+    --transpose                Pivot sideways sheets, with the field names down the first
+                               column and the values in the next columns (e.g. property
+                               sheets), so the first column becomes the header row and each
+                               of the next columns a row. With --range, only the range is
+                               transposed. The header row options are applied after
+                               transposing, so --header-row & --detect-header-row pick the
+                               header column instead (e.g. --header-row 1 for column B of
+                               the range). Cannot be used with --skip-hidden-rows,
+                               --skip-hidden-cols, --apply-autofilter, --formulas or
+                               --formulas-with-values.
+    --output-precision <n>     The number of decimal places to format non-date float values to.
+                               Whole numbers are still formatted without a decimal point.
+                               If not set, floats are formatted with full precision.
+    --trim-trailing-zeros      Remove trailing zeros from floats formatted with --output-precision.
+    --coerce                   Coerce the cells of mixed-type columns to the majority type of
+                               the column's non-empty cells (ties go to text).
+                               In numeric columns, text cells that are numbers (e.g. " 1,234 ")
+                               are converted to numbers. The remaining text cells are outliers
+                               and are left as-is.
+                               In text columns, numeric cells are written as plain decimals,
+                               without date conversion or --output-precision formatting.
+                               The coerced & outlier cell counts are sent to stderr.
+    --coerce-numeric-strings   Write the text cells that are numbers once trimmed (e.g. " 1234 ")
+                               as the trimmed number, leaving the other text cells as-is.
+                               Unlike --coerce, thousands separators are not allowed, and the
+                               number is written as it is in the cell (e.g. 1234.50 stays 1234.50).
+                               The coerced cell count is sent to stderr.
+    --empty-token <str>        The value to write for empty cells (e.g. \N for PostgreSQL's COPY,
+                               or NULL), so they can be told apart from the text cells that are
+                               empty strings, which are still written as empty fields.
+                               With --trim, the text cells that are empty or only whitespace
+                               are written as the token too.
+                               If not set, empty cells are written as empty fields.
+    --bool-format <true/false> How boolean cells are written, as the true & false values
+                               separated by a slash - e.g. true/false, 1/0, Y/N, TRUE/FALSE
+                               or any other pair of values (e.g. yes/no).
+                               The following presets are also available:
+                                 lower - true/false
+                                 upper - TRUE/FALSE, as Excel displays them
+                                 tf    - T/F
+                                 10    - 1/0
+                               [default: true/false]
+    --max-cell-length <n>      Truncate the text cells of the exported rows that are longer
+                               than <n> characters to their first <n> characters, as a safety
+                               valve against huge cells (e.g. notes or embedded data).
+                               The number of truncated cells is sent to stderr.
+                               If not set, cells are never truncated.
+    --ellipsis <str>           The text to append to the cells truncated by
+                               --max-cell-length (e.g. ...).
+    --error-format <mode>      How error cells are written:
+                                 debug - the name of the error (e.g. Div0, NA)
+                                 excel - the error as displayed by Excel (e.g. #DIV/0!, #N/A)
+                                 none - an empty string
+                               [default: debug]
+    --fail-on-error            Fail at the first error cell of the exported data rows (e.g.
+                               #DIV/0! or #REF!), with its cell reference & error, instead of
+                               writing it with --error-format, to reject broken workbooks.
+    --keep-serial <layout>     For each date-whitelisted column, also keep the raw Excel serial
+                               number of the column's numeric cells (e.g. 40729 for 2011-07-05),
+                               so date conversions can be checked. Non-numeric cells leave the
+                               serial blank. The layouts are:
+                                 serial - keep the converted date in <col>, and add the serial
+                                          in a <col>_serial column right after it
+                                 date - keep the serial in <col>, and add the converted date
+                                        in a <col>_date column right after it
+    --row-filter <condition>   Only export the rows matching a condition on a column, given
+                               by its name in the header row:
+                                 <column>==<value> - the cell is equal to <value>
+                                 <column>!=<value> - the cell is not equal to <value>
+                                 <column>~=<value> - the cell contains <value>
+                                 <column>          - the cell is not empty
+                               e.g. --row-filter "status==active". The condition is checked
+                               against the exported (formatted) cell, and is case-sensitive.
+                               For more complex conditions, use the search command instead.
+    --concat <specs>           Concatenate the cells of columns into new columns, given as
+                               ;-separated specs of the source columns, joined by +, and the
+                               name of the new column - e.g. "first+last=full_name" or
+                               "area_code+number=phone;street+city=address". The columns are
+                               given by their name in the header row, and the new columns are
+                               appended to the end of the rows, with the non-empty exported
+                               (formatted) cells of the source columns joined by --concat-sep.
+    --concat-sep <sep>         The separator between the cells concatenated by --concat.
+                               (default: a space)
+    --concat-drop              Do not export the source columns of --concat.
+    --formulas-with-values     For each column with formula cells, add a <col>_formula column
+                               right after it, with the formula text (e.g. =SUM(B2:B10)) of
+                               the column's formula cells. The value column still has the
+                               cached values of the formulas. Non-formula cells leave the
+                               formula column blank.
+                               Formula text is only read from xlsx, xlsm & ods files.
+                               For xls & xlsb files, or when the formulas of a sheet cannot
+                               be read, only the cached values are exported, with a warning.
+    --formulas                 Export the formula text (e.g. =SUM(A1:A10)) of the formula
+                               cells instead of their cached values. The other cells are
+                               exported as usual, but without --dates-whitelist processing.
+                               Like --formulas-with-values, formulas are only read from xlsx,
+                               xlsm & ods files, and only the cached values are exported, with
+                               a warning, when they cannot be read.
+                               Cannot be used with --formulas-with-values.
+    --strict-formulas          Fail instead of only exporting the cached values when the
+                               formulas cannot be read with --formulas-with-values or
+                               --formulas.
     --dates-whitelist <list>   The case-insensitive patterns to look for when 
                                shortlisting columns for date processing.
                                i.e. if the column's name has any of these patterns,
@@ -66,36 +418,642 @@ Excel options:
 
                                Conversely, set to "none" to stop date processing altogether.
 
-                               If the list is all integers, its interpreted as the zero-based
-                               index of all the date columns for date processing.
+                               The integers of the list are interpreted as the zero-based
+                               indices of date columns, and can be combined with patterns
+                               (e.g. 2,5,due for the 3rd & 6th columns, plus the columns
+                               whose names contain "due").
+
+                               If the list starts with @, the rest is the path of a file to read
+                               the whitelist from (e.g. @date-columns.txt), with one pattern or
+                               column index per line. Empty lines and lines starting with #
+                               are ignored.
+
+                               "all" & "none" take precedence. Otherwise, a column is a date
+                               column if its index is in the list, or if its name contains one
+                               of the patterns of the list (or matches one of them, with
+                               --dates-whitelist-regex). Unless --quiet is set, a warning is
+                               sent to stderr for each index or pattern of a non-default list
+                               that matches no column, to catch typos.
                                [default: date,time,due,open,close,created]                               
+    --dates-whitelist-regex    Interpret each pattern of --dates-whitelist as a regex matched
+                               against the lowercased column name (e.g. "_dt$,^received_"),
+                               instead of a substring, so "date" need not match update_flag.
+    --strict-whitelist         Fail if a column index of the --dates-whitelist is beyond the
+                               last column of the sheet (e.g. copied from another sheet's
+                               layout), instead of only warning about it.
+    --infer-dates              Instead of matching the column names with --dates-whitelist,
+                               infer the date columns from their values, by sampling the
+                               first --infer-dates-sample non-empty cells below the header row
+                               of each column. A column is a date column when at least 90% of
+                               its sampled cells are numbers between 7306 (1920-01-01) and
+                               60000 (2064-04-08), the plausible serial numbers of Excel
+                               dates, or cells formatted as dates. Small numbers like
+                               quantities are thus not inferred as dates. Cannot be used with --formulas.
+    --infer-dates-sample <n>   The number of non-empty cells of each column to sample
+                               with --infer-dates. [default: 100]
+    --date-format <fmt>        The strftime format of the exported dates & datetimes
+                               (e.g. %m/%d/%Y). For formats, see
+                               https://docs.rs/chrono/latest/chrono/format/strftime/
+                               Date cells with a fractional part (i.e. a time of day) are
+                               datetimes, and whole numbers are dates at midnight.
+                               If not set, dates are exported in the ISO 8601 format
+                               (e.g. 2023-03-03 & 2023-03-03 14:30:00).
+    --datetime-threshold <e>   The date cells whose fractional part is within <e> of a whole
+                               number are dates instead of datetimes, as floating-point
+                               round-tripping can leave tiny fractions in date-only serials
+                               (e.g. 40729.0000000001 is 2011-07-05, not 2011-07-05 00:00:00).
+                               Set to 0 to only treat whole numbers as dates.
+                               [default: 0.000000001]
+    --on-date-error <mode>     How the date cells that cannot be converted to a date or
+                               datetime (e.g. out of range serial numbers) are written:
+                                 error-string - an ERROR: Cannot convert <serial> to date text
+                                 passthrough  - the serial number, as a number
+                                 empty        - an empty field
+                                 fail         - fail, with the cell reference of the cell
+                               [default: error-string]
+    --assume-timezone <tz>     The timezone of the datetimes of the workbook, which Excel stores
+                               without one, as an IANA name (e.g. America/New_York) or UTC.
+                               The datetimes are then exported with their UTC offset
+                               (e.g. 2023-03-03 14:30:00-05:00), unless --date-format is set,
+                               which can use the timezone specifiers (e.g. %z or %Z).
+                               Dates without a time of day are not converted.
+    --output-timezone <tz>     Convert the datetimes to the timezone <tz> before formatting
+                               them, from the --assume-timezone, or from UTC if it is not set.
+    --date-system <system>     The date system of the workbook's date serial numbers -
+                               1900 (serial 1 is 1900-01-01, the Windows Excel default),
+                               1904 (serial 0 is 1904-01-01, used by older Mac Excel files)
+                               or auto. With auto, the date system set in the workbook is
+                               used for xlsx/xlsm files, and 1900 for the other formats.
+                               [default: auto]
 
 Common options:
     -h, --help                 Display this message
     -o, --output <file>        Write output to <file> instead of stdout.
-    -Q, --quiet                Do not display export summary message.
+                               If <file> has a {sheet} placeholder, it is replaced by the
+                               name of the exported sheet (e.g. --output "exports/{sheet}.csv").
+                               Characters that are not allowed in filenames (/ \ : * ? " < > |
+                               and control characters) are replaced by underscores, and
+                               trailing dots & spaces are removed. Windows device names
+                               (e.g. CON or NUL) get an underscore (e.g. CON_). A warning is
+                               sent to stderr when the sheet name is changed.
+    -d, --delimiter <arg>      The field delimiter for writing the CSV output, e.g. ; or |.
+                               Must be a single character, or \t for tab. (default: ,)
+    --raw-sheet-filenames      Use the sheet name as-is for the {sheet} placeholder of
+                               --output, without replacing illegal filename characters.
+    --split-by <column>        Write the rows to one file per value of <column>, given by its
+                               name in the header row, instead of a single output. --output
+                               is then the existing directory to write the files to, named
+                               after the values (e.g. "Sales.csv", or "Sales.jsonl" with
+                               --jsonl), sanitized like the {sheet} placeholder of --output.
+                               Rows with an empty value go to "empty.csv". Each file has the
+                               header row. The files & their row counts are sent to stderr.
+                               Values longer than 240 bytes are truncated in the filenames.
+                               Can only be used when exporting a single sheet, and cannot be
+                               used with --all-sheets or the {sheet} placeholder.
+    --split-open-files <n>     The maximum number of --split-by files kept open at once. When
+                               another file is needed, the least recently written one is closed,
+                               and it is reopened to append to it when its value shows up again.
+                               [default: 100]
+    --summary-json <file>      Write a JSON report of the export to <file> at the end of
+                               the run, with the filename, format, sheet name & index, output
+                               path, number of rows exported (not counting the header row)
+                               and of columns, the date-whitelisted column names, and the
+                               number of error cells (e.g. #DIV/0!), in total & by error type.
+                               The report is written even with --quiet.
+    --stats-json               Send the report of --summary-json of each exported sheet to
+                               stderr instead of the export summary message, as a JSON object
+                               on a single line, so scripts can check the row counts without
+                               parsing the message. Unlike --summary-json, it can be used when
+                               exporting several sheets, with one line per sheet.
+                               The reports are sent even with --quiet.
+    --schema <file>            Write a JSON schema of the exported sheet to <file>, with the
+                               sheet name and the name, type & date flag of each exported
+                               column of the sheet. The type is the type of the column's
+                               non-empty data cells - int (whole numbers), float, date, bool
+                               or string - or float if the column has both ints & floats,
+                               and string if it has several other types or only empty cells.
+                               The date flag is whether the column was processed as a date
+                               column (e.g. with --dates-whitelist). The CSV output is the same.
+                               Can only be used when exporting a single sheet, and cannot be
+                               used with --dump-types.
+    --bom                      Start the CSV output with a UTF-8 byte order mark (BOM), so
+                               Excel reads its non-ASCII characters correctly when opening it.
+                               The BOM is not written when appending to a non-empty file with
+                               --append, nor with --jsonl.
+    --quote-all                Quote all the fields of the CSV output, for the strict CSV
+                               parsers that require it, instead of only quoting the fields
+                               that need it (e.g. with a delimiter, quote or newline).
+    --append                   Append the exported rows to the --output file instead of
+                               overwriting it, without the header row if the file is not
+                               empty, e.g. to export several workbooks into one CSV file.
+                               The columns must be in the same order as in the file, which
+                               is not checked. Cannot be used with --all-sheets, --split-by
+                               or the {sheet} placeholder of --output.
+    -Q, --quiet                Do not display export summary message. It is still logged
+                               at the info level.
+    -p, --progressbar          Show progress bars, with the progress of the rows of each
+                               exported sheet.
 "#;
 
-use std::{cmp, path::PathBuf};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write as _,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+};
 
-use calamine::{open_workbook_auto, DataType, Range, Reader};
+use calamine::{
+    open_workbook, open_workbook_auto, CellErrorType, DataType, Ods, Range, Reader, Sheets, Xls,
+    Xlsx,
+};
+use chrono::{NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+#[cfg(any(feature = "full", feature = "lite"))]
+use indicatif::{HumanCount, ProgressBar, ProgressDrawTarget};
 use itertools::Itertools;
 use log::info;
+use quick_xml::events::{BytesStart, Event};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use thousands::Separable;
+use zip::{read::ZipFile, result::ZipError, ZipArchive};
 
-use crate::{config::Config, util, CliResult};
+use crate::{
+    config::{Config, Delimiter},
+    select::{NormalSelection, SelectColumns},
+    util, CliResult,
+};
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 struct Args {
-    arg_input:            String,
-    flag_sheet:           String,
-    flag_metadata:        String,
-    flag_flexible:        bool,
-    flag_trim:            bool,
-    flag_dates_whitelist: String,
-    flag_output:          Option<String>,
-    flag_quiet:           bool,
+    arg_input:                    Vec<String>,
+    flag_sheet:                   Option<String>,
+    flag_sheet_regex:             Option<String>,
+    flag_strict_sheet:            bool,
+    flag_with_sheet_name:         bool,
+    flag_with_filename:           bool,
+    flag_on_duplicate_sheet_name: String,
+    flag_all_sheets:              bool,
+    flag_jobs:                    Option<usize>,
+    flag_metadata:                String,
+    flag_range:                   Option<String>,
+    flag_list_named_ranges:       bool,
+    flag_list_sheets:             bool,
+    flag_json:                    bool,
+    flag_properties:              bool,
+    flag_count_only:              bool,
+    flag_dump_types:              bool,
+    flag_dimension:               String,
+    flag_skip_hidden_rows:        bool,
+    flag_skip_hidden_cols:        bool,
+    flag_merge_fill:              bool,
+    flag_hyperlinks:              String,
+    flag_comments_column:         Option<String>,
+    flag_keep_text_format:        bool,
+    flag_percent_format:          String,
+    flag_elapsed_time:            bool,
+    flag_skip_empty:              bool,
+    flag_max_rows:                Option<usize>,
+    flag_apply_autofilter:        bool,
+    flag_flexible:                bool,
+    flag_pad_rows:                bool,
+    flag_trim:                    bool,
+    flag_trim_columns:            Option<SelectColumns>,
+    flag_keep_newlines:           bool,
+    flag_jsonl:                   bool,
+    flag_detect_header_row:       bool,
+    flag_header_row:              usize,
+    flag_no_headers:              bool,
+    flag_auto_widen:              bool,
+    flag_dedup_headers:           bool,
+    flag_header_case:             Option<String>,
+    flag_select:                  Option<SelectColumns>,
+    flag_transpose:               bool,
+    flag_output_precision:        Option<usize>,
+    flag_trim_trailing_zeros:     bool,
+    flag_coerce:                  bool,
+    flag_coerce_numeric_strings:  bool,
+    flag_empty_token:             Option<String>,
+    flag_max_cell_length:         Option<usize>,
+    flag_ellipsis:                Option<String>,
+    flag_formulas_with_values:    bool,
+    flag_formulas:                bool,
+    flag_strict_formulas:         bool,
+    flag_bool_format:             String,
+    flag_error_format:            String,
+    flag_fail_on_error:           bool,
+    flag_row_filter:              Option<String>,
+    flag_concat:                  Option<String>,
+    flag_concat_sep:              Option<String>,
+    flag_concat_drop:             bool,
+    flag_keep_serial:             Option<String>,
+    flag_dates_whitelist:         String,
+    flag_dates_whitelist_regex:   bool,
+    flag_strict_whitelist:        bool,
+    flag_infer_dates:             bool,
+    flag_infer_dates_sample:      usize,
+    flag_date_format:             Option<String>,
+    flag_datetime_threshold:      f64,
+    flag_on_date_error:           String,
+    flag_assume_timezone:         Option<String>,
+    flag_output_timezone:         Option<String>,
+    flag_date_system:             String,
+    flag_output:                  Option<String>,
+    flag_delimiter:               Option<Delimiter>,
+    flag_raw_sheet_filenames:     bool,
+    flag_split_by:                Option<String>,
+    flag_split_open_files:        usize,
+    flag_summary_json:            Option<String>,
+    flag_stats_json:              bool,
+    flag_schema:                  Option<String>,
+    flag_bom:                     bool,
+    flag_quote_all:               bool,
+    flag_append:                  bool,
+    flag_quiet:                   bool,
+    flag_progressbar:             bool,
+}
+
+// the placeholder in --output that is replaced by the name of the exported sheet
+const SHEET_PLACEHOLDER: &str = "{sheet}";
+
+// the number of days between the epochs of the 1900 & 1904 date systems,
+// i.e. the 1900 date system serial number of 1904-01-01
+const DATE_1904_OFFSET: f64 = 1462.0;
+
+// the --dates-whitelist default, whose patterns are not expected to all match
+const DEFAULT_DATES_WHITELIST: &str = "date,time,due,open,close,created";
+
+// the largest integer a float stores exactly (2^53), like Excel's numbers
+const MAX_EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+// the largest sheet Excel can hold, so larger sheets are likely corrupt
+const EXCEL_MAX_ROWS: usize = 1_048_576;
+const EXCEL_MAX_COLUMNS: usize = 16_384;
+
+// the UTF-8 byte order mark written at the start of the CSV files with --bom
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+// the types of the --schema, in the order of the type counts of its columns
+const SCHEMA_TYPES: [&str; 5] = ["int", "float", "string", "date", "bool"];
+
+// the device names that Windows doesn't allow as filenames, with or without an extension
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+// maximum number of rows to scan when --detect-header-row is set
+const DETECT_HEADER_ROW_SCAN_ROWS: usize = 20;
+
+// the maximum length in bytes of the sanitized sheet names & --split-by values in filenames
+const MAX_FILENAME_STEM_LEN: usize = 240;
+
+// the header name transforms of --header-case
+#[derive(Clone, Copy)]
+enum HeaderCase {
+    Lower,
+    Upper,
+    Snake,
+    Camel,
+}
+
+// the majority type of a column with --coerce
+#[derive(Clone, Copy, PartialEq)]
+enum CoerceType {
+    Numeric,
+    Text,
+}
+
+// the JSON type of an exported field with --jsonl
+#[derive(Clone, Copy)]
+enum JsonKind {
+    Null,
+    Bool(bool),
+    Number,
+    String,
+}
+
+// the writer of the exported rows, as CSV or as JSONL with --jsonl
+enum ExportWriter {
+    Csv(csv::Writer<Box<dyn io::Write + 'static>>),
+    Jsonl(io::BufWriter<Box<dyn io::Write + 'static>>),
+}
+
+impl ExportWriter {
+    fn new(output: &Option<String>, args: &Args) -> io::Result<Self> {
+        Self::open(output, args, args.flag_append)
+    }
+
+    /// opens the writer of output, appending to it if append is set (e.g. for the --split-by
+    /// files that are reopened)
+    fn open(output: &Option<String>, args: &Args, append: bool) -> io::Result<Self> {
+        let config = Config::new(output)
+            .delimiter(args.flag_delimiter)
+            .flexible(args.flag_flexible)
+            .quote_style(if args.flag_quote_all {
+                csv::QuoteStyle::Always
+            } else {
+                csv::QuoteStyle::Necessary
+            });
+        // appended files already start with their BOM, if any
+        let appending = append
+            && output.as_deref().map_or(false, |path| {
+                fs::metadata(path).map_or(false, |metadata| metadata.len() > 0)
+            });
+        let mut io_writer: Box<dyn io::Write + 'static> = match output {
+            Some(path) if append => Box::new(
+                fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)?,
+            ),
+            _ => config.io_writer()?,
+        };
+        if args.flag_bom && !args.flag_jsonl && !appending {
+            io_writer.write_all(UTF8_BOM)?;
+        }
+        Ok(if args.flag_jsonl {
+            ExportWriter::Jsonl(io::BufWriter::new(io_writer))
+        } else {
+            ExportWriter::Csv(config.from_writer(io_writer))
+        })
+    }
+
+    /// writes an exported record. With --jsonl, the header row is only used
+    /// for the keys of the JSON objects, and is not written.
+    fn write(
+        &mut self,
+        is_header: bool,
+        keys: &[String],
+        record: &csv::StringRecord,
+        kinds: &[JsonKind],
+    ) -> CliResult<()> {
+        match self {
+            ExportWriter::Csv(wtr) => wtr.write_record(record)?,
+            ExportWriter::Jsonl(_) if is_header => {}
+            ExportWriter::Jsonl(wtr) => write_jsonl_record(wtr, keys, record, kinds)?,
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            ExportWriter::Csv(wtr) => wtr.flush(),
+            ExportWriter::Jsonl(wtr) => wtr.flush(),
+        }
+    }
+}
+
+// a file of --split-by, with its writer while it is open
+struct SplitFile {
+    path:     String,
+    wtr:      Option<ExportWriter>,
+    rows:     usize,
+    // the row last written to the file, to close the least recently written file
+    last_row: usize,
+}
+
+// a --row-filter condition on the cells of a column
+struct RowFilter {
+    column: String,
+    op:     RowFilterOp,
+    value:  String,
+}
+
+enum RowFilterOp {
+    Equal,
+    NotEqual,
+    Contains,
+    NotEmpty,
+}
+
+impl RowFilter {
+    /// parses a --row-filter condition, trying the operators in turn.
+    /// A condition without an operator is a non-empty check of the column
+    fn parse(condition: &str) -> Self {
+        for (operator, op) in [
+            ("==", RowFilterOp::Equal),
+            ("!=", RowFilterOp::NotEqual),
+            ("~=", RowFilterOp::Contains),
+        ] {
+            if let Some((column, value)) = condition.split_once(operator) {
+                return Self {
+                    column: column.trim().to_string(),
+                    op,
+                    value: value.to_string(),
+                };
+            }
+        }
+        Self {
+            column: condition.trim().to_string(),
+            op:     RowFilterOp::NotEmpty,
+            value:  String::new(),
+        }
+    }
+
+    fn matches(&self, cell: &str) -> bool {
+        match self.op {
+            RowFilterOp::Equal => cell == self.value,
+            RowFilterOp::NotEqual => cell != self.value,
+            RowFilterOp::Contains => cell.contains(&self.value),
+            RowFilterOp::NotEmpty => !cell.is_empty(),
+        }
+    }
+}
+
+// a --concat spec, concatenating the cells of the source columns into a new column
+struct ConcatSpec {
+    sources: Vec<String>,
+    name:    String,
+}
+
+impl ConcatSpec {
+    /// parses the ;-separated --concat specs, each of the form <column>+<column>...=<name>
+    fn parse_specs(specs: &str) -> CliResult<Vec<Self>> {
+        specs
+            .split(';')
+            .filter(|spec| !spec.trim().is_empty())
+            .map(|spec| {
+                let Some((sources, name)) = spec.rsplit_once('=') else {
+                    return fail_clierror!(
+                        "Invalid --concat spec: {spec}. It should be the source columns joined by \
+                         + and the name of the new column (e.g. first+last=full_name)."
+                    );
+                };
+                let sources = sources
+                    .split('+')
+                    .map(|source| source.trim().to_string())
+                    .collect_vec();
+                let name = name.trim().to_string();
+                if name.is_empty() || sources.iter().any(String::is_empty) {
+                    return fail_clierror!("Invalid --concat spec: {spec}");
+                }
+                Ok(Self { sources, name })
+            })
+            .collect()
+    }
+}
+
+// the AutoFilter of a sheet, with the criteria of its filtered columns
+struct AutoFilter {
+    // the zero-based (start, end) positions of the AutoFilter range. Its first row is
+    // the header row, so only the rows below it are filtered
+    range:   ((u32, u32), (u32, u32)),
+    // the zero-based sheet column of each criteria
+    columns: Vec<(u32, FilterCriteria)>,
+}
+
+enum FilterCriteria {
+    // the cell is one of the values, or is blank if blank is set
+    Values {
+        values: Vec<String>,
+        blank:  bool,
+    },
+    // all (with and) or any of the comparisons match the cell
+    Custom {
+        and:         bool,
+        comparisons: Vec<(FilterOperator, String)>,
+    },
+    // the criteria that cannot be interpreted, by name
+    Unsupported(&'static str),
+}
+
+#[derive(Clone, Copy)]
+enum FilterOperator {
+    Equal,
+    NotEqual,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+}
+
+impl AutoFilter {
+    /// returns true if the row at the zero-based sheet row matches all the interpreted
+    /// criteria, with start_col the sheet column of the first cell of the row
+    fn matches(&self, sheet_row: u32, start_col: u32, row: &[DataType]) -> bool {
+        let ((first_row, _), (last_row, _)) = self.range;
+        if sheet_row <= first_row || sheet_row > last_row {
+            return true;
+        }
+        self.columns.iter().all(|(col, criteria)| {
+            let cell = col
+                .checked_sub(start_col)
+                .and_then(|col_idx| row.get(col_idx as usize))
+                .unwrap_or(&DataType::Empty);
+            criteria.matches(cell)
+        })
+    }
+}
+
+impl FilterCriteria {
+    fn matches(&self, cell: &DataType) -> bool {
+        let text = filter_text(cell);
+        match self {
+            FilterCriteria::Values { values, blank } => {
+                if text.trim().is_empty() {
+                    return *blank;
+                }
+                let lower_text = text.to_lowercase();
+                let number = filter_number(cell);
+                values.iter().any(|value| {
+                    value.to_lowercase() == lower_text
+                        || (number.is_some() && value.trim().parse::<f64>().ok() == number)
+                })
+            }
+            FilterCriteria::Custom { and, comparisons } => {
+                let mut results = comparisons
+                    .iter()
+                    .map(|(op, value)| filter_compare(*op, value, cell, &text));
+                if *and {
+                    results.all(|matched| matched)
+                } else {
+                    results.any(|matched| matched)
+                }
+            }
+            FilterCriteria::Unsupported(_) => true,
+        }
+    }
+}
+
+/// the text of a cell, as compared by the AutoFilter criteria
+fn filter_text(cell: &DataType) -> String {
+    match cell {
+        DataType::Empty => String::new(),
+        DataType::String(s) => s.clone(),
+        DataType::Bool(b) => (if *b { "TRUE" } else { "FALSE" }).to_string(),
+        _ => cell.to_string(),
+    }
+}
+
+/// the number of a cell, as compared by the AutoFilter criteria
+fn filter_number(cell: &DataType) -> Option<f64> {
+    match cell {
+        DataType::Int(i) => Some(*i as f64),
+        DataType::Float(f) | DataType::DateTime(f) => Some(*f),
+        DataType::String(s) => s.trim().parse::<f64>().ok(),
+        _ => None,
+    }
+}
+
+/// compares a cell against the value of a custom AutoFilter criteria
+fn filter_compare(op: FilterOperator, value: &str, cell: &DataType, text: &str) -> bool {
+    // (does not) equal a space is how Excel filters (non) blank cells
+    if value.trim().is_empty() {
+        return match op {
+            FilterOperator::Equal => text.trim().is_empty(),
+            FilterOperator::NotEqual => !text.trim().is_empty(),
+            _ => false,
+        };
+    }
+    let ordering = match (filter_number(cell), value.trim().parse::<f64>()) {
+        (Some(number), Ok(value_number)) => number.partial_cmp(&value_number),
+        _ => match op {
+            FilterOperator::Equal => return wildcard_match(value, text),
+            FilterOperator::NotEqual => return !wildcard_match(value, text),
+            _ => Some(text.to_lowercase().cmp(&value.to_lowercase())),
+        },
+    };
+    let Some(ordering) = ordering else {
+        return false;
+    };
+    match op {
+        FilterOperator::Equal => ordering.is_eq(),
+        FilterOperator::NotEqual => ordering.is_ne(),
+        FilterOperator::LessThan => ordering.is_lt(),
+        FilterOperator::LessThanOrEqual => ordering.is_le(),
+        FilterOperator::GreaterThan => ordering.is_gt(),
+        FilterOperator::GreaterThanOrEqual => ordering.is_ge(),
+    }
+}
+
+/// matches text against a pattern with the * (any characters) & ? (any character)
+/// wildcards of the AutoFilter criteria, ignoring case
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0_usize, 0_usize);
+    // the pattern & text positions to backtrack to after the last star
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
 }
 
 #[derive(PartialEq)]
@@ -118,6 +1076,44 @@ struct SheetMetadata {
     unsafe_headers:          Vec<String>,
     unsafe_headers_count:    usize,
     duplicate_headers_count: usize,
+    hidden:                  bool,
+}
+
+#[derive(Serialize)]
+struct SheetListing {
+    index:  usize,
+    name:   String,
+    hidden: bool,
+    rows:   usize,
+    cols:   usize,
+}
+
+#[derive(Serialize)]
+struct ExportSummary {
+    filename:          String,
+    format:            String,
+    sheet:             String,
+    sheet_index:       usize,
+    output:            Option<String>,
+    rows:              usize,
+    columns:           usize,
+    date_columns:      Vec<String>,
+    error_cells:       usize,
+    error_cell_counts: BTreeMap<String, usize>,
+}
+
+#[derive(Serialize)]
+struct SchemaColumn {
+    name:     String,
+    #[serde(rename = "type")]
+    col_type: &'static str,
+    date:     bool,
+}
+
+#[derive(Serialize)]
+struct ExportSchema {
+    sheet:   String,
+    columns: Vec<SchemaColumn>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -128,9 +1124,426 @@ struct MetadataStruct {
     sheet:      Vec<SheetMetadata>,
 }
 
+// the output shared by the exports of several <input> workbooks
+struct InputsExport {
+    wtr:           ExportWriter,
+    // the header row of the first workbook, to check the headers of the next ones
+    first_headers: Option<csv::StringRecord>,
+}
+
+// the export options of Args, validated & resolved
+struct ExportOptions<'a> {
+    metadata_mode:         MetadataMode,
+    // the exported values of the true & false cells, from --bool-format
+    true_str:              &'a str,
+    false_str:             &'a str,
+    empty_token:           &'a str,
+    ellipsis:              &'a str,
+    error_format:          String,
+    on_date_error:         String,
+    // the timezone the datetimes are assumed to be in, and the one they're exported in
+    timezones:             Option<(Tz, Tz)>,
+    date_format:           Option<&'a str>,
+    first_duplicate_sheet: bool,
+    header_case:           Option<HeaderCase>,
+    date_1904:             bool,
+    dimension:             String,
+    percent_format:        String,
+    hyperlinks_mode:       String,
+    // with the date layout of --keep-serial, the serial stays in <col> & the converted date
+    // gets a suffix
+    keep_serial:           bool,
+    date_suffix:           bool,
+}
+
+impl<'a> ExportOptions<'a> {
+    /// validates the export options of args, and the combinations of options that cannot be
+    /// used together. workbook_date1904 is the date system of the workbook, for
+    /// --date-system auto
+    fn from_args(args: &'a Args, workbook_date1904: bool) -> CliResult<Self> {
+        // set Metadata Mode
+        let first_letter = args.flag_metadata.chars().next().unwrap_or_default();
+        let metadata_mode = match first_letter {
+            'c' | 'C' => MetadataMode::Csv,
+            'j' => MetadataMode::Json,
+            'J' => MetadataMode::PrettyJSON,
+            'n' | 'N' => MetadataMode::None,
+            _ => {
+                return fail_clierror!("Invalid mode: {}", args.flag_metadata);
+            }
+        };
+
+        let bool_format = match args.flag_bool_format.as_str() {
+            "lower" => "true/false",
+            "upper" => "TRUE/FALSE",
+            "tf" => "T/F",
+            "10" => "1/0",
+            bool_format => bool_format,
+        };
+        let Some((true_str, false_str)) = bool_format.split_once('/') else {
+            return fail_clierror!(
+                "Invalid --bool-format: {}. It should be the true & false values separated by a \
+                 slash (e.g. 1/0), or one of the lower, upper, tf & 10 presets.",
+                args.flag_bool_format
+            );
+        };
+        let empty_token = args.flag_empty_token.as_deref().unwrap_or_default();
+
+        if args.flag_max_cell_length == Some(0) {
+            return fail!("--max-cell-length must be greater than zero.");
+        }
+        let ellipsis = args.flag_ellipsis.as_deref().unwrap_or_default();
+
+        let error_format = args.flag_error_format.to_lowercase();
+        if !matches!(error_format.as_str(), "debug" | "excel" | "none") {
+            return fail_clierror!("Invalid --error-format: {}", args.flag_error_format);
+        }
+        let on_date_error = args.flag_on_date_error.to_lowercase();
+        if !matches!(
+            on_date_error.as_str(),
+            "error-string" | "passthrough" | "empty" | "fail"
+        ) {
+            return fail_clierror!("Invalid --on-date-error: {}", args.flag_on_date_error);
+        }
+
+        let timezones =
+            if args.flag_assume_timezone.is_some() || args.flag_output_timezone.is_some() {
+                let assumed_tz = match args.flag_assume_timezone.as_deref() {
+                    Some(tz) => match tz.parse::<Tz>() {
+                        Ok(tz) => tz,
+                        Err(e) => return fail_clierror!("Invalid --assume-timezone {tz}: {e}"),
+                    },
+                    None => Tz::UTC,
+                };
+                let output_tz = match args.flag_output_timezone.as_deref() {
+                    Some(tz) => match tz.parse::<Tz>() {
+                        Ok(tz) => tz,
+                        Err(e) => return fail_clierror!("Invalid --output-timezone {tz}: {e}"),
+                    },
+                    None => assumed_tz,
+                };
+                Some((assumed_tz, output_tz))
+            } else {
+                None
+            };
+
+        let date_format = args.flag_date_format.as_deref();
+        if let Some(date_format) = date_format {
+            // formatting a sample datetime fails on invalid & unsupported (e.g. timezone)
+            // specifiers
+            let sample = NaiveDate::from_ymd_opt(2000, 1, 1)
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .unwrap_or_default();
+            let formatted = if timezones.is_some() {
+                // the timezone specifiers are supported for the datetimes in a timezone
+                write!(
+                    String::new(),
+                    "{}",
+                    Tz::UTC.from_utc_datetime(&sample).format(date_format)
+                )
+            } else {
+                write!(String::new(), "{}", sample.format(date_format))
+            };
+            if formatted.is_err() {
+                return fail_clierror!("Invalid --date-format: {date_format}");
+            }
+        }
+
+        let first_duplicate_sheet = match args.flag_on_duplicate_sheet_name.to_lowercase().as_str()
+        {
+            "error" => false,
+            "first" => true,
+            _ => {
+                return fail_clierror!(
+                    "Invalid --on-duplicate-sheet-name: {}",
+                    args.flag_on_duplicate_sheet_name
+                )
+            }
+        };
+
+        let header_case = match args
+            .flag_header_case
+            .as_deref()
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            None | Some("none") => None,
+            Some("lower") => Some(HeaderCase::Lower),
+            Some("upper") => Some(HeaderCase::Upper),
+            Some("snake") => Some(HeaderCase::Snake),
+            Some("camel") => Some(HeaderCase::Camel),
+            Some(_) => {
+                return fail_clierror!(
+                    "Invalid --header-case: {}",
+                    args.flag_header_case.as_deref().unwrap_or_default()
+                )
+            }
+        };
+
+        let date_1904 = match args.flag_date_system.to_lowercase().as_str() {
+            "1900" => false,
+            "1904" => true,
+            "auto" => workbook_date1904,
+            _ => return fail_clierror!("Invalid --date-system: {}", args.flag_date_system),
+        };
+        info!(
+            "using the {} date system",
+            if date_1904 { 1904 } else { 1900 }
+        );
+
+        let dimension = args.flag_dimension.to_lowercase();
+        if !matches!(dimension.as_str(), "used" | "full" | "auto") {
+            return fail_clierror!("Invalid --dimension: {}", args.flag_dimension);
+        }
+        let percent_format = args.flag_percent_format.to_lowercase();
+        if !matches!(percent_format.as_str(), "raw" | "times100" | "sign") {
+            return fail_clierror!("Invalid --percent-format: {}", args.flag_percent_format);
+        }
+        if !(0.0..0.5).contains(&args.flag_datetime_threshold) {
+            return fail_clierror!(
+                "--datetime-threshold must be at least 0 and less than 0.5: {}",
+                args.flag_datetime_threshold
+            );
+        }
+        let hyperlinks_mode = args.flag_hyperlinks.to_lowercase();
+        if !matches!(hyperlinks_mode.as_str(), "keep" | "url" | "both") {
+            return fail_clierror!("Invalid --hyperlinks: {}", args.flag_hyperlinks);
+        }
+
+        if args.flag_count_only
+            && (args.flag_split_by.is_some() || args.flag_append || args.flag_dump_types)
+        {
+            return fail!("--count-only cannot be used with --split-by, --append or --dump-types.");
+        }
+
+        if args.flag_all_sheets {
+            match args.flag_output {
+                // the sheets are only counted, so there are no files to export them to
+                _ if args.flag_count_only => {}
+                Some(ref output) if Path::new(output).is_dir() => {}
+                Some(ref output) => {
+                    return fail_clierror!(
+                        "--output must be an existing directory with --all-sheets: {output}"
+                    );
+                }
+                None => {
+                    return fail!(
+                        "--output must be set to the directory to export the sheets to with \
+                         --all-sheets."
+                    );
+                }
+            }
+            if args.flag_range.is_some() || args.flag_summary_json.is_some() {
+                return fail!("--all-sheets cannot be used with --range or --summary-json.");
+            }
+        }
+
+        if args.flag_formulas && args.flag_formulas_with_values {
+            return fail!("--formulas cannot be used with --formulas-with-values.");
+        }
+
+        if args.flag_infer_dates && args.flag_formulas {
+            return fail!("--infer-dates cannot be used with --formulas.");
+        }
+
+        if args.flag_transpose
+            && (args.flag_skip_hidden_rows
+                || args.flag_skip_hidden_cols
+                || args.flag_apply_autofilter
+                || args.flag_formulas
+                || args.flag_formulas_with_values
+                || args.flag_keep_text_format
+                || args.flag_comments_column.is_some())
+        {
+            return fail!(
+                "--transpose cannot be used with --skip-hidden-rows, --skip-hidden-cols, \
+                 --apply-autofilter, --formulas, --formulas-with-values, --keep-text-format or \
+                 --comments-column."
+            );
+        }
+
+        if args.flag_dump_types
+            && (args.flag_jsonl
+                || args.flag_split_by.is_some()
+                || args.flag_all_sheets
+                || args.flag_schema.is_some()
+                || args
+                    .flag_output
+                    .as_deref()
+                    .map_or(false, |output| output.contains(SHEET_PLACEHOLDER)))
+        {
+            return fail!(
+                "--dump-types cannot be used with --jsonl, --split-by, --all-sheets, --schema or \
+                 the {sheet} placeholder of --output."
+            );
+        }
+
+        let (keep_serial, date_suffix) = match args.flag_keep_serial.as_deref() {
+            None => (false, false),
+            Some("serial") => (true, false),
+            Some("date") => (true, true),
+            Some(layout) => return fail_clierror!("Invalid --keep-serial: {}", layout),
+        };
+
+        if args.flag_append {
+            match args.flag_output {
+                None => {
+                    return fail!("--append requires --output, as stdout cannot be appended to.")
+                }
+                Some(ref output)
+                    if args.flag_all_sheets
+                        || args.flag_split_by.is_some()
+                        || output.contains(SHEET_PLACEHOLDER) =>
+                {
+                    return fail!(
+                        "--append cannot be used with --all-sheets, --split-by or the {sheet} \
+                         placeholder of --output."
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+
+        if args.flag_split_by.is_some() {
+            match args.flag_output {
+                Some(ref output) if output.contains(SHEET_PLACEHOLDER) => {
+                    return fail!(
+                        "--split-by cannot be used with the {sheet} placeholder of --output."
+                    );
+                }
+                Some(ref output) if Path::new(output).is_dir() => {}
+                Some(ref output) => {
+                    return fail_clierror!(
+                        "--output must be an existing directory with --split-by: {output}"
+                    );
+                }
+                None => {
+                    return fail!(
+                        "--output must be set to the directory to write the files to with \
+                         --split-by."
+                    );
+                }
+            }
+            if args.flag_all_sheets {
+                return fail!("--split-by cannot be used with --all-sheets.");
+            }
+            if args.flag_split_open_files == 0 {
+                return fail!("--split-open-files must be greater than 0.");
+            }
+        }
+
+        if args.flag_detect_header_row && args.flag_header_row > 0 {
+            return fail!("--header-row cannot be used with --detect-header-row.");
+        }
+
+        if args.flag_no_headers && (args.flag_detect_header_row || args.flag_header_row > 0) {
+            return fail!("--no-headers cannot be used with --header-row or --detect-header-row.");
+        }
+
+        Ok(Self {
+            metadata_mode,
+            true_str,
+            false_str,
+            empty_token,
+            ellipsis,
+            error_format,
+            on_date_error,
+            timezones,
+            date_format,
+            first_duplicate_sheet,
+            header_case,
+            date_1904,
+            dimension,
+            percent_format,
+            hyperlinks_mode,
+            keep_serial,
+            date_suffix,
+        })
+    }
+}
+
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
-    let path = &args.arg_input;
+    if args.arg_input.len() > 1 {
+        return export_inputs(args);
+    }
+    export(args, None, None)?;
+    Ok(())
+}
+
+/// exports the --sheet of each of several <input> workbooks into a single output,
+/// with the header row of the first workbook
+fn export_inputs(args: Args) -> CliResult<()> {
+    if args.flag_all_sheets
+        || args.flag_split_by.is_some()
+        || args.flag_summary_json.is_some()
+        || args.flag_schema.is_some()
+        || args.flag_count_only
+        || args.flag_dump_types
+        || !args.flag_metadata.to_lowercase().starts_with('n')
+        || args.flag_list_sheets
+        || args.flag_list_named_ranges
+        || args.flag_properties
+        || args
+            .flag_output
+            .as_deref()
+            .map_or(false, |output| output.contains(SHEET_PLACEHOLDER))
+    {
+        return fail!(
+            "Several <input> workbooks cannot be used with --all-sheets, --split-by, \
+             --summary-json, --schema, --count-only, --dump-types, --metadata, --list-sheets, \
+             --list-named-ranges, --properties or the {sheet} placeholder of --output."
+        );
+    }
+    if args
+        .arg_input
+        .iter()
+        .any(|input| input.is_empty() || input == "-")
+    {
+        return fail!("stdin cannot be one of several <input> workbooks.");
+    }
+
+    let mut inputs_export = InputsExport {
+        wtr:           ExportWriter::new(&args.flag_output, &args)?,
+        first_headers: None,
+    };
+    let mut total_row_count = 0_usize;
+    for input in &args.arg_input {
+        let mut input_args = args.clone();
+        input_args.arg_input = vec![input.clone()];
+        total_row_count += export(input_args, None, Some(&mut inputs_export))?;
+    }
+    inputs_export.wtr.flush()?;
+    if !args.flag_quiet && !args.flag_stats_json {
+        winfo!(
+            "{} rows exported from {} workbooks",
+            total_row_count.separate_with_commas(),
+            args.arg_input.len()
+        );
+    }
+    Ok(())
+}
+
+/// exports the sheets of the workbook, or only the sheet at only_sheet when a sheet of
+/// --all-sheets is exported concurrently with --jobs. With several <input> workbooks, the
+/// rows are written to the output of inputs_export. Returns the number of exported rows.
+fn export(
+    args: Args,
+    only_sheet: Option<usize>,
+    inputs_export: Option<&mut InputsExport>,
+) -> CliResult<usize> {
+    // a workbook piped to stdin is buffered to a temp file, as the xlsx, xlsb & ods
+    // formats are zip archives, which cannot be read without random access
+    let input = args.arg_input.first().map_or("-", String::as_str);
+    let stdin_dir;
+    let stdin_path;
+    let path = if input.is_empty() || input == "-" {
+        stdin_dir = tempfile::tempdir()?;
+        stdin_path = stdin_workbook(stdin_dir.path())?;
+        stdin_path.as_str()
+    } else {
+        input
+    };
 
     let sce = PathBuf::from(path);
     let mut ods_flag = false;
@@ -145,17 +1558,38 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         .unwrap_or_default()
         .to_lowercase();
     match format.as_str() {
-        "xls" | "xlsx" | "xlsm" | "xlsb" => (),
-        "ods" => ods_flag = true,
+        "xls" | "xlsx" | "xlsm" | "xlsb" | "xlt" | "xltx" | "xltm" => (),
+        "ods" | "ots" => ods_flag = true,
+        "csv" | "tsv" | "tab" | "ssv" => {
+            return fail_clierror!(
+                "{filename} is already a CSV file, not an Excel/ODS workbook, so it does not need \
+                 to be exported. Use it with the other qsv commands directly - e.g. qsv input to \
+                 normalize it, or qsv fmt to change its delimiter."
+            );
+        }
         _ => {
             return fail!(
                 "\"{format}\" not supported. The excel command only supports the following file \
-                 formats - xls, xlsx, xlsm, xlsb and ods."
+                 formats - xls, xlsx, xlsm, xlsb and ods, and the xlt, xltx, xltm and ots \
+                 templates."
             );
         }
     };
 
-    let mut workbook = match open_workbook_auto(path) {
+    // open_workbook_auto doesn't know the template extensions
+    let workbook_result = match format.as_str() {
+        "xlt" => open_workbook::<Xls<_>, _>(path)
+            .map(Sheets::Xls)
+            .map_err(calamine::Error::Xls),
+        "xltx" | "xltm" => open_workbook::<Xlsx<_>, _>(path)
+            .map(Sheets::Xlsx)
+            .map_err(calamine::Error::Xlsx),
+        "ots" => open_workbook::<Ods<_>, _>(path)
+            .map(Sheets::Ods)
+            .map_err(calamine::Error::Ods),
+        _ => open_workbook_auto(path),
+    };
+    let mut workbook = match workbook_result {
         Ok(workbook) => workbook,
         Err(e) => {
             let es = e.to_string();
@@ -169,45 +1603,202 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         }
     };
 
+    // the details that calamine doesn't expose are read from the zip archive of the xlsx/xlsm
+    // workbooks, which is only opened once
+    let xlsx_format = matches!(format.as_str(), "xlsx" | "xlsm" | "xltx" | "xltm");
+    let mut xlsx = if xlsx_format {
+        match XlsxArchive::open(path) {
+            Ok(xlsx) => Some(xlsx),
+            Err(e) => {
+                winfo!(
+                    "cannot read the zip archive of {filename}: {e}. The sheets are exported \
+                     without the details that are only read from it (e.g. the hidden sheets)."
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     let sheet_names = workbook.sheet_names();
-    if sheet_names.is_empty() {
-        if ods_flag {
-            return fail_clierror!("{path} may be password protected.");
-        };
-        return fail!("No sheets found.");
-    }
     let num_sheets = sheet_names.len();
     let sheet_vec = sheet_names.to_owned();
 
-    let mut wtr = Config::new(&args.flag_output)
-        .flexible(args.flag_flexible)
-        .writer()?;
-
-    // set Metadata Mode
-    let first_letter = args.flag_metadata.chars().next().unwrap_or_default();
-    let metadata_mode = match first_letter {
-        'c' | 'C' => MetadataMode::Csv,
-        'j' => MetadataMode::Json,
-        'J' => MetadataMode::PrettyJSON,
-        'n' | 'N' => MetadataMode::None,
-        _ => {
-            return fail_clierror!("Invalid mode: {}", args.flag_metadata);
+    if args.flag_properties {
+        if args.flag_list_sheets
+            || args.flag_list_named_ranges
+            || !args.flag_metadata.to_lowercase().starts_with('n')
+        {
+            return fail!(
+                "--properties cannot be used with --list-sheets, --list-named-ranges or \
+                 --metadata."
+            );
         }
-    };
-
-    // use with_capacity to minimize reallocation
-    let mut record = csv::StringRecord::with_capacity(200, 20);
-
-    if metadata_mode != MetadataMode::None {
-        let mut excelmetadata_struct = MetadataStruct {
-            filename,
-            format,
-            num_sheets,
-            sheet: vec![],
+        let mut wtr = Config::new(&args.flag_output)
+            .delimiter(args.flag_delimiter)
+            .writer()?;
+        wtr.write_record(["property", "value"])?;
+        wtr.write_record(["format", &format])?;
+        wtr.write_record(["sheet_count", &num_sheets.to_string()])?;
+        wtr.write_record([
+            "date_system",
+            if xlsx.as_ref().map_or(false, |xlsx| xlsx.date1904) {
+                "1904"
+            } else {
+                "1900"
+            },
+        ])?;
+        let properties = match xlsx.as_mut().map(XlsxArchive::properties) {
+            Some(Ok(properties)) => properties,
+            Some(Err(e)) => {
+                winfo!("cannot read the document properties of {filename}: {e}.");
+                vec![]
+            }
+            None => vec![],
         };
-        #[allow(clippy::needless_range_loop)]
-        for i in 0..num_sheets {
-            let sheet_name = sheet_vec[i].clone();
+        for (property, value) in properties {
+            wtr.write_record([property, value])?;
+        }
+        wtr.flush()?;
+        return Ok(0);
+    }
+
+    if args.flag_list_named_ranges {
+        let mut wtr = Config::new(&args.flag_output)
+            .delimiter(args.flag_delimiter)
+            .writer()?;
+        wtr.write_record(["name", "sheet", "range"])?;
+        for (name, reference) in workbook.defined_names() {
+            match parse_defined_name(reference) {
+                Some((sheet, range_ref, _)) => wtr.write_record([name, &sheet, &range_ref])?,
+                None => wtr.write_record([name.as_str(), "", ""])?,
+            }
+        }
+        wtr.flush()?;
+        return Ok(0);
+    }
+
+    if args.flag_json && !args.flag_list_sheets {
+        return fail!("--json can only be used with --list-sheets.");
+    }
+
+    if args.flag_list_sheets {
+        let mut sheet_listings = Vec::with_capacity(num_sheets);
+        for (i, sheet_name) in sheet_vec.iter().enumerate() {
+            let (num_rows, num_columns) = match workbook.worksheet_range_at(i) {
+                Some(Ok(range)) => range.get_size(),
+                Some(Err(e)) => {
+                    return fail_clierror!("Cannot retrieve range from {sheet_name}: {e}.");
+                }
+                None => (0, 0),
+            };
+            sheet_listings.push(SheetListing {
+                index:  i,
+                name:   sheet_name.to_string(),
+                hidden: xlsx.as_ref().map_or(false, |xlsx| xlsx.sheet_hidden(i)),
+                rows:   num_rows,
+                cols:   num_columns,
+            });
+        }
+        if args.flag_json {
+            let Ok(json_result) = serde_json::to_string(&sheet_listings) else {
+                return fail!("Cannot create JSON");
+            };
+            let mut wtr = Config::new(&args.flag_output).io_writer()?;
+            writeln!(wtr, "{json_result}")?;
+            wtr.flush()?;
+            return Ok(0);
+        }
+        let mut wtr = Config::new(&args.flag_output)
+            .delimiter(args.flag_delimiter)
+            .writer()?;
+        wtr.write_record(["index", "sheet_name", "rows", "columns", "hidden"])?;
+        for sheet in sheet_listings {
+            wtr.write_record([
+                sheet.index.to_string(),
+                sheet.name,
+                sheet.rows.to_string(),
+                sheet.cols.to_string(),
+                sheet.hidden.to_string(),
+            ])?;
+        }
+        wtr.flush()?;
+        return Ok(0);
+    }
+
+    // the listings above are only their header row for an empty workbook,
+    // but there is no sheet to export
+    if num_sheets == 0 {
+        if ods_flag {
+            return fail_clierror!("{path} may be password protected.");
+        };
+        return fail!("Workbook contains no sheets.");
+    }
+
+    // the --sheet to export & the absolute (start, end) positions of the --range
+    let (sheet_arg, cell_range) = match args.flag_range.as_deref() {
+        Some(range_arg) => match range_arg.strip_prefix('@') {
+            Some(range_name) => {
+                let Some((_, reference)) = workbook
+                    .defined_names()
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(range_name))
+                else {
+                    return fail_clierror!(
+                        "Cannot find named range \"{range_name}\". Use --list-named-ranges to \
+                         list them."
+                    );
+                };
+                let Some((sheet, _, positions)) = parse_defined_name(reference) else {
+                    return fail_clierror!(
+                        r#"Named range "{range_name}" is not a single range: {reference}"#
+                    );
+                };
+                info!("named range {range_name}: {reference}");
+                (Some(sheet), Some((positions.0, Some(positions.1))))
+            }
+            None => {
+                // an open-ended range (e.g. A5:) runs to the end of the sheet
+                let positions = match range_arg.strip_suffix(':') {
+                    Some(start_ref) => parse_a1_cell(start_ref).map(|start| (start, None)),
+                    None => parse_a1_range(range_arg).map(|(start, end)| (start, Some(end))),
+                };
+                let Some(positions) = positions else {
+                    return fail_clierror!(
+                        "Invalid --range: {range_arg}. Expected an A1-style range like A1:D50 or \
+                         A5:."
+                    );
+                };
+                (args.flag_sheet.clone(), Some(positions))
+            }
+        },
+        None => (args.flag_sheet.clone(), None),
+    };
+
+    let options =
+        ExportOptions::from_args(&args, xlsx.as_ref().map_or(false, |xlsx| xlsx.date1904))?;
+
+    // use with_capacity to minimize reallocation
+    let mut record = csv::StringRecord::with_capacity(200, 20);
+
+    if options.metadata_mode != MetadataMode::None {
+        let mut wtr = Config::new(&args.flag_output)
+            .delimiter(args.flag_delimiter)
+            .flexible(args.flag_flexible)
+            .writer()?;
+        let mut excelmetadata_struct = MetadataStruct {
+            filename,
+            format,
+            num_sheets,
+            sheet: vec![],
+        };
+        for (sheet_name, indices) in duplicate_sheet_names(&sheet_vec) {
+            winfo!(r#"duplicate sheet name "{sheet_name}" - sheets {indices:?}"#);
+        }
+        #[allow(clippy::needless_range_loop)]
+        for i in 0..num_sheets {
+            let sheet_name = sheet_vec[i].clone();
 
             let range = if let Some(result) = workbook.worksheet_range_at(i) {
                 match result {
@@ -278,11 +1869,12 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 unsafe_headers_count: unsafeheaders_vec.len(),
                 unsafe_headers: unsafeheaders_vec,
                 duplicate_headers_count: dupe_count,
+                hidden: xlsx.as_ref().map_or(false, |xlsx| xlsx.sheet_hidden(i)),
             };
 
             excelmetadata_struct.sheet.push(sheetmetadata_struct);
         }
-        match metadata_mode {
+        match options.metadata_mode {
             MetadataMode::Csv => {
                 record.push_field("index");
                 record.push_field("sheet_name");
@@ -294,6 +1886,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 record.push_field("unsafe_headers");
                 record.push_field("unsafe_headers_count");
                 record.push_field("duplicate_headers_count");
+                record.push_field("hidden");
 
                 wtr.write_record(&record)?;
 
@@ -309,6 +1902,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                     record.push_field(&format!("{:?}", sheetmetadata.unsafe_headers));
                     record.push_field(&sheetmetadata.unsafe_headers_count.to_string());
                     record.push_field(&sheetmetadata.duplicate_headers_count.to_string());
+                    record.push_field(&sheetmetadata.hidden.to_string());
 
                     wtr.write_record(&record)?;
                 }
@@ -331,7 +1925,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         info!(r#"exported metadata for "{path}" workbook sheets: {sheet_vec:?}"#);
         // after we export metadata, we're done.
         // we're not exporting the spreadsheet to CSV
-        return Ok(());
+        return Ok(0);
     }
 
     // convert sheet_names to lowercase so we can do a case-insensitive compare
@@ -340,208 +1934,3249 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         lower_sheet_names.push(s.to_lowercase());
     }
 
-    // if --sheet name was passed, see if its a valid sheet name.
-    let mut sheet = if lower_sheet_names.contains(&args.flag_sheet.to_lowercase()) {
-        args.flag_sheet
-    } else {
-        // otherwise, if --sheet is a number, its a zero-based index, fetch it
-        if let Ok(sheet_index) = args.flag_sheet.parse::<i32>() {
-            if sheet_index >= 0 {
-                if sheet_index as usize <= sheet_names.len() {
-                    sheet_names[sheet_index as usize].to_string()
+    // the first sheet is exported if --sheet is not set
+    let flag_sheet = sheet_arg.as_deref().unwrap_or("0");
+    // --sheet can be a comma-separated list of sheets, unless a sheet is named like the list
+    let sheet_list =
+        if flag_sheet.contains(',') && !lower_sheet_names.contains(&flag_sheet.to_lowercase()) {
+            flag_sheet.split(',').map(str::trim).collect_vec()
+        } else {
+            vec![flag_sheet]
+        };
+    let mut sheet_indices: Vec<usize> = Vec::with_capacity(sheet_list.len());
+    for flag_sheet in sheet_list {
+        // if --sheet name was passed, see if its a valid sheet name.
+        let lower_flag_sheet = flag_sheet.to_lowercase();
+        let name_indices = lower_sheet_names
+            .iter()
+            .enumerate()
+            .filter(|(_, lower_sheet_name)| **lower_sheet_name == lower_flag_sheet)
+            .map(|(idx, _)| idx)
+            .collect_vec();
+        // a sheet named exactly --sheet wins over the case-insensitive matches
+        let exact_index = sheet_names.iter().position(|name| name == flag_sheet);
+        if exact_index.is_none() && name_indices.len() > 1 && !options.first_duplicate_sheet {
+            return fail_clierror!(
+                "{} sheets are named \"{flag_sheet}\" - sheets {name_indices:?}. Use the --sheet \
+                 index of the sheet to export, or --on-duplicate-sheet-name first.",
+                name_indices.len()
+            );
+        }
+        let sheet_index = if let Some(idx) = exact_index.as_ref().or(name_indices.first()) {
+            *idx
+        } else {
+            // otherwise, if --sheet is a number, its a zero-based index, fetch it
+            if let Ok(sheet_index) = flag_sheet.parse::<i32>() {
+                let in_range_index = if sheet_index >= 0 {
+                    Some(sheet_index as usize).filter(|idx| *idx < num_sheets)
                 } else {
-                    return fail_clierror!(
-                        "sheet index {sheet_index} is greater than number of sheets {}",
-                        sheet_names.len()
-                    );
-                }
+                    // if its a negative number, start from the end
+                    // i.e -1 is the last sheet; -2 = 2nd to last sheet
+                    num_sheets.checked_sub(sheet_index.unsigned_abs() as usize)
+                };
+                let Some(in_range_index) = in_range_index else {
+                    let mut message = if sheet_index >= 0 {
+                        format!(
+                            "sheet index {sheet_index} is greater than number of sheets \
+                             {num_sheets}"
+                        )
+                    } else {
+                        format!(
+                            "sheet index {sheet_index} is out of range of the {num_sheets} sheets"
+                        )
+                    };
+                    if args.flag_strict_sheet {
+                        message = format!("{message}. Available sheets: {sheet_names:?}");
+                    }
+                    return fail_clierror!("{message}");
+                };
+                in_range_index
+            } else if args.flag_strict_sheet {
+                return fail_clierror!(
+                    r#"Cannot find sheet "{flag_sheet}". Available sheets: {sheet_names:?}"#
+                );
             } else {
-                // if its a negative number, start from the end
-                // i.e -1 is the last sheet; -2 = 2nd to last sheet
-                sheet_names[cmp::max(
-                    0,
-                    cmp::min(
-                        num_sheets - 1,
-                        num_sheets.abs_diff(sheet_index.unsigned_abs() as usize),
-                    ),
-                )]
-                .to_string()
+                // failing all else, get the first sheet
+                info!(
+                    r#"Invalid sheet "{flag_sheet}". Using the first sheet "{}" instead."#,
+                    sheet_names[0]
+                );
+                0
             }
-        } else {
-            // failing all else, get the first sheet
-            let first_sheet = sheet_names[0].to_string();
-            info!(
-                r#"Invalid sheet "{}". Using the first sheet "{}" instead."#,
-                args.flag_sheet, first_sheet
+        };
+        sheet_indices.push(sheet_index);
+    }
+    if args.flag_all_sheets {
+        sheet_indices = (0..num_sheets).collect_vec();
+    }
+    if let Some(ref sheet_regex) = args.flag_sheet_regex {
+        if args.flag_all_sheets || sheet_arg.is_some() {
+            return fail!(
+                "--sheet-regex cannot be used with --sheet, --all-sheets or a named --range."
             );
-            first_sheet
         }
+        let pattern = match regex::Regex::new(sheet_regex) {
+            Ok(pattern) => pattern,
+            Err(e) => return fail_clierror!("Invalid --sheet-regex {sheet_regex}: {e}"),
+        };
+        sheet_indices = sheet_names
+            .iter()
+            .enumerate()
+            .filter(|(_, sheet_name)| pattern.is_match(sheet_name))
+            .map(|(idx, _)| idx)
+            .collect_vec();
+        if sheet_indices.is_empty() {
+            return fail_clierror!(
+                "No sheet matches --sheet-regex {sheet_regex}. Available sheets: {sheet_names:?}"
+            );
+        }
+    }
+    // the sheets matching --sheet-regex always have the source_sheet column
+    let with_sheet_name = args.flag_with_sheet_name || args.flag_sheet_regex.is_some();
+    if sheet_indices.len() > 1 && args.flag_summary_json.is_some() {
+        return fail!("--summary-json can only be used when exporting a single sheet.");
+    }
+    if sheet_indices.len() > 1 && args.flag_schema.is_some() {
+        return fail!("--schema can only be used when exporting a single sheet.");
+    }
+    if sheet_indices.len() > 1 && args.flag_split_by.is_some() {
+        return fail!("--split-by can only be used when exporting a single sheet.");
+    }
+    if let Some(sheet_index) = only_sheet {
+        sheet_indices = vec![sheet_index];
+    }
+
+    // the sheet details to read from xlsx/xlsm workbooks, for the options that use them
+    let sheet_options = XlsxSheetOptions {
+        dimension:    options.dimension == "full",
+        hidden_rows:  args.flag_skip_hidden_rows,
+        hidden_cols:  args.flag_skip_hidden_cols,
+        hyperlinks:   options.hyperlinks_mode != "keep",
+        merged_cells: args.flag_merge_fill,
+        autofilter:   args.flag_apply_autofilter,
+        comments:     args.flag_comments_column.is_some(),
+        zero_padded:  args.flag_keep_text_format,
+        duration:     args.flag_elapsed_time,
+        percent:      options.percent_format != "raw",
     };
-    let lower_sheet = sheet.to_lowercase();
-    let sheet_index = if let Some(idx) = lower_sheet_names.iter().position(|s| *s == lower_sheet) {
-        // set to actual name of the sheet, not the one passed using the --sheet option,
-        // as we process the option case insensitively
-        sheet = sheet_names[idx].clone();
-        idx
-    } else {
-        return fail_clierror!("Cannot get sheet index for {sheet}");
-    };
+    let sheet_flags = sheet_options.flags();
+    // the full dimension & the times of day are also exported for the other formats
+    let xlsx_only_flags = sheet_flags
+        .iter()
+        .filter(|flag| !matches!(**flag, "--dimension full" | "--elapsed-time"))
+        .join(", ");
+    // the sheets exported with --jobs don't repeat the warning
+    if !xlsx_format && !xlsx_only_flags.is_empty() && only_sheet.is_none() {
+        winfo!(
+            "{xlsx_only_flags} only work with xlsx/xlsm workbooks, so they are ignored for the \
+             {format} workbook {filename}."
+        );
+    }
 
-    let range = if let Some(result) = workbook.worksheet_range_at(sheet_index) {
-        match result {
-            Ok(result) => result,
-            Err(e) => return fail_clierror!("Cannot retrieve range from {sheet}: {e}"),
+    // with --jobs, each sheet of --all-sheets is exported to its file by its own export,
+    // with its own workbook, as calamine workbooks cannot be shared across threads
+    if args.flag_all_sheets
+        && !args.flag_count_only
+        && args.flag_jobs.map_or(false, |jobs| jobs != 1)
+    {
+        util::njobs(args.flag_jobs.filter(|jobs| *jobs > 0));
+        let output_dir = args.flag_output.clone().unwrap_or_default();
+        let mut used_sheet_filenames: Vec<String> = Vec::with_capacity(sheet_indices.len());
+        let mut sheet_exports = Vec::with_capacity(sheet_indices.len());
+        for &sheet_index in &sheet_indices {
+            let sheet = &sheet_names[sheet_index];
+            let sheet_filename = if args.flag_raw_sheet_filenames {
+                sheet.clone()
+            } else {
+                let sanitized = sanitize_sheet_filename(sheet, &mut used_sheet_filenames);
+                if sanitized != *sheet {
+                    winfo!(
+                        r#"sheet name "{sheet}" was changed to "{sanitized}" in the output path"#
+                    );
+                }
+                sanitized
+            };
+            let mut sheet_args = args.clone();
+            sheet_args.arg_input = vec![path.to_string()];
+            sheet_args.flag_all_sheets = false;
+            sheet_args.flag_jobs = None;
+            sheet_args.flag_output = Some(all_sheets_output(
+                &output_dir,
+                &sheet_filename,
+                args.flag_jsonl,
+            ));
+            sheet_args.flag_progressbar = false;
+            sheet_exports.push((sheet_args, sheet_index));
+        }
+        let row_counts = sheet_exports
+            .into_par_iter()
+            .map(|(sheet_args, sheet_index)| export(sheet_args, Some(sheet_index), None))
+            .collect::<CliResult<Vec<usize>>>()?;
+        let total_row_count = row_counts.iter().sum::<usize>();
+        if row_counts.len() > 1 && !args.flag_quiet && !args.flag_stats_json {
+            winfo!(
+                "{} rows exported from {} sheets",
+                total_row_count.separate_with_commas(),
+                row_counts.len()
+            );
         }
+        return Ok(total_row_count);
+    }
+
+    // the sheets are concatenated into the same output, unless each sheet has its own file
+    let per_sheet_output = args.flag_all_sheets
+        || args
+            .flag_output
+            .as_deref()
+            .map_or(false, |output| output.contains(SHEET_PLACEHOLDER));
+    // with --append, the header row is only written to a new or empty --output file
+    let skip_header = args.flag_append
+        && args.flag_output.as_deref().map_or(false, |output| {
+            fs::metadata(output).map_or(false, |metadata| metadata.len() > 0)
+        });
+    // the header row of the first sheet, to check the headers of the concatenated sheets.
+    // With several <input> workbooks, it is the header row of the first workbook.
+    let mut own_first_headers: Option<csv::StringRecord> = None;
+    let (inputs_wtr, first_headers) = match inputs_export {
+        Some(InputsExport { wtr, first_headers }) => (Some(wtr), first_headers),
+        None => (None, &mut own_first_headers),
+    };
+    let headers_from_first_workbook = first_headers.is_some();
+    let mut own_wtr = if inputs_wtr.is_some()
+        || per_sheet_output
+        || args.flag_split_by.is_some()
+        || args.flag_count_only
+    {
+        None
     } else {
-        Range::empty()
+        Some(ExportWriter::new(&args.flag_output, &args)?)
     };
+    let mut shared_wtr = inputs_wtr.or(own_wtr.as_mut());
+    // the field count of the exported header row, that --pad-rows pads the data rows to
+    let mut pad_width = 0_usize;
+    let mut types_header_written = false;
+    // the data row count of each sheet, with --count-only
+    let mut sheet_counts: Vec<(String, usize)> = Vec::with_capacity(sheet_indices.len());
+    // the formula fallback warning is only sent once per run
+    let mut formula_fallback_warned = false;
+    let num_exported_sheets = sheet_indices.len();
+    let mut used_sheet_filenames: Vec<String> = Vec::with_capacity(sheet_indices.len());
+    let mut total_row_count = 0_usize;
 
-    let whitelist_lower = args.flag_dates_whitelist.to_lowercase();
-    info!("using date-whitelist: {whitelist_lower}");
+    for sheet_index in sheet_indices {
+        // use the actual name of the sheet, not the one passed using the --sheet option,
+        // as we process the option case insensitively
+        let sheet = sheet_names[sheet_index].clone();
 
-    // an all number whitelist means we're being given
-    // the column indices of the date column names
-    let mut all_numbers_whitelist = true;
+        // replace the {sheet} placeholder of the output path with the sheet name
+        let output = match args.flag_output {
+            Some(ref output)
+                if !args.flag_count_only
+                    && (args.flag_all_sheets || output.contains(SHEET_PLACEHOLDER)) =>
+            {
+                let sheet_filename = if args.flag_raw_sheet_filenames {
+                    sheet.clone()
+                } else {
+                    let sanitized = sanitize_sheet_filename(&sheet, &mut used_sheet_filenames);
+                    if sanitized != sheet {
+                        winfo!(
+                            r#"sheet name "{sheet}" was changed to "{sanitized}" in the output path"#
+                        );
+                    }
+                    sanitized
+                };
+                if args.flag_all_sheets {
+                    Some(all_sheets_output(output, &sheet_filename, args.flag_jsonl))
+                } else {
+                    Some(output.replace(SHEET_PLACEHOLDER, &sheet_filename))
+                }
+            }
+            _ => args.flag_output.clone(),
+        };
+        // with --split-by, the rows are written by the writers of each value instead
+        let mut sheet_wtr;
+        let mut wtr = if let Some(ref mut wtr) = shared_wtr {
+            Some(&mut **wtr)
+        } else if args.flag_split_by.is_some() || args.flag_count_only {
+            None
+        } else {
+            sheet_wtr = ExportWriter::new(&output, &args)?;
+            Some(&mut sheet_wtr)
+        };
 
-    let mut dates_whitelist = whitelist_lower
-        .split(',')
-        .map(|s| {
-            if all_numbers_whitelist && s.parse::<u16>().is_err() {
-                all_numbers_whitelist = false;
-                info!("NOT a column index dates whitelist");
+        let range = if let Some(result) = workbook.worksheet_range_at(sheet_index) {
+            match result {
+                Ok(result) => result,
+                Err(e) => return fail_clierror!("Cannot retrieve range from {sheet}: {e}"),
             }
-            s.trim().to_string()
-        })
-        .collect_vec();
+        } else {
+            return fail_clierror!("Worksheet '{sheet}' could not be read.");
+        };
+        let sheet_details = match xlsx.as_mut() {
+            Some(xlsx) if !sheet_flags.is_empty() => {
+                match xlsx.sheet_details(sheet_index, &sheet_options) {
+                    Ok(sheet_details) => sheet_details,
+                    Err(e) => {
+                        winfo!(
+                            "cannot read the \"{sheet}\" sheet details used by {}: {e}. Exporting \
+                             the sheet without them.",
+                            sheet_flags.join(", ")
+                        );
+                        XlsxSheetDetails::default()
+                    }
+                }
+            }
+            _ => XlsxSheetDetails::default(),
+        };
+        let range = if options.hyperlinks_mode == "keep" {
+            range
+        } else {
+            link_cells(
+                range,
+                &sheet_details.hyperlinks,
+                options.hyperlinks_mode == "both",
+            )
+        };
+        let range = if args.flag_merge_fill {
+            fill_merged_cells(range, &sheet_details.merged_cells)
+        } else {
+            range
+        };
+        let range = match options.dimension.as_str() {
+            "full" => {
+                let full_dimension = sheet_details
+                    .dimension
+                    .or_else(|| range.end().map(|end| ((0, 0), end)));
+                if let Some((start, end)) = full_dimension {
+                    info!("using full dimension: {start:?} to {end:?}");
+                    resize_range(range, start, end)
+                } else {
+                    range
+                }
+            }
+            "auto" => tight_range(range),
+            _ => range,
+        };
+        // e.g. a bad dimension record, or the repeated empty rows of a malformed ODS file
+        let (num_rows, num_columns) = range.get_size();
+        if (num_rows > EXCEL_MAX_ROWS || num_columns > EXCEL_MAX_COLUMNS) && !args.flag_quiet {
+            winfo!(
+                "WARNING: the \"{sheet}\" sheet has a {} x {} range of cells, larger than the {} \
+                 x {} cells of an Excel sheet, so the workbook may be corrupt (e.g. with a bad \
+                 dimension record).",
+                num_rows.separate_with_commas(),
+                num_columns.separate_with_commas(),
+                EXCEL_MAX_ROWS.separate_with_commas(),
+                EXCEL_MAX_COLUMNS.separate_with_commas()
+            );
+        }
+        let range = match cell_range {
+            Some((start, end)) => {
+                let (Some(range_start), Some(range_end)) = (range.start(), range.end()) else {
+                    return fail_clierror!(
+                        "--range {} is outside of the \"{sheet}\" sheet, which is empty.",
+                        args.flag_range.as_deref().unwrap_or_default()
+                    );
+                };
+                let end = end.unwrap_or(range_end);
+                if start.0 > range_end.0
+                    || start.1 > range_end.1
+                    || end.0 < range_start.0
+                    || end.1 < range_start.1
+                {
+                    return fail_clierror!(
+                        "--range {} is outside of the \"{sheet}\" sheet's data, which spans {}:{}.",
+                        args.flag_range.as_deref().unwrap_or_default(),
+                        a1_cell(range_start),
+                        a1_cell(range_end)
+                    );
+                }
+                resize_range(range, start, end)
+            }
+            None => range,
+        };
+        let range = if args.flag_transpose {
+            transpose_range(range)
+        } else {
+            range
+        };
 
-    // we sort the whitelist, so we can do the faster binary_search() instead of contains()
-    // with an all_numbers_whitelist
-    if all_numbers_whitelist {
-        dates_whitelist.sort_unstable();
-    }
+        // formulas are exported verbatim, so date processing is disabled with --formulas
+        let whitelist_lower = if args.flag_formulas {
+            "none".to_string()
+        } else if let Some(whitelist_path) = args.flag_dates_whitelist.strip_prefix('@') {
+            match fs::read_to_string(whitelist_path) {
+                Ok(whitelist_file) => whitelist_file
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .join(",")
+                    .to_lowercase(),
+                Err(e) => {
+                    return fail_clierror!("Cannot read dates whitelist file {whitelist_path}: {e}")
+                }
+            }
+        } else {
+            args.flag_dates_whitelist.to_lowercase()
+        };
+        info!("using date-whitelist: {whitelist_lower}");
 
-    // use with_capacity to minimize reallocations
-    let mut trimmed_record = csv::StringRecord::with_capacity(200, 20);
-    let mut date_flag: Vec<bool> = Vec::with_capacity(20);
+        // the numbers of the whitelist are the column indices of the date columns,
+        // and the rest are the patterns to look for in the column names
+        let mut whitelist_indices: Vec<usize> = Vec::new();
+        let mut dates_whitelist: Vec<String> = Vec::new();
+        for item in whitelist_lower.split(',').map(str::trim) {
+            match item.parse::<u16>() {
+                Ok(col_idx) => whitelist_indices.push(col_idx as usize),
+                Err(_) => dates_whitelist.push(item.to_string()),
+            }
+        }
 
-    let mut cell_date_flag: bool;
-    let mut float_val = 0_f64;
-    let mut float_flag: bool;
-    let mut row_count = 0_usize;
+        // we sort the column indices, so we can do the faster binary_search() instead of
+        // contains()
+        whitelist_indices.sort_unstable();
+        whitelist_indices.dedup();
+        // the indices beyond the last column are likely from another sheet layout
+        let num_columns = range.width();
+        for col_idx in whitelist_indices
+            .iter()
+            .filter(|col_idx| **col_idx >= num_columns)
+        {
+            if args.flag_strict_whitelist {
+                return fail_clierror!(
+                    "--dates-whitelist index {col_idx} is beyond the last column of the \
+                     \"{sheet}\" sheet, which has {num_columns} columns."
+                );
+            }
+            if !args.flag_quiet {
+                winfo!(
+                    "WARNING: --dates-whitelist index {col_idx} is beyond the last column of the \
+                     \"{sheet}\" sheet, which has {num_columns} columns"
+                );
+            }
+        }
+        // the whitelist items that matched a column, to warn about the ones that did not
+        let mut index_matched = vec![false; whitelist_indices.len()];
+        let mut pattern_matched = vec![false; dates_whitelist.len()];
 
-    info!("exporting sheet ({sheet})...");
-    for (row_idx, row) in range.rows().enumerate() {
-        record.clear();
-        for (col_idx, cell) in row.iter().enumerate() {
-            if row_idx == 0 {
-                // its the header row, check the dates whitelist
-                info!("processing first row...");
-                let col_name = cell.get_string().unwrap_or_default();
-                record.push_field(col_name);
-                match whitelist_lower.as_str() {
-                    // "all" - all numeric fields are to be treated as dates
-                    "all" => date_flag.insert(col_idx, true),
-                    // "none" - date processing will not be attempted
-                    "none" => date_flag.insert(col_idx, false),
-                    // check if the column name is in the dates_whitelist
-                    _ => date_flag.insert(
-                        col_idx,
-                        if all_numbers_whitelist {
-                            dates_whitelist.binary_search(&col_idx.to_string()).is_ok()
-                        } else {
-                            let mut date_found = false;
-                            let col_name_lower = col_name.to_lowercase();
-                            for whitelist_item in &dates_whitelist {
-                                if col_name_lower.contains(whitelist_item) {
-                                    date_found = true;
-                                    info!("date-whitelisted: {col_name}");
-                                    break;
-                                }
-                            }
-                            date_found
-                        },
-                    ),
+        let mut whitelist_regexes: Vec<regex::Regex> = Vec::new();
+        if args.flag_dates_whitelist_regex {
+            for pattern in &dates_whitelist {
+                match regex::Regex::new(pattern) {
+                    Ok(re) => whitelist_regexes.push(re),
+                    Err(e) => {
+                        return fail_clierror!("Invalid --dates-whitelist regex {pattern}: {e}")
+                    }
                 }
-                info!("date_flag: {date_flag:?}");
-                continue;
             }
-            cell_date_flag = false;
-            float_flag = false;
-            match *cell {
-                DataType::Empty => record.push_field(""),
-                DataType::String(ref s) => record.push_field(s),
-                DataType::Int(ref i) => {
-                    let mut buffer = itoa::Buffer::new();
-                    record.push_field(buffer.format(*i));
+        }
+
+        let header_row = if args.flag_no_headers {
+            0
+        } else if args.flag_detect_header_row {
+            if let Some(detected_row) = detect_header_row(&range) {
+                info!("detected header row: {detected_row}");
+                detected_row
+            } else {
+                info!("cannot detect header row. Using the first row instead.");
+                0
+            }
+        } else {
+            args.flag_header_row
+        };
+        if header_row > 0 && header_row >= range.height() {
+            return fail_clierror!(
+                "--header-row {header_row} is beyond the last row of the \"{sheet}\" sheet, which \
+                 has {} rows.",
+                range.height()
+            );
+        }
+
+        // use with_capacity to minimize reallocations
+        let mut date_flag: Vec<bool> = Vec::with_capacity(20);
+        // the JSON types of the fields of the exported records, and the
+        // header names they're keyed by, with --jsonl
+        let mut kinds: Vec<JsonKind> = Vec::with_capacity(20);
+        // the --select columns, resolved against the header row
+        let mut selected_columns: Option<NormalSelection> = None;
+        // with --auto-widen, the index of the first trailing column without a header.
+        // With --no-headers, all the columns are named after their column number
+        let mut widen_from: Option<usize> = if args.flag_no_headers { Some(0) } else { None };
+        let mut jsonl_keys: Vec<String> = Vec::new();
+
+        // with --schema, the exported columns of the sheet & their names, and
+        // the type counts of the data cells of each column of the sheet
+        let mut schema_columns: Vec<(usize, String)> = Vec::new();
+        let mut schema_type_counts = vec![[0_usize; SCHEMA_TYPES.len()]; range.width()];
+
+        let mut row_count = 0_usize;
+
+        // the first row below the header row, which is the first row with --no-headers
+        let first_data_row = if args.flag_no_headers {
+            0
+        } else {
+            header_row + 1
+        };
+        if args.flag_dump_types {
+            let Some(wtr) = wtr.as_mut() else {
+                continue;
+            };
+            if !types_header_written && !skip_header {
+                let mut types_header = csv::StringRecord::new();
+                if num_exported_sheets > 1 {
+                    types_header.push_field("sheet");
                 }
-                DataType::DateTime(ref f) => {
-                    float_val = *f;
-                    float_flag = true;
-                    cell_date_flag = true;
+                types_header.extend([
+                    "column_name",
+                    "ints",
+                    "floats",
+                    "strings",
+                    "dates",
+                    "bools",
+                    "errors",
+                    "empties",
+                ]);
+                wtr.write(true, &[], &types_header, &[])?;
+            }
+            types_header_written = true;
+            let widen_from = if args.flag_no_headers { Some(0) } else { None };
+            let header_cells = range.rows().nth(header_row).unwrap_or_default();
+            let mut types_record = csv::StringRecord::new();
+            for (col_idx, counts) in column_type_counts(&range, first_data_row)
+                .iter()
+                .enumerate()
+            {
+                let col_name = header_cells.get(col_idx).map_or_else(
+                    || Cow::Owned(format!("col_{}", col_idx + 1)),
+                    |cell| header_cell_name(cell, col_idx, widen_from),
+                );
+                types_record.clear();
+                if num_exported_sheets > 1 {
+                    types_record.push_field(&sheet);
                 }
-                DataType::Float(ref f) => {
-                    float_val = *f;
-                    float_flag = true;
-                    cell_date_flag = date_flag[col_idx];
+                match options.header_case {
+                    Some(header_case) => {
+                        types_record.push_field(&convert_header_case(&col_name, header_case));
+                    }
+                    None => types_record.push_field(&col_name),
                 }
-                DataType::Error(ref e) => record.push_field(&format!("{e:?}")),
-                DataType::Bool(ref b) => record.push_field(&b.to_string()),
-            };
-
-            // dates are stored as floats in Excel
-            // that's why we need the --dates-whitelist, so we can convert the float to a date.
-            // However, with the XLSX format, we can get a cell's format as an attribute. So we can
-            // automatically process a cell as a date, even if its column is NOT in the whitelist
-            if float_flag {
-                if cell_date_flag {
-                    if float_val.fract() > 0.0 {
-                        record.push_field({
-                            &cell.as_datetime().map_or_else(
-                                || format!("ERROR: Cannot convert {float_val} to datetime"),
-                                |dt| format!("{dt}"),
-                            )
-                        });
-                    } else {
-                        record.push_field({
-                            &cell.as_date().map_or_else(
-                                || format!("ERROR: Cannot convert {float_val} to date"),
-                                |d| format!("{d}"),
-                            )
-                        });
-                    };
-                } else {
-                    record.push_field(&float_val.to_string());
+                for count in counts {
+                    types_record.push_field(itoa::Buffer::new().format(*count));
                 }
+                wtr.write(false, &[], &types_record, &[])?;
             }
+            wtr.flush()?;
+            continue;
         }
+        let inferred_dates = if args.flag_infer_dates {
+            infer_date_columns(&range, first_data_row, args.flag_infer_dates_sample)
+        } else {
+            vec![]
+        };
+        let coerce_types = if args.flag_coerce {
+            majority_types(&range, first_data_row)
+        } else {
+            vec![]
+        };
+        let mut coerced_count = 0_usize;
+        let mut outlier_count = 0_usize;
+        let mut date_columns: Vec<String> = Vec::new();
 
-        if args.flag_trim {
-            record.trim();
-            trimmed_record.clear();
-            record.iter().for_each(|field| {
-                if field.contains('\n') {
-                    trimmed_record.push_field(&field.to_string().replace('\n', " "));
-                } else {
-                    trimmed_record.push_field(field);
+        let formula_range = if args.flag_formulas_with_values || args.flag_formulas {
+            // calamine cannot reliably read the formula text of xls & xlsb files
+            let formula_result = if matches!(format.as_str(), "xls" | "xlsb") {
+                Err(format!("formulas cannot be read from {format} files"))
+            } else {
+                match workbook.worksheet_formula(&sheet) {
+                    Some(Ok(formula_range)) => Ok(formula_range),
+                    Some(Err(e)) => Err(format!("cannot retrieve formulas from {sheet}: {e}")),
+                    None => Err(format!("cannot retrieve formulas from {sheet}")),
                 }
-            });
-            wtr.write_record(&trimmed_record)?;
+            };
+            match formula_result {
+                Ok(formula_range) => formula_range,
+                Err(e) if args.flag_strict_formulas => {
+                    return fail_clierror!(
+                        "{}: {e}",
+                        if args.flag_formulas {
+                            "--formulas"
+                        } else {
+                            "--formulas-with-values"
+                        }
+                    );
+                }
+                Err(e) => {
+                    if !formula_fallback_warned {
+                        winfo!("{e}. Only exporting the cached values of the formulas.");
+                        formula_fallback_warned = true;
+                    }
+                    Range::empty()
+                }
+            }
         } else {
-            wtr.write_record(&record)?;
-        }
-        row_count += 1;
-    }
-    wtr.flush()?;
+            Range::empty()
+        };
+        let formula_columns = if args.flag_formulas {
+            vec![false; range.width()]
+        } else {
+            formula_columns(&range, &formula_range, header_row)
+        };
+        let mut row_pipeline = RowPipeline::new(
+            &args,
+            &options,
+            &sheet,
+            input,
+            with_sheet_name,
+            sheet_details.comments,
+        )?;
+        // the rows filtered out by --row-filter
+        let mut filtered_count = 0_usize;
+        let mut padded_record = csv::StringRecord::new();
+        let mut padded_kinds: Vec<JsonKind> = Vec::new();
+        let mut split_files = args
+            .flag_split_by
+            .as_deref()
+            .map(|column| SplitFiles::new(&args, column, output.clone().unwrap_or_default()));
+        let mut exported_columns = 0_usize;
+        let (start_row, start_col) = range.start().unwrap_or_default();
+        let hidden_rows = if args.flag_skip_hidden_rows {
+            sheet_details.hidden_rows
+        } else {
+            HashSet::new()
+        };
+        let hidden_cols = if args.flag_skip_hidden_cols {
+            sheet_details.hidden_cols
+        } else {
+            HashSet::new()
+        };
+        let mut hidden_row_count = 0_usize;
+        let mut empty_row_count = 0_usize;
+        let mut cell_converter = CellConverter {
+            args:                 &args,
+            options:              &options,
+            sheet:                &sheet,
+            zero_padded_cells:    sheet_details.zero_padded_cells,
+            duration_cells:       sheet_details.duration_cells,
+            percent_cells:        sheet_details.percent_cells,
+            numeric_string_count: 0,
+            truncated_count:      0,
+            imprecise_count:      0,
+            error_cell_counts:    BTreeMap::new(),
+        };
+        let autofilter = if args.flag_apply_autofilter {
+            let autofilter = sheet_details.autofilter;
+            match autofilter {
+                Some(ref autofilter) => {
+                    for (col, criteria) in &autofilter.columns {
+                        if let FilterCriteria::Unsupported(kind) = criteria {
+                            winfo!(
+                                "cannot interpret the {kind} AutoFilter criteria of column {} of \
+                                 the \"{sheet}\" sheet. Ignoring it.",
+                                col + 1
+                            );
+                        }
+                    }
+                }
+                None => {
+                    winfo!(
+                        r#"no AutoFilter found in the "{sheet}" sheet. Exporting all the rows."#
+                    );
+                }
+            }
+            autofilter
+        } else {
+            None
+        };
+        let mut autofilter_count = 0_usize;
 
-    if !args.flag_quiet {
-        let end_msg = format!(
-            "{} {}-column rows exported from \"{sheet}\" sheet",
-            // don't count the header in row count
-            row_count.saturating_sub(1).separate_with_commas(),
-            record.len().separate_with_commas(),
-        );
-        winfo!("{end_msg}");
-    }
+        // prep progress bar
+        #[cfg(any(feature = "full", feature = "lite"))]
+        let show_progress = args.flag_progressbar || std::env::var("QSV_PROGRESSBAR").is_ok();
+        #[cfg(any(feature = "full", feature = "lite"))]
+        let progress = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr_with_hz(5));
+        #[cfg(any(feature = "full", feature = "lite"))]
+        if show_progress {
+            util::prep_progress(&progress, range.height() as u64);
+            progress.set_message(format!(" of {} rows", HumanCount(range.height() as u64)));
+        } else {
+            progress.set_draw_target(ProgressDrawTarget::hidden());
+        }
 
-    Ok(())
+        info!("exporting sheet ({sheet})...");
+        // with --no-headers, the first row is exported twice: as the synthesized header row,
+        // then as data
+        let rows = range.rows().enumerate().flat_map(|(row_idx, row)| {
+            let header_pass = (row_idx == header_row).then_some((row_idx, row, true));
+            let data_pass =
+                (row_idx != header_row || args.flag_no_headers).then_some((row_idx, row, false));
+            header_pass.into_iter().chain(data_pass)
+        });
+        for (row_idx, row, is_header) in rows {
+            #[cfg(any(feature = "full", feature = "lite"))]
+            if show_progress && !(is_header && args.flag_no_headers) {
+                progress.inc(1);
+            }
+            // skip the preamble rows above the header row
+            if row_idx < header_row {
+                continue;
+            }
+            if !is_header && hidden_rows.contains(&(start_row + row_idx as u32)) {
+                hidden_row_count += 1;
+                continue;
+            }
+            if args.flag_skip_empty
+                && !is_header
+                && row.iter().all(|cell| {
+                    cell.is_empty() || matches!(cell, DataType::String(s) if s.trim().is_empty())
+                })
+            {
+                empty_row_count += 1;
+                continue;
+            }
+            if let Some(ref autofilter) = autofilter {
+                if !is_header && !autofilter.matches(start_row + row_idx as u32, start_col, row) {
+                    autofilter_count += 1;
+                    continue;
+                }
+            }
+            if is_header && args.flag_auto_widen && !args.flag_no_headers {
+                let header_width = row
+                    .iter()
+                    .rposition(|cell| {
+                        !cell.is_empty() && !matches!(cell, DataType::String(s) if s.is_empty())
+                    })
+                    .map_or(0, |idx| idx + 1);
+                if header_width < row.len() {
+                    widen_from = Some(header_width);
+                    info!(
+                        "synthesized headers: {:?}",
+                        (header_width..row.len())
+                            .map(|col_idx| format!("col_{}", col_idx + 1))
+                            .collect_vec()
+                    );
+                }
+            }
+            if is_header {
+                if let Some(ref select) = args.flag_select {
+                    let header_names: csv::ByteRecord = row
+                        .iter()
+                        .enumerate()
+                        .map(|(col_idx, cell)| {
+                            let col_name = header_cell_name(cell, col_idx, widen_from);
+                            match options.header_case {
+                                Some(header_case) => convert_header_case(&col_name, header_case),
+                                None => col_name.into_owned(),
+                            }
+                        })
+                        .collect();
+                    match select.selection(&header_names, true) {
+                        Ok(selection) => selected_columns = Some(selection.normal()),
+                        Err(e) => {
+                            return fail_clierror!(
+                                "Cannot select the columns of the \"{sheet}\" sheet: {e}"
+                            );
+                        }
+                    }
+                }
+            }
+            record.clear();
+            kinds.clear();
+            for (col_idx, cell) in row.iter().enumerate() {
+                let unselected = selected_columns.as_ref().map_or(false, |selected| {
+                    !selected.get(col_idx).copied().unwrap_or(false)
+                });
+                if unselected || hidden_cols.contains(&(start_col + col_idx as u32)) {
+                    if is_header {
+                        // keep the date flags aligned with the column indices
+                        date_flag.insert(col_idx, false);
+                    }
+                    continue;
+                }
+                if is_header {
+                    // its the header row, check the dates whitelist
+                    info!("processing first row...");
+                    let col_name = header_cell_name(cell, col_idx, widen_from);
+                    match options.header_case {
+                        Some(header_case) => {
+                            record.push_field(&convert_header_case(&col_name, header_case));
+                        }
+                        None => record.push_field(&col_name),
+                    }
+                    if args.flag_schema.is_some() {
+                        let export_name = record.iter().last().unwrap_or_default();
+                        schema_columns.push((col_idx, export_name.to_string()));
+                    }
+                    match whitelist_lower.as_str() {
+                        // --infer-dates - the columns whose sampled values look like dates
+                        _ if !inferred_dates.is_empty() => {
+                            if inferred_dates[col_idx] {
+                                info!("date-inferred: {col_name}");
+                            }
+                            date_flag.insert(col_idx, inferred_dates[col_idx]);
+                        }
+                        // "all" - all numeric fields are to be treated as dates
+                        "all" => date_flag.insert(col_idx, true),
+                        // "none" - date processing will not be attempted
+                        "none" => date_flag.insert(col_idx, false),
+                        // check if the column index or name is in the dates_whitelist
+                        _ => {
+                            let col_name_lower = col_name.to_lowercase();
+                            let mut date_found = false;
+                            if let Ok(pos) = whitelist_indices.binary_search(&col_idx) {
+                                index_matched[pos] = true;
+                                date_found = true;
+                            }
+                            // without a header row, there are no column names to match
+                            let name_patterns = if args.flag_no_headers {
+                                &[][..]
+                            } else {
+                                &dates_whitelist[..]
+                            };
+                            for (pos, whitelist_item) in name_patterns.iter().enumerate() {
+                                let pattern_found = if args.flag_dates_whitelist_regex {
+                                    whitelist_regexes[pos].is_match(&col_name_lower)
+                                } else {
+                                    col_name_lower.contains(whitelist_item)
+                                };
+                                if pattern_found {
+                                    pattern_matched[pos] = true;
+                                    date_found = true;
+                                }
+                            }
+                            if date_found {
+                                info!("date-whitelisted: {col_name}");
+                            }
+                            date_flag.insert(col_idx, date_found);
+                        }
+                    }
+                    let date_suffixed = options.date_suffix && date_flag[col_idx];
+                    if date_suffixed {
+                        date_columns.push(format!("{col_name}_date"));
+                    } else if date_flag[col_idx] {
+                        date_columns.push(col_name.to_string());
+                    }
+                    info!("date_flag: {date_flag:?}");
+                    if date_suffixed {
+                        record.push_field(&format!("{col_name}_date"));
+                    } else if options.keep_serial && date_flag[col_idx] {
+                        record.push_field(&format!("{col_name}_serial"));
+                    }
+                    if formula_columns[col_idx] {
+                        record.push_field(&format!("{col_name}_formula"));
+                    }
+                    continue;
+                }
+                if args.flag_formulas {
+                    match formula_range
+                        .get_value((start_row + row_idx as u32, start_col + col_idx as u32))
+                    {
+                        Some(formula) if !formula.is_empty() => {
+                            record.push_field(&format!("={formula}"));
+                            kinds.push(JsonKind::String);
+                            continue;
+                        }
+                        _ => {}
+                    }
+                }
+                let coerced_cell;
+                let cell = match (coerce_types.get(col_idx).copied().flatten(), cell) {
+                    (Some(CoerceType::Numeric), DataType::String(s)) => {
+                        if let Some(number) = parse_numeric(s) {
+                            coerced_count += 1;
+                            coerced_cell = DataType::Float(number);
+                            &coerced_cell
+                        } else {
+                            if !s.trim().is_empty() {
+                                outlier_count += 1;
+                                info!("outlier in numeric column {col_idx}, row {row_idx}: {s:?}");
+                            }
+                            cell
+                        }
+                    }
+                    (Some(CoerceType::Text), DataType::Int(_) | DataType::Float(_)) => {
+                        coerced_count += 1;
+                        coerced_cell = DataType::String(cell.to_string());
+                        &coerced_cell
+                    }
+                    _ => cell,
+                };
+                let position = (start_row + row_idx as u32, start_col + col_idx as u32);
+                let type_idx = cell_converter.convert_cell(
+                    cell,
+                    position,
+                    date_flag[col_idx],
+                    &mut record,
+                    &mut kinds,
+                )?;
+                if args.flag_schema.is_some() {
+                    if let (Some(type_idx), Some(col_counts)) =
+                        (type_idx, schema_type_counts.get_mut(col_idx))
+                    {
+                        col_counts[type_idx] += 1;
+                    }
+                }
+
+                if formula_columns[col_idx] {
+                    match formula_range.get_value(position) {
+                        Some(formula) if !formula.is_empty() => {
+                            record.push_field(&format!("={formula}"));
+                            kinds.push(JsonKind::String);
+                        }
+                        _ => {
+                            record.push_field("");
+                            kinds.push(JsonKind::Null);
+                        }
+                    }
+                }
+            }
+
+            let Some((export_record, export_kinds)) = row_pipeline.process_row(
+                is_header,
+                start_row + row_idx as u32,
+                &mut record,
+                &mut kinds,
+            )?
+            else {
+                filtered_count += 1;
+                continue;
+            };
+            if is_header && args.flag_jsonl {
+                jsonl_keys = export_record.iter().map(String::from).collect();
+            }
+            if is_header && !per_sheet_output {
+                if let Some(first_header_row) = first_headers.as_ref() {
+                    if !first_header_row.iter().eq(export_record.iter()) {
+                        if headers_from_first_workbook {
+                            if !args.flag_flexible {
+                                return fail_clierror!(
+                                    "The \"{sheet}\" sheet of {input} has a different header row \
+                                     than the first workbook. Use --flexible to concatenate the \
+                                     workbooks anyway."
+                                );
+                            }
+                            winfo!(
+                                r#"the "{sheet}" sheet of {input} has a different header row than the first workbook"#
+                            );
+                        } else {
+                            if !args.flag_flexible {
+                                return fail_clierror!(
+                                    "The \"{sheet}\" sheet has a different header row than the \
+                                     first sheet. Use --flexible to concatenate the sheets anyway."
+                                );
+                            }
+                            winfo!(
+                                r#"the "{sheet}" sheet has a different header row than the first sheet"#
+                            );
+                        }
+                    }
+                    // the concatenated sheets only have the header row of the first sheet
+                    row_count += 1;
+                    continue;
+                }
+                *first_headers = Some(export_record.clone());
+            }
+            let (export_record, export_kinds) = if !args.flag_pad_rows {
+                (export_record, export_kinds)
+            } else if is_header {
+                pad_width = export_record.len();
+                (export_record, export_kinds)
+            } else if export_record.len() == pad_width {
+                (export_record, export_kinds)
+            } else {
+                padded_record.clear();
+                padded_record.extend(export_record.iter().take(pad_width));
+                for _ in padded_record.len()..pad_width {
+                    padded_record.push_field(options.empty_token);
+                }
+                padded_kinds.clear();
+                padded_kinds.extend(export_kinds.iter().take(pad_width));
+                padded_kinds.resize(pad_width, JsonKind::Null);
+                (&padded_record, padded_kinds.as_slice())
+            };
+            if let Some(ref mut split_files) = split_files {
+                split_files.write(
+                    is_header,
+                    &jsonl_keys,
+                    export_record,
+                    export_kinds,
+                    row_count,
+                )?;
+            } else if let Some(wtr) = wtr.as_mut() {
+                if !(is_header && skip_header) {
+                    wtr.write(is_header, &jsonl_keys, export_record, export_kinds)?;
+                }
+            }
+            exported_columns = export_record.len();
+            row_count += 1;
+            // row_count includes the header row
+            if let Some(max_rows) = args.flag_max_rows {
+                if row_count > max_rows {
+                    info!("stopped exporting the \"{sheet}\" sheet at --max-rows {max_rows}");
+                    break;
+                }
+            }
+        }
+        if let Some(wtr) = wtr {
+            wtr.flush()?;
+        }
+        if let Some(ref mut split_files) = split_files {
+            split_files.finish()?;
+        }
+
+        #[cfg(any(feature = "full", feature = "lite"))]
+        if show_progress {
+            progress.set_message(format!(
+                " - {} rows exported from \"{sheet}\" sheet",
+                HumanCount(row_count.saturating_sub(1) as u64)
+            ));
+            util::finish_progress(&progress);
+        }
+
+        if empty_row_count > 0 {
+            info!("skipped {empty_row_count} empty rows");
+        }
+        if hidden_row_count > 0 || !hidden_cols.is_empty() {
+            info!(
+                "skipped {hidden_row_count} hidden rows and {} hidden columns",
+                hidden_cols.len()
+            );
+        }
+        // the dates whitelist is only used when it is not "all" or "none", nor --infer-dates
+        if args.flag_dates_whitelist != DEFAULT_DATES_WHITELIST
+            && inferred_dates.is_empty()
+            && !matches!(whitelist_lower.as_str(), "all" | "none")
+            && !args.flag_quiet
+        {
+            let unmatched_items = whitelist_indices
+                .iter()
+                .zip(&index_matched)
+                // the indices beyond the last column were already warned about
+                .filter(|(col_idx, matched)| !**matched && **col_idx < num_columns)
+                .map(|(col_idx, _)| col_idx.to_string())
+                .chain(
+                    dates_whitelist
+                        .iter()
+                        .zip(&pattern_matched)
+                        .filter(|(_, matched)| !**matched)
+                        .map(|(pattern, _)| pattern.clone()),
+                );
+            for unmatched_item in unmatched_items {
+                winfo!(r#"WARNING: date-whitelist pattern "{unmatched_item}" matched no columns"#);
+            }
+        }
+        if autofilter.is_some() && !args.flag_quiet {
+            winfo!(
+                "{} rows filtered out by the AutoFilter",
+                autofilter_count.separate_with_commas()
+            );
+        }
+        if args.flag_row_filter.is_some() && !args.flag_quiet {
+            winfo!(
+                "{} rows filtered out by --row-filter",
+                filtered_count.separate_with_commas()
+            );
+        }
+        if args.flag_coerce && !args.flag_quiet {
+            winfo!(
+                "{} cells coerced, {} outliers left as-is",
+                coerced_count.separate_with_commas(),
+                outlier_count.separate_with_commas()
+            );
+        }
+        if args.flag_coerce_numeric_strings && !args.flag_quiet {
+            winfo!(
+                "{} numeric text cells coerced",
+                cell_converter.numeric_string_count.separate_with_commas()
+            );
+        }
+        if let Some(max_length) = args.flag_max_cell_length {
+            if !args.flag_quiet {
+                winfo!(
+                    "{} cells truncated to {max_length} characters",
+                    cell_converter.truncated_count.separate_with_commas()
+                );
+            }
+        }
+        if cell_converter.imprecise_count > 0 && !args.flag_quiet {
+            winfo!(
+                "WARNING: {} numeric cells are integers larger than {}, which Excel cannot store \
+                 exactly, so their last digits may have been lost (e.g. in IDs entered as \
+                 numbers).",
+                cell_converter.imprecise_count.separate_with_commas(),
+                (MAX_EXACT_INTEGER as u64).separate_with_commas()
+            );
+        }
+        let end_msg = format!(
+            "{} {}-column rows exported from \"{sheet}\" sheet",
+            // don't count the header in row count
+            row_count.saturating_sub(1).separate_with_commas(),
+            exported_columns.separate_with_commas(),
+        );
+        // with --quiet or --stats-json, the summary is still logged
+        if args.flag_quiet || args.flag_stats_json {
+            info!("{end_msg}");
+        } else {
+            winfo!("{end_msg}");
+        }
+
+        if let Some(ref schema_path) = args.flag_schema {
+            let schema = ExportSchema {
+                sheet:   sheet.clone(),
+                columns: schema_columns
+                    .iter()
+                    .map(|(col_idx, name)| SchemaColumn {
+                        name:     name.clone(),
+                        col_type: schema_type_counts
+                            .get(*col_idx)
+                            .map_or("string", schema_type),
+                        date:     date_flag.get(*col_idx).copied().unwrap_or_default(),
+                    })
+                    .collect(),
+            };
+            let Ok(schema_json) = serde_json::to_string_pretty(&schema) else {
+                return fail!("Cannot create export schema JSON");
+            };
+            if let Err(e) = fs::write(schema_path, schema_json) {
+                return fail_clierror!("Cannot write export schema to {schema_path}: {e}");
+            }
+        }
+
+        if args.flag_summary_json.is_some() || args.flag_stats_json {
+            let summary = ExportSummary {
+                filename: filename.clone(),
+                format: format.clone(),
+                sheet: sheet.clone(),
+                sheet_index,
+                output,
+                // don't count the header in row count
+                rows: row_count.saturating_sub(1),
+                columns: exported_columns,
+                date_columns,
+                error_cells: cell_converter.error_cell_counts.values().sum(),
+                error_cell_counts: cell_converter.error_cell_counts,
+            };
+            if args.flag_stats_json {
+                let Ok(stats_json) = serde_json::to_string(&summary) else {
+                    return fail!("Cannot create export stats JSON");
+                };
+                eprintln!("{stats_json}");
+            }
+            if let Some(ref summary_path) = args.flag_summary_json {
+                let Ok(summary_json) = serde_json::to_string_pretty(&summary) else {
+                    return fail!("Cannot create export summary JSON");
+                };
+                if let Err(e) = fs::write(summary_path, summary_json) {
+                    return fail_clierror!("Cannot write export summary to {summary_path}: {e}");
+                }
+            }
+        }
+        // don't count the header in row count
+        total_row_count += row_count.saturating_sub(1);
+        sheet_counts.push((sheet, row_count.saturating_sub(1)));
+    }
+
+    if args.flag_count_only {
+        let mut wtr = Config::new(&args.flag_output)
+            .delimiter(args.flag_delimiter)
+            .writer()?;
+        if let [(_, count)] = &sheet_counts[..] {
+            wtr.write_record([count.to_string()])?;
+        } else {
+            wtr.write_record(["sheet", "rows"])?;
+            for (sheet, count) in &sheet_counts {
+                wtr.write_record([sheet, &count.to_string()])?;
+            }
+        }
+        wtr.flush()?;
+    }
+
+    if num_exported_sheets > 1 && !args.flag_quiet && !args.flag_stats_json && !args.flag_dump_types
+    {
+        winfo!(
+            "{} rows exported from {num_exported_sheets} sheets",
+            total_row_count.separate_with_commas()
+        );
+    }
+
+    Ok(total_row_count)
+}
+
+// the steps that the converted records of a sheet go through before they are exported:
+// --trim, --dedup-headers, --row-filter, --concat, --comments-column & the source columns
+struct RowPipeline<'a> {
+    args:              &'a Args,
+    empty_token:       &'a str,
+    sheet:             &'a str,
+    input:             &'a str,
+    with_sheet_name:   bool,
+    // with --trim-columns, whether each exported column is trimmed
+    trim_flag:         Vec<bool>,
+    trimmed_record:    csv::StringRecord,
+    deduped_headers:   csv::StringRecord,
+    row_filter:        Option<RowFilter>,
+    // the index of the --row-filter column in the exported records
+    row_filter_idx:    usize,
+    concat_specs:      Vec<ConcatSpec>,
+    concat_sep:        &'a str,
+    // the indices of the source columns of each --concat spec in the exported records
+    concat_indices:    Vec<Vec<usize>>,
+    concat_record:     csv::StringRecord,
+    concat_kinds:      Vec<JsonKind>,
+    comments:          HashMap<u32, Vec<String>>,
+    commented_record:  csv::StringRecord,
+    commented_kinds:   Vec<JsonKind>,
+    sheet_name_record: csv::StringRecord,
+    sheet_name_kinds:  Vec<JsonKind>,
+}
+
+impl<'a> RowPipeline<'a> {
+    fn new(
+        args: &'a Args,
+        options: &ExportOptions<'a>,
+        sheet: &'a str,
+        input: &'a str,
+        with_sheet_name: bool,
+        comments: HashMap<u32, Vec<String>>,
+    ) -> CliResult<Self> {
+        let concat_specs = match args.flag_concat {
+            Some(ref specs) => ConcatSpec::parse_specs(specs)?,
+            None => Vec::new(),
+        };
+        Ok(Self {
+            args,
+            empty_token: options.empty_token,
+            sheet,
+            input,
+            with_sheet_name,
+            trim_flag: Vec::new(),
+            // use with_capacity to minimize reallocations
+            trimmed_record: csv::StringRecord::with_capacity(200, 20),
+            deduped_headers: csv::StringRecord::new(),
+            row_filter: args.flag_row_filter.as_deref().map(RowFilter::parse),
+            row_filter_idx: 0,
+            concat_indices: Vec::with_capacity(concat_specs.len()),
+            concat_specs,
+            concat_sep: args.flag_concat_sep.as_deref().unwrap_or(" "),
+            concat_record: csv::StringRecord::new(),
+            concat_kinds: Vec::new(),
+            comments,
+            commented_record: csv::StringRecord::new(),
+            commented_kinds: Vec::new(),
+            sheet_name_record: csv::StringRecord::new(),
+            sheet_name_kinds: Vec::new(),
+        })
+    }
+
+    /// returns the exported record & JSON kinds of the converted record & kinds of the
+    /// sheet row, or None when the row is filtered out by --row-filter. The header row
+    /// resolves the columns of --trim-columns, --row-filter & --concat.
+    fn process_row<'r>(
+        &'r mut self,
+        is_header: bool,
+        row: u32,
+        record: &'r mut csv::StringRecord,
+        kinds: &'r mut Vec<JsonKind>,
+    ) -> CliResult<Option<(&'r csv::StringRecord, &'r [JsonKind])>> {
+        let args = self.args;
+        let sheet = self.sheet;
+        let export_record: &csv::StringRecord =
+            if let Some(ref trim_columns) = args.flag_trim_columns {
+                if is_header {
+                    let header_names: csv::ByteRecord = record.iter().map(str::trim).collect();
+                    match trim_columns.selection(&header_names, true) {
+                        Ok(selection) => {
+                            let selection = selection.normal();
+                            self.trim_flag = (0..record.len())
+                                .map(|idx| selection.get(idx).copied().unwrap_or(false))
+                                .collect();
+                        }
+                        Err(e) => {
+                            return fail_clierror!(
+                                "Cannot select the --trim-columns of the \"{sheet}\" sheet: {e}"
+                            );
+                        }
+                    }
+                }
+                self.trimmed_record.clear();
+                for (idx, field) in record.iter().enumerate() {
+                    if !is_header && !self.trim_flag.get(idx).copied().unwrap_or(false) {
+                        self.trimmed_record.push_field(field);
+                        continue;
+                    }
+                    let field = field.trim();
+                    if field.is_empty()
+                        && !is_header
+                        && args.flag_empty_token.is_some()
+                        && matches!(kinds.get(idx), Some(JsonKind::String))
+                    {
+                        // the text cells that are empty once trimmed are empty cells too
+                        self.trimmed_record.push_field(self.empty_token);
+                        kinds[idx] = JsonKind::Null;
+                    } else if field.contains('\n') && !args.flag_keep_newlines {
+                        self.trimmed_record.push_field(&field.replace('\n', " "));
+                    } else {
+                        self.trimmed_record.push_field(field);
+                    }
+                }
+                &self.trimmed_record
+            } else if args.flag_trim {
+                record.trim();
+                self.trimmed_record.clear();
+                record.iter().for_each(|field| {
+                    if field.contains('\n') && !args.flag_keep_newlines {
+                        self.trimmed_record
+                            .push_field(&field.to_string().replace('\n', " "));
+                    } else {
+                        self.trimmed_record.push_field(field);
+                    }
+                });
+                &self.trimmed_record
+            } else {
+                record
+            };
+        let export_record = if is_header && args.flag_dedup_headers {
+            self.deduped_headers = dedup_header_names(export_record);
+            &self.deduped_headers
+        } else {
+            export_record
+        };
+        if let Some(ref row_filter) = self.row_filter {
+            if is_header {
+                let Some(idx) = export_record
+                    .iter()
+                    .position(|col_name| col_name == row_filter.column)
+                else {
+                    return fail_clierror!(
+                        r#"--row-filter column "{}" not found in the header row."#,
+                        row_filter.column
+                    );
+                };
+                self.row_filter_idx = idx;
+            } else if !row_filter
+                .matches(export_record.get(self.row_filter_idx).unwrap_or_default())
+            {
+                return Ok(None);
+            }
+        }
+        let (export_record, export_kinds) = if self.concat_specs.is_empty() {
+            (export_record, kinds.as_slice())
+        } else {
+            if is_header {
+                for spec in &self.concat_specs {
+                    let mut indices = Vec::with_capacity(spec.sources.len());
+                    for source in &spec.sources {
+                        let Some(idx) =
+                            export_record.iter().position(|col_name| col_name == source)
+                        else {
+                            return fail_clierror!(
+                                r#"--concat column "{source}" not found in the header row."#
+                            );
+                        };
+                        indices.push(idx);
+                    }
+                    self.concat_indices.push(indices);
+                }
+            }
+            self.concat_record.clear();
+            self.concat_kinds.clear();
+            for (idx, field) in export_record.iter().enumerate() {
+                if !args.flag_concat_drop || !self.concat_indices.iter().flatten().contains(&idx) {
+                    self.concat_record.push_field(field);
+                    if let Some(kind) = kinds.get(idx) {
+                        self.concat_kinds.push(*kind);
+                    }
+                }
+            }
+            for (spec, indices) in self.concat_specs.iter().zip(&self.concat_indices) {
+                if is_header {
+                    self.concat_record.push_field(&spec.name);
+                } else {
+                    let concatenated = indices
+                        .iter()
+                        .filter_map(|idx| export_record.get(*idx))
+                        .filter(|field| !field.is_empty())
+                        .join(self.concat_sep);
+                    self.concat_kinds.push(if concatenated.is_empty() {
+                        JsonKind::Null
+                    } else {
+                        JsonKind::String
+                    });
+                    self.concat_record.push_field(&concatenated);
+                }
+            }
+            (&self.concat_record, self.concat_kinds.as_slice())
+        };
+        let (export_record, export_kinds) =
+            if let Some(ref comments_column) = args.flag_comments_column {
+                let row_comments = if is_header {
+                    comments_column.clone()
+                } else {
+                    self.comments
+                        .get(&row)
+                        .map(|row_comments| row_comments.join("; "))
+                        .unwrap_or_default()
+                };
+                self.commented_record.clone_from(export_record);
+                self.commented_kinds.clear();
+                self.commented_kinds.extend_from_slice(export_kinds);
+                self.commented_kinds.push(if row_comments.is_empty() {
+                    JsonKind::Null
+                } else {
+                    JsonKind::String
+                });
+                self.commented_record.push_field(&row_comments);
+                (&self.commented_record, self.commented_kinds.as_slice())
+            } else {
+                (export_record, export_kinds)
+            };
+        let (export_record, export_kinds) = if args.flag_with_filename || self.with_sheet_name {
+            self.sheet_name_record.clear();
+            self.sheet_name_kinds.clear();
+            if args.flag_with_filename {
+                self.sheet_name_record.push_field(if is_header {
+                    "source_file"
+                } else {
+                    self.input
+                });
+                self.sheet_name_kinds.push(JsonKind::String);
+            }
+            if self.with_sheet_name {
+                self.sheet_name_record.push_field(if is_header {
+                    "source_sheet"
+                } else {
+                    self.sheet
+                });
+                self.sheet_name_kinds.push(JsonKind::String);
+            }
+            self.sheet_name_record.extend(export_record);
+            self.sheet_name_kinds.extend_from_slice(export_kinds);
+            (&self.sheet_name_record, self.sheet_name_kinds.as_slice())
+        } else {
+            (export_record, export_kinds)
+        };
+        Ok(Some((export_record, export_kinds)))
+    }
+}
+
+// the files of the values of the --split-by column of a sheet, of which at most
+// --split-open-files are open at a time
+struct SplitFiles<'a> {
+    args:           &'a Args,
+    column:         &'a str,
+    dir:            String,
+    // the index of the split column, and the header row written to each file
+    idx:            usize,
+    headers:        csv::StringRecord,
+    // the file of each value
+    indices:        HashMap<String, usize>,
+    files:          Vec<SplitFile>,
+    open_files:     Vec<usize>,
+    used_filenames: Vec<String>,
+}
+
+impl<'a> SplitFiles<'a> {
+    fn new(args: &'a Args, column: &'a str, dir: String) -> Self {
+        Self {
+            args,
+            column,
+            dir,
+            idx: 0,
+            headers: csv::StringRecord::new(),
+            indices: HashMap::new(),
+            files: Vec::new(),
+            open_files: Vec::with_capacity(args.flag_split_open_files),
+            used_filenames: Vec::new(),
+        }
+    }
+
+    /// writes an exported record to the file of its split column value, opening the file
+    /// if needed. The header row locates the split column, and is only written to the files
+    /// when they are created.
+    fn write(
+        &mut self,
+        is_header: bool,
+        jsonl_keys: &[String],
+        record: &csv::StringRecord,
+        kinds: &[JsonKind],
+        row_count: usize,
+    ) -> CliResult<()> {
+        if is_header {
+            let Some(idx) = record.iter().position(|col_name| col_name == self.column) else {
+                return fail_clierror!(
+                    r#"--split-by column "{}" not found in the header row."#,
+                    self.column
+                );
+            };
+            self.idx = idx;
+            self.headers = record.clone();
+        } else {
+            let value = record.get(self.idx).unwrap_or_default();
+            let split_file = if let Some(split_file) = self.indices.get(value) {
+                *split_file
+            } else {
+                let filename = sanitize_sheet_filename(
+                    if value.is_empty() { "empty" } else { value },
+                    &mut self.used_filenames,
+                );
+                let split_path = Path::new(&self.dir)
+                    .join(format!(
+                        "{filename}.{}",
+                        if self.args.flag_jsonl { "jsonl" } else { "csv" }
+                    ))
+                    .to_string_lossy()
+                    .into_owned();
+                self.files.push(SplitFile {
+                    path:     split_path,
+                    wtr:      None,
+                    rows:     0,
+                    last_row: 0,
+                });
+                self.indices.insert(value.to_string(), self.files.len() - 1);
+                self.files.len() - 1
+            };
+            if self.files[split_file].wtr.is_none() {
+                // the least recently written file is closed to bound the open files
+                if self.open_files.len() >= self.args.flag_split_open_files {
+                    let lru_pos = self
+                        .open_files
+                        .iter()
+                        .position_min_by_key(|open_file| self.files[**open_file].last_row)
+                        .unwrap_or_default();
+                    let lru_file = self.open_files.swap_remove(lru_pos);
+                    if let Some(mut lru_wtr) = self.files[lru_file].wtr.take() {
+                        lru_wtr.flush()?;
+                    }
+                }
+                // the header row is only written when the file is created
+                let split = &mut self.files[split_file];
+                let mut split_wtr =
+                    ExportWriter::open(&Some(split.path.clone()), self.args, split.rows > 0)?;
+                if split.rows == 0 {
+                    split_wtr.write(true, jsonl_keys, &self.headers, &[])?;
+                }
+                split.wtr = Some(split_wtr);
+                self.open_files.push(split_file);
+            }
+            let split = &mut self.files[split_file];
+            if let Some(split_wtr) = split.wtr.as_mut() {
+                split_wtr.write(false, jsonl_keys, record, kinds)?;
+            }
+            split.rows += 1;
+            split.last_row = row_count;
+        }
+        Ok(())
+    }
+
+    /// flushes the open files, and reports the rows written to each file
+    fn finish(&mut self) -> CliResult<()> {
+        for split_wtr in self.files.iter_mut().filter_map(|split| split.wtr.as_mut()) {
+            split_wtr.flush()?;
+        }
+        if !self.args.flag_quiet {
+            winfo!(
+                "{} files written by --split-by:",
+                self.files.len().separate_with_commas()
+            );
+            for split in &self.files {
+                winfo!("{}: {} rows", split.path, split.rows.separate_with_commas());
+            }
+        }
+        Ok(())
+    }
+}
+
+// converts the data cells of a sheet to the fields they're exported as, counting the
+// conversions that are reported once the sheet is exported
+struct CellConverter<'a> {
+    args:                 &'a Args,
+    options:              &'a ExportOptions<'a>,
+    sheet:                &'a str,
+    zero_padded_cells:    HashMap<(u32, u32), usize>,
+    duration_cells:       HashSet<(u32, u32)>,
+    percent_cells:        HashMap<(u32, u32), usize>,
+    numeric_string_count: usize,
+    truncated_count:      usize,
+    imprecise_count:      usize,
+    error_cell_counts:    BTreeMap<String, usize>,
+}
+
+impl CellConverter<'_> {
+    /// pushes the fields of the data cell at position & their JSON kinds to record & kinds.
+    /// date_column is whether the cell is in a date column, which --keep-serial also exports
+    /// the serial of. Returns the index of the type of the cell in SCHEMA_TYPES, if any.
+    fn convert_cell(
+        &mut self,
+        cell: &DataType,
+        position: (u32, u32),
+        date_column: bool,
+        record: &mut csv::StringRecord,
+        kinds: &mut Vec<JsonKind>,
+    ) -> CliResult<Option<usize>> {
+        let args = self.args;
+        let options = self.options;
+        let sheet = self.sheet;
+        let mut cell_date_flag = false;
+        let mut float_val = 0_f64;
+        let mut float_flag = false;
+        match *cell {
+            DataType::Empty => {
+                record.push_field(options.empty_token);
+                kinds.push(JsonKind::Null);
+            }
+            DataType::String(ref s) => {
+                if args.flag_trim
+                    && args.flag_trim_columns.is_none()
+                    && args.flag_empty_token.is_some()
+                    && s.trim().is_empty()
+                {
+                    // the text cells that are empty once trimmed are empty cells too
+                    record.push_field(options.empty_token);
+                    kinds.push(JsonKind::Null);
+                } else if args.flag_coerce_numeric_strings && is_numeric_string(s) {
+                    self.numeric_string_count += 1;
+                    record.push_field(s.trim());
+                    kinds.push(JsonKind::Number);
+                } else if let Some(truncated) = args
+                    .flag_max_cell_length
+                    .and_then(|max_length| truncate_cell(s, max_length, options.ellipsis))
+                {
+                    self.truncated_count += 1;
+                    record.push_field(&truncated);
+                    kinds.push(JsonKind::String);
+                } else {
+                    record.push_field(s);
+                    kinds.push(JsonKind::String);
+                }
+            }
+            DataType::Int(ref i) => {
+                let mut buffer = itoa::Buffer::new();
+                record.push_field(buffer.format(*i));
+                kinds.push(JsonKind::Number);
+            }
+            DataType::DateTime(ref f) => {
+                float_val = *f;
+                float_flag = true;
+                cell_date_flag = true;
+            }
+            DataType::Float(ref f) => {
+                float_val = *f;
+                float_flag = true;
+                cell_date_flag = date_column;
+            }
+            DataType::Error(ref e) => {
+                if args.flag_fail_on_error {
+                    return fail_clierror!(
+                        "Error cell at {} of the \"{sheet}\" sheet: {}",
+                        a1_cell(position),
+                        excel_error_text(e)
+                    );
+                }
+                let error = format!("{e:?}");
+                match options.error_format.as_str() {
+                    "excel" => record.push_field(excel_error_text(e)),
+                    "none" => record.push_field(""),
+                    _ => record.push_field(&error),
+                }
+                kinds.push(if options.error_format == "none" {
+                    JsonKind::Null
+                } else {
+                    JsonKind::String
+                });
+                *self.error_cell_counts.entry(error).or_default() += 1;
+            }
+            DataType::Bool(ref b) => {
+                record.push_field(if *b {
+                    options.true_str
+                } else {
+                    options.false_str
+                });
+                kinds.push(JsonKind::Bool(*b));
+            }
+        };
+        // the index of the type of the cell in SCHEMA_TYPES
+        let type_idx = match *cell {
+            _ if float_flag && cell_date_flag => Some(3),
+            _ if float_flag && float_val.fract() == 0.0 => Some(0),
+            _ if float_flag => Some(1),
+            DataType::Int(_) => Some(0),
+            DataType::String(ref s) if !s.is_empty() => Some(2),
+            DataType::Bool(_) => Some(4),
+            _ => None,
+        };
+        // dates are stored as floats in Excel
+        // that's why we need the --dates-whitelist, so we can convert the float to a date.
+        // However, with the XLSX format, we can get a cell's format as an attribute. So we
+        // can automatically process a cell as a date, even if its column is
+        // NOT in the whitelist
+        // the zero-padded width of the number format of the cell, with --keep-text-format
+        let zero_padded_width = if float_flag && !cell_date_flag {
+            self.zero_padded_cells
+                .get(&position)
+                .copied()
+                .filter(|_| float_val >= 0.0 && float_val.fract() == 0.0)
+        } else {
+            None
+        };
+        // the decimals of the percentage number format of the cell, with --percent-format
+        let percent_decimals = if float_flag && !cell_date_flag {
+            self.percent_cells.get(&position).copied()
+        } else {
+            None
+        };
+        if float_flag
+            && !cell_date_flag
+            && float_val.fract() == 0.0
+            && float_val.abs() > MAX_EXACT_INTEGER
+        {
+            self.imprecise_count += 1;
+        }
+        if float_flag {
+            kinds.push(
+                if cell_date_flag
+                    || zero_padded_width.is_some()
+                    || (percent_decimals.is_some() && options.percent_format == "sign")
+                {
+                    JsonKind::String
+                } else {
+                    JsonKind::Number
+                },
+            );
+            if cell_date_flag
+                && args.flag_elapsed_time
+                && (self.duration_cells.contains(&position)
+                    || (options.date_format.is_none() && (0.0..1.0).contains(&float_val)))
+            {
+                // with --elapsed-time, durations (e.g. [h]:mm:ss) & times of day
+                // (serials below 1) are a number of days, not a date
+                record.push_field(&format_elapsed_time(float_val));
+            } else if cell_date_flag {
+                // serials within --datetime-threshold of a whole number are dates,
+                // rounded so that the likes of 40729.9999999999 are the next day
+                let date_only =
+                    (float_val - float_val.round()).abs() <= args.flag_datetime_threshold;
+                let serial = if date_only {
+                    float_val.round()
+                } else {
+                    float_val
+                };
+                // shift 1904 date system serials to the 1900 date system of calamine
+                let date_cell;
+                let cell = if options.date_1904 {
+                    date_cell = DataType::DateTime(serial + DATE_1904_OFFSET);
+                    &date_cell
+                } else if date_only {
+                    date_cell = DataType::DateTime(serial);
+                    &date_cell
+                } else {
+                    cell
+                };
+                let converted = if date_only {
+                    cell.as_date().map(|d| match options.date_format {
+                        // format dates as datetimes, so time specifiers work.
+                        // They're not converted to the --output-timezone
+                        Some(date_format) => format_datetime(
+                            d.and_hms_opt(0, 0, 0).unwrap_or_default(),
+                            Some(date_format),
+                            options
+                                .timezones
+                                .map(|(assumed_tz, _)| (assumed_tz, assumed_tz)),
+                        ),
+                        None => format!("{d}"),
+                    })
+                } else {
+                    cell.as_datetime()
+                        .map(|dt| format_datetime(dt, options.date_format, options.timezones))
+                };
+                let date_kind = if date_only { "date" } else { "datetime" };
+                match converted {
+                    Some(converted) => record.push_field(&converted),
+                    None => match options.on_date_error.as_str() {
+                        "passthrough" => {
+                            record.push_field(&float_val.to_string());
+                            if let Some(kind) = kinds.last_mut() {
+                                *kind = JsonKind::Number;
+                            }
+                        }
+                        "empty" => {
+                            record.push_field("");
+                            if let Some(kind) = kinds.last_mut() {
+                                *kind = JsonKind::Null;
+                            }
+                        }
+                        "fail" => {
+                            return fail_clierror!(
+                                "Cannot convert the {float_val} cell at {} of the \"{sheet}\" \
+                                 sheet to a {date_kind}.",
+                                a1_cell(position)
+                            );
+                        }
+                        _ => record.push_field(&format!(
+                            "ERROR: Cannot convert {float_val} to {date_kind}"
+                        )),
+                    },
+                }
+            } else if let Some(width) = zero_padded_width {
+                record.push_field(&format!("{:0width$}", float_val as u64));
+            } else if let Some(decimals) = percent_decimals {
+                // shift the decimal point of the shortest representation of the value,
+                // so 0.07 is 7 and not 7.000000000000001
+                let percent = format!("{float_val}e2")
+                    .parse::<f64>()
+                    .unwrap_or(float_val * 100.0);
+                if options.percent_format == "sign" {
+                    record.push_field(&format!("{percent:.decimals$}%"));
+                } else if let Some(precision) = args.flag_output_precision {
+                    record.push_field(&format_float(
+                        percent,
+                        precision,
+                        args.flag_trim_trailing_zeros,
+                    ));
+                } else {
+                    record.push_field(&percent.to_string());
+                }
+            } else if let Some(precision) = args.flag_output_precision {
+                record.push_field(&format_float(
+                    float_val,
+                    precision,
+                    args.flag_trim_trailing_zeros,
+                ));
+            } else {
+                record.push_field(&float_val.to_string());
+            }
+        }
+
+        if options.keep_serial && date_column {
+            // with the date layout, the serial goes before the converted date
+            let date_field = if options.date_suffix {
+                let date_field = record.get(record.len() - 1).unwrap_or_default().to_string();
+                record.truncate(record.len() - 1);
+                kinds.pop().map(|date_kind| (date_field, date_kind))
+            } else {
+                None
+            };
+            match *cell {
+                DataType::Float(serial) | DataType::DateTime(serial) => {
+                    record.push_field(&serial.to_string());
+                    kinds.push(JsonKind::Number);
+                }
+                DataType::Int(serial) => {
+                    let mut buffer = itoa::Buffer::new();
+                    record.push_field(buffer.format(serial));
+                    kinds.push(JsonKind::Number);
+                }
+                _ => {
+                    record.push_field("");
+                    kinds.push(JsonKind::Null);
+                }
+            }
+            if let Some((date_field, date_kind)) = date_field {
+                record.push_field(&date_field);
+                kinds.push(date_kind);
+            }
+        }
+        Ok(type_idx)
+    }
+}
+
+/// returns the number of int, float, string, date, bool, error & empty cells of each
+/// column of range, from its first_data_row, for --dump-types
+fn column_type_counts(range: &Range<DataType>, first_data_row: usize) -> Vec<[usize; 7]> {
+    let mut counts = vec![[0_usize; 7]; range.width()];
+    for row in range.rows().skip(first_data_row) {
+        for (col_counts, cell) in counts.iter_mut().zip(row) {
+            let type_idx = match cell {
+                DataType::Int(_) => 0,
+                DataType::Float(_) => 1,
+                DataType::String(_) => 2,
+                DataType::DateTime(_) => 3,
+                DataType::Bool(_) => 4,
+                DataType::Error(_) => 5,
+                DataType::Empty => 6,
+            };
+            col_counts[type_idx] += 1;
+        }
+    }
+    counts
+}
+
+/// writes an exported record with --jsonl, as a JSON object keyed by the header names.
+/// The fields without a header name (with --flexible) are keyed by their column number.
+fn write_jsonl_record(
+    wtr: &mut impl Write,
+    keys: &[String],
+    record: &csv::StringRecord,
+    kinds: &[JsonKind],
+) -> CliResult<()> {
+    let mut object = serde_json::Map::with_capacity(record.len());
+    for (idx, field) in record.iter().enumerate() {
+        let value = match kinds.get(idx).copied().unwrap_or(JsonKind::String) {
+            JsonKind::Null => Value::Null,
+            JsonKind::Bool(b) => Value::Bool(b),
+            JsonKind::Number => {
+                if let Ok(int_val) = field.parse::<i64>() {
+                    Value::from(int_val)
+                } else {
+                    field
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(serde_json::Number::from_f64)
+                        .map_or_else(|| Value::String(field.to_string()), Value::Number)
+                }
+            }
+            JsonKind::String => Value::String(field.to_string()),
+        };
+        let key = keys
+            .get(idx)
+            .cloned()
+            .unwrap_or_else(|| format!("column_{}", idx + 1));
+        object.insert(key, value);
+    }
+    serde_json::to_writer(&mut *wtr, &object)?;
+    wtr.write_all(b"\n")?;
+    Ok(())
+}
+
+/// returns the --schema type of a column from the type counts of its data cells: the type
+/// of all its non-empty cells, float for ints & floats, and string otherwise
+fn schema_type(type_counts: &[usize; SCHEMA_TYPES.len()]) -> &'static str {
+    let mut present_types = SCHEMA_TYPES
+        .iter()
+        .zip(type_counts)
+        .filter(|(_, count)| **count > 0)
+        .map(|(col_type, _)| *col_type);
+    match (
+        present_types.next(),
+        present_types.next(),
+        present_types.next(),
+    ) {
+        (Some(col_type), None, None) => col_type,
+        (Some("int"), Some("float"), None) => "float",
+        _ => "string",
+    }
+}
+
+/// parses a text cell as a number for --coerce, ignoring surrounding whitespace
+/// and thousands separators. Non-finite values (e.g. "NaN", "inf") are not numbers.
+fn parse_numeric(s: &str) -> Option<f64> {
+    let number = s.trim().replace(',', "").parse::<f64>().ok()?;
+    if number.is_finite() {
+        Some(number)
+    } else {
+        None
+    }
+}
+
+/// returns true if a text cell is a number once trimmed, for --coerce-numeric-strings.
+/// Non-finite values (e.g. "NaN", "inf") are not numbers.
+fn is_numeric_string(s: &str) -> bool {
+    s.trim().parse::<f64>().map_or(false, f64::is_finite)
+}
+
+/// returns the majority type of the non-empty cells of each column from first_data_row,
+/// for --coerce. Columns that only have empty cells have no majority type.
+fn majority_types(range: &Range<DataType>, first_data_row: usize) -> Vec<Option<CoerceType>> {
+    // the (numeric, text) cell counts of each column
+    let mut counts = vec![(0_usize, 0_usize); range.width()];
+    for row in range.rows().skip(first_data_row) {
+        for (col_idx, cell) in row.iter().enumerate() {
+            match cell {
+                DataType::Int(_) | DataType::Float(_) | DataType::DateTime(_) => {
+                    counts[col_idx].0 += 1;
+                }
+                DataType::String(s) if parse_numeric(s).is_some() => counts[col_idx].0 += 1,
+                DataType::String(s) if !s.trim().is_empty() => counts[col_idx].1 += 1,
+                _ => {}
+            }
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(numeric, text)| {
+            if numeric == 0 && text == 0 {
+                None
+            } else if numeric > text {
+                Some(CoerceType::Numeric)
+            } else {
+                Some(CoerceType::Text)
+            }
+        })
+        .collect()
+}
+
+/// returns the columns whose first `sample_size` non-empty cells from first_data_row are
+/// mostly plausible Excel date serial numbers (or date cells), for --infer-dates
+fn infer_date_columns(
+    range: &Range<DataType>,
+    first_data_row: usize,
+    sample_size: usize,
+) -> Vec<bool> {
+    // the (sampled, date-like) cell counts of each column
+    let mut counts = vec![(0_usize, 0_usize); range.width()];
+    for row in range.rows().skip(first_data_row) {
+        for (col_idx, cell) in row.iter().enumerate() {
+            if counts[col_idx].0 >= sample_size {
+                continue;
+            }
+            match cell {
+                DataType::Empty => continue,
+                DataType::String(s) if s.trim().is_empty() => continue,
+                DataType::Float(serial) if (7306.0..=60000.0).contains(serial) => {
+                    counts[col_idx].1 += 1;
+                }
+                DataType::DateTime(_) => counts[col_idx].1 += 1,
+                _ => {}
+            }
+            counts[col_idx].0 += 1;
+        }
+    }
+    counts
+        .into_iter()
+        // at least 90% of the sampled cells are date-like
+        .map(|(sampled, date_like)| sampled > 0 && date_like * 10 >= sampled * 9)
+        .collect()
+}
+
+/// returns the columns of the value range that have formula cells below the header row,
+/// for --formulas-with-values
+fn formula_columns(
+    range: &Range<DataType>,
+    formula_range: &Range<String>,
+    header_row: usize,
+) -> Vec<bool> {
+    let mut formula_columns = vec![false; range.width()];
+    let (Some((start_row, start_col)), Some((formula_row, formula_col))) =
+        (range.start(), formula_range.start())
+    else {
+        return formula_columns;
+    };
+    for (row, col, formula) in formula_range.used_cells() {
+        if formula.is_empty() {
+            continue;
+        }
+        // the formula range doesn't necessarily start at the same cell as the value range
+        let abs_row = formula_row + row as u32;
+        let abs_col = formula_col + col as u32;
+        if abs_row > start_row + header_row as u32 && abs_col >= start_col {
+            if let Some(is_formula) = formula_columns.get_mut((abs_col - start_col) as usize) {
+                *is_formula = true;
+            }
+        }
+    }
+    formula_columns
+}
+
+/// formats a non-date float to the given number of decimal places.
+/// Whole numbers are formatted without a decimal point.
+fn format_float(float_val: f64, precision: usize, trim_trailing_zeros: bool) -> String {
+    if float_val.fract() == 0.0 {
+        return float_val.to_string();
+    }
+    let formatted = format!("{float_val:.precision$}");
+    if trim_trailing_zeros && formatted.contains('.') {
+        formatted
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    } else {
+        formatted
+    }
+}
+
+/// makes a sheet name safe to use as a filename, replacing the characters that are not allowed
+/// in filenames (on Windows in particular) with underscores and removing trailing dots & spaces.
+/// Names reserved by Windows for devices (e.g. CON or NUL, even with an extension like
+/// NUL.csv) get an underscore after the device name. If the sanitized name is already in
+/// `used_names` (case-insensitively, as Windows & macOS filesystems are case-insensitive), a _2,
+/// _3, etc. suffix is added to it. The returned name is added to `used_names`.
+fn sanitize_sheet_filename(sheet_name: &str, used_names: &mut Vec<String>) -> String {
+    let mut sanitized: String = sheet_name
+        .chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    // filesystems commonly limit filenames to 255 bytes, which also leaves room for the
+    // dedup suffix & the extension
+    if sanitized.len() > MAX_FILENAME_STEM_LEN {
+        let mut stem_len = MAX_FILENAME_STEM_LEN;
+        while !sanitized.is_char_boundary(stem_len) {
+            stem_len -= 1;
+        }
+        sanitized.truncate(stem_len);
+    }
+    sanitized.truncate(sanitized.trim_end_matches(['.', ' ']).len());
+    if sanitized.is_empty() {
+        sanitized = "sheet".to_string();
+    }
+    // Windows also reserves the device names followed by an extension, so the
+    // underscore goes after the device name
+    let stem = sanitized.split('.').next().unwrap_or_default().trim_end();
+    if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+    {
+        let stem_len = stem.len();
+        sanitized.insert(stem_len, '_');
+    }
+
+    let mut unique_name = sanitized.clone();
+    let mut suffix = 1_usize;
+    while used_names
+        .iter()
+        .any(|used| used.eq_ignore_ascii_case(&unique_name))
+    {
+        suffix += 1;
+        unique_name = format!("{sanitized}_{suffix}");
+    }
+    used_names.push(unique_name.clone());
+    unique_name
+}
+
+/// returns the path of the file of a sheet in the --output directory of --all-sheets
+fn all_sheets_output(output_dir: &str, sheet_filename: &str, jsonl: bool) -> String {
+    Path::new(output_dir)
+        .join(format!(
+            "{sheet_filename}.{}",
+            if jsonl { "jsonl" } else { "csv" }
+        ))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// buffers the workbook piped to stdin to a file of dir, named after the workbook format
+/// detected from its content (e.g. stdin.xlsx), so it can be opened like the other workbooks
+fn stdin_workbook(dir: &Path) -> CliResult<String> {
+    let mut buffer = Vec::new();
+    io::stdin().read_to_end(&mut buffer)?;
+    if buffer.is_empty() {
+        return fail!("No workbook was read from stdin.");
+    }
+    let format = if buffer.starts_with(b"\xD0\xCF\x11\xE0") {
+        // the Compound File Binary format of xls files
+        "xls"
+    } else if buffer.starts_with(b"PK\x03\x04") {
+        match ZipArchive::new(io::Cursor::new(&buffer)) {
+            Ok(archive) if archive.file_names().any(|name| name == "xl/workbook.bin") => "xlsb",
+            Ok(archive) if archive.file_names().any(|name| name == "mimetype") => "ods",
+            Ok(_) => "xlsx",
+            Err(e) => return fail_clierror!("Cannot read the workbook from stdin: {e}."),
+        }
+    } else {
+        return fail!(
+            "The stdin input is not a workbook. The excel command only supports the following \
+             file formats - xls, xlsx, xlsm, xlsb and ods."
+        );
+    };
+    let stdin_path = dir.join(format!("stdin.{format}"));
+    fs::write(&stdin_path, buffer)?;
+    Ok(stdin_path.to_string_lossy().into_owned())
+}
+
+/// returns the header name of a header row cell. With --auto-widen, the trailing
+/// columns without a header cell are named after their column number (e.g. col_8)
+fn header_cell_name(cell: &DataType, col_idx: usize, widen_from: Option<usize>) -> Cow<str> {
+    match widen_from {
+        Some(widen_from) if col_idx >= widen_from => Cow::Owned(format!("col_{}", col_idx + 1)),
+        _ => Cow::Borrowed(cell.get_string().unwrap_or_default()),
+    }
+}
+
+/// appends _2, _3, etc. to the repeated names of a header row, for --dedup-headers
+fn dedup_header_names(headers: &csv::StringRecord) -> csv::StringRecord {
+    // the names already in use, and the next suffix of each repeated name
+    let mut used_names: HashSet<String> = headers.iter().map(str::to_string).collect();
+    let mut name_counts: HashMap<&str, u32> = HashMap::new();
+    let mut deduped_headers =
+        csv::StringRecord::with_capacity(headers.as_slice().len(), headers.len());
+    for name in headers {
+        let count = name_counts.entry(name).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            deduped_headers.push_field(name);
+            continue;
+        }
+        // skip the suffixed names that are already in use (e.g. Amount_2)
+        let mut new_name = format!("{name}_{count}");
+        while used_names.contains(&new_name) {
+            *count += 1;
+            new_name = format!("{name}_{count}");
+        }
+        info!("renamed duplicate header {name} to {new_name}");
+        deduped_headers.push_field(&new_name);
+        used_names.insert(new_name);
+    }
+    deduped_headers
+}
+
+/// converts a header name to the --header-case
+fn convert_header_case(name: &str, header_case: HeaderCase) -> String {
+    // split the name into words at non-alphanumeric characters & camelCase boundaries
+    let mut words: Vec<String> = Vec::new();
+    let mut prev_lowercase = false;
+    let mut word = String::new();
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+        } else {
+            if c.is_uppercase() && prev_lowercase && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.push(c);
+        }
+        prev_lowercase = c.is_lowercase() || c.is_ascii_digit();
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    match header_case {
+        HeaderCase::Lower => name.to_lowercase(),
+        HeaderCase::Upper => name.to_uppercase(),
+        HeaderCase::Snake => words.iter().map(|word| word.to_lowercase()).join("_"),
+        HeaderCase::Camel => words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                let word = word.to_lowercase();
+                if i == 0 {
+                    return word;
+                }
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            })
+            .collect(),
+    }
+}
+
+/// returns the zero-based index of the first row within the first
+/// DETECT_HEADER_ROW_SCAN_ROWS rows that looks like a header row - i.e. all its cells
+/// (ignoring trailing empty cells) are non-empty strings, and the row after it
+/// is a non-empty row that is not wider than it.
+fn detect_header_row(range: &Range<DataType>) -> Option<usize> {
+    // the number of cells in a row, ignoring trailing empty cells
+    let row_width = |row: &[DataType]| {
+        row.iter()
+            .rposition(|cell| !cell.is_empty())
+            .map_or(0, |last_idx| last_idx + 1)
+    };
+
+    let rows = range
+        .rows()
+        .take(DETECT_HEADER_ROW_SCAN_ROWS + 1)
+        .collect_vec();
+    for (row_idx, &row) in rows.iter().enumerate().take(DETECT_HEADER_ROW_SCAN_ROWS) {
+        let width = row_width(row);
+        if width == 0 {
+            continue;
+        }
+        let all_strings = row[..width]
+            .iter()
+            .all(|cell| matches!(cell, DataType::String(s) if !s.trim().is_empty()));
+        if !all_strings {
+            continue;
+        }
+        if let Some(&next_row) = rows.get(row_idx + 1) {
+            let next_width = row_width(next_row);
+            if next_width > 0 && next_width <= width {
+                return Some(row_idx);
+            }
+        }
+    }
+    None
+}
+
+/// returns the sheet names shared by more than one sheet (ignoring case), with the
+/// indices of their sheets
+fn duplicate_sheet_names(sheet_names: &[String]) -> Vec<(String, Vec<usize>)> {
+    let mut indices_by_name: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (idx, sheet_name) in sheet_names.iter().enumerate() {
+        indices_by_name
+            .entry(sheet_name.to_lowercase())
+            .or_default()
+            .push(idx);
+    }
+    indices_by_name
+        .into_values()
+        .filter(|indices| indices.len() > 1)
+        .map(|indices| (sheet_names[indices[0]].clone(), indices))
+        .collect()
+}
+
+// a streaming reader of an XML part of the zip archive of an xlsx/xlsm workbook
+type XlsxPartReader<'a> = quick_xml::Reader<BufReader<ZipFile<'a>>>;
+
+/// returns a streaming reader of an XML part (e.g. "xl/workbook.xml") of the zip archive of
+/// an xlsx/xlsm workbook. Returns None if the workbook doesn't have the part.
+fn xlsx_part_reader<'a>(
+    archive: &'a mut ZipArchive<File>,
+    part_name: &str,
+) -> io::Result<Option<XlsxPartReader<'a>>> {
+    match archive.by_name(part_name) {
+        Ok(part) => Ok(Some(quick_xml::Reader::from_reader(BufReader::new(part)))),
+        Err(ZipError::FileNotFound) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// reads the next event of an XML part into buf, which is cleared first
+fn next_xml_event<'b>(
+    reader: &mut quick_xml::Reader<impl BufRead>,
+    buf: &'b mut Vec<u8>,
+) -> io::Result<Event<'b>> {
+    buf.clear();
+    reader
+        .read_event_into(buf)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+/// returns the unescaped value of an attribute of an XML element, ignoring its namespace
+fn xml_attr(element: &BytesStart, attr_name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == attr_name)
+        .and_then(|attr| attr.unescape_value().ok().map(Cow::into_owned))
+}
+
+/// returns true if a boolean attribute of an XML element is set (1 or true)
+fn xml_flag(element: &BytesStart, attr_name: &[u8]) -> bool {
+    xml_attr(element, attr_name).map_or(false, |flag| flag == "1" || flag == "true")
+}
+
+// a relationship of a part of an xlsx/xlsm workbook (e.g. of a sheet to its comments)
+struct XlsxRelationship {
+    id:       String,
+    rel_type: String,
+    target:   String,
+}
+
+/// returns the relationships of a part of an xlsx/xlsm workbook, from its relationships
+/// part (e.g. xl/worksheets/_rels/sheet1.xml.rels for xl/worksheets/sheet1.xml).
+/// Returns no relationships if the part doesn't have any.
+fn xlsx_relationships(
+    archive: &mut ZipArchive<File>,
+    part_name: &str,
+) -> io::Result<Vec<XlsxRelationship>> {
+    let rels_part = match part_name.rsplit_once('/') {
+        Some((folder, file)) => format!("{folder}/_rels/{file}.rels"),
+        None => format!("_rels/{part_name}.rels"),
+    };
+    let mut relationships = Vec::new();
+    let Some(mut reader) = xlsx_part_reader(archive, &rels_part)? else {
+        return Ok(relationships);
+    };
+    let mut buf = Vec::new();
+    loop {
+        match next_xml_event(&mut reader, &mut buf)? {
+            Event::Start(ref e) | Event::Empty(ref e)
+                if e.local_name().as_ref() == b"Relationship" =>
+            {
+                if let (Some(id), Some(target)) = (xml_attr(e, b"Id"), xml_attr(e, b"Target")) {
+                    relationships.push(XlsxRelationship {
+                        id,
+                        rel_type: xml_attr(e, b"Type").unwrap_or_default(),
+                        target,
+                    });
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(relationships)
+}
+
+/// returns the zip part of a relationship target, which is relative to the folder of the
+/// part with the relationship (e.g. ../comments1.xml for xl/worksheets/sheet1.xml),
+/// unless it's absolute
+fn xlsx_target_part(folder: &str, target: &str) -> String {
+    if let Some(absolute_target) = target.strip_prefix('/') {
+        return absolute_target.to_string();
+    }
+    let mut folders: Vec<&str> = folder.split('/').filter(|f| !f.is_empty()).collect();
+    for segment in target.split('/') {
+        match segment {
+            ".." => {
+                folders.pop();
+            }
+            "." => {}
+            _ => folders.push(segment),
+        }
+    }
+    folders.join("/")
+}
+
+// the details of a sheet of an xlsx/xlsm workbook that calamine doesn't expose
+struct XlsxSheet {
+    hidden: bool,
+    // the zip part of the sheet (e.g. xl/worksheets/sheet1.xml)
+    part:   Option<String>,
+}
+
+// the sheet details of an xlsx/xlsm workbook to collect, for the options that use them
+struct XlsxSheetOptions {
+    dimension:    bool,
+    hidden_rows:  bool,
+    hidden_cols:  bool,
+    hyperlinks:   bool,
+    merged_cells: bool,
+    autofilter:   bool,
+    comments:     bool,
+    zero_padded:  bool,
+    duration:     bool,
+    percent:      bool,
+}
+
+impl XlsxSheetOptions {
+    /// returns the options that need sheet details, for the warnings
+    fn flags(&self) -> Vec<&'static str> {
+        [
+            (self.dimension, "--dimension full"),
+            (self.hidden_rows, "--skip-hidden-rows"),
+            (self.hidden_cols, "--skip-hidden-cols"),
+            (self.hyperlinks, "--hyperlinks"),
+            (self.merged_cells, "--merge-fill"),
+            (self.autofilter, "--apply-autofilter"),
+            (self.comments, "--comments-column"),
+            (self.zero_padded, "--keep-text-format"),
+            (self.duration, "--elapsed-time"),
+            (self.percent, "--percent-format"),
+        ]
+        .into_iter()
+        .filter_map(|(requested, flag)| requested.then_some(flag))
+        .collect()
+    }
+}
+
+/// the details of a sheet of an xlsx/xlsm workbook that calamine doesn't expose, collected
+/// in a single pass over the sheet XML. The positions are zero-based (row, column) positions,
+/// and only the details of the requested XlsxSheetOptions are collected.
+#[derive(Default)]
+struct XlsxSheetDetails {
+    // the declared dimension (e.g. <dimension ref="A1:D10"/>)
+    dimension:         Option<((u32, u32), (u32, u32))>,
+    // the rows (<row hidden="1">) & columns (<col min max hidden="1">) that are hidden
+    hidden_rows:       HashSet<u32>,
+    hidden_cols:       HashSet<u32>,
+    // the hyperlink target of each linked cell. The target of an external link is its URL,
+    // and the target of a link within the workbook its location (e.g. Sheet2!A1)
+    hyperlinks:        HashMap<(u32, u32), String>,
+    // the merged cell regions (<mergeCell ref="A1:A3"/>), as (start, end) positions
+    merged_cells:      Vec<((u32, u32), (u32, u32))>,
+    autofilter:        Option<AutoFilter>,
+    // the comments (notes) of each row, prefixed by their cell reference (e.g. B5: looks
+    // wrong), in column order
+    comments:          HashMap<u32, Vec<String>>,
+    // the width of the numeric cells whose number format only has zeros (e.g. 00000)
+    zero_padded_cells: HashMap<(u32, u32), usize>,
+    // the numeric cells with an elapsed time number format (e.g. [h]:mm:ss)
+    duration_cells:    HashSet<(u32, u32)>,
+    // the decimals of the numeric cells with a percentage number format (e.g. 2 for 0.00%)
+    percent_cells:     HashMap<(u32, u32), usize>,
+}
+
+// the number format details of each cell style of an xlsx/xlsm workbook, by style index
+#[derive(Default)]
+struct XlsxCellStyles {
+    zero_padded: Vec<Option<usize>>,
+    duration:    Vec<bool>,
+    percent:     Vec<Option<usize>>,
+}
+
+/// the zip archive of an xlsx/xlsm workbook, to read the workbook details that calamine
+/// doesn't expose. It is opened once per workbook, and the zip part of each sheet is
+/// resolved when it's opened.
+struct XlsxArchive {
+    archive:  ZipArchive<File>,
+    // the sheets in workbook order
+    sheets:   Vec<XlsxSheet>,
+    // the workbook uses the 1904 date system (the date1904 attribute of its workbookPr)
+    date1904: bool,
+}
+
+impl XlsxArchive {
+    fn open(path: &str) -> io::Result<Self> {
+        let mut archive = ZipArchive::new(File::open(path)?)?;
+        // the hidden state (also "very hidden") & relationship id of each sheet
+        let mut sheet_ids: Vec<(bool, Option<String>)> = Vec::new();
+        let mut date1904 = false;
+        if let Some(mut reader) = xlsx_part_reader(&mut archive, "xl/workbook.xml")? {
+            let mut buf = Vec::new();
+            loop {
+                match next_xml_event(&mut reader, &mut buf)? {
+                    Event::Start(ref e) | Event::Empty(ref e) => match e.local_name().as_ref() {
+                        b"workbookPr" => date1904 = xml_flag(e, b"date1904"),
+                        b"sheet" => sheet_ids.push((
+                            xml_attr(e, b"state").map_or(false, |state| state != "visible"),
+                            xml_attr(e, b"id"),
+                        )),
+                        _ => {}
+                    },
+                    Event::Eof => break,
+                    _ => {}
+                }
+            }
+        }
+        let relationships = xlsx_relationships(&mut archive, "xl/workbook.xml")?;
+        let sheets = sheet_ids
+            .into_iter()
+            .map(|(hidden, rel_id)| XlsxSheet {
+                hidden,
+                part: relationships
+                    .iter()
+                    .find(|rel| rel_id.as_deref() == Some(rel.id.as_str()))
+                    .map(|rel| xlsx_target_part("xl", &rel.target)),
+            })
+            .collect();
+        Ok(Self {
+            archive,
+            sheets,
+            date1904,
+        })
+    }
+
+    /// returns true if the sheet at sheet_index is hidden (or "very hidden")
+    fn sheet_hidden(&self, sheet_index: usize) -> bool {
+        self.sheets
+            .get(sheet_index)
+            .map_or(false, |sheet| sheet.hidden)
+    }
+
+    /// returns the document properties of the workbook, as (name, value) pairs: the core
+    /// properties (docProps/core.xml) that are set, in document order, followed by the main
+    /// extended properties (docProps/app.xml)
+    fn properties(&mut self) -> io::Result<Vec<(String, String)>> {
+        // the extended properties also have lists (e.g. TitlesOfParts), which are skipped
+        const APP_PROPERTIES: [&str; 5] = [
+            "Application",
+            "AppVersion",
+            "Company",
+            "Manager",
+            "Template",
+        ];
+        let mut properties = Vec::new();
+        let mut buf = Vec::new();
+        for (part, names) in [
+            ("docProps/core.xml", None),
+            ("docProps/app.xml", Some(&APP_PROPERTIES)),
+        ] {
+            let Some(mut reader) = xlsx_part_reader(&mut self.archive, part)? else {
+                continue;
+            };
+            // the name & text of the current property element
+            let mut property: Option<(String, String)> = None;
+            loop {
+                match next_xml_event(&mut reader, &mut buf)? {
+                    Event::Start(ref e) => {
+                        let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                        property = names
+                            .map_or(true, |names| names.contains(&name.as_str()))
+                            .then_some((name, String::new()));
+                    }
+                    Event::Text(ref e) => {
+                        if let (Some((_, value)), Ok(text)) = (property.as_mut(), e.unescape()) {
+                            value.push_str(&text);
+                        }
+                    }
+                    Event::End(_) => {
+                        if let Some((name, value)) = property.take() {
+                            let value = value.trim();
+                            if !value.is_empty() {
+                                properties.push((name, value.to_string()));
+                            }
+                        }
+                    }
+                    Event::Eof => break,
+                    _ => {}
+                }
+            }
+        }
+        Ok(properties)
+    }
+
+    /// returns the details of the sheet at sheet_index requested by options, reading the
+    /// sheet XML once. The comments & the number formats are read from their own parts.
+    fn sheet_details(
+        &mut self,
+        sheet_index: usize,
+        options: &XlsxSheetOptions,
+    ) -> io::Result<XlsxSheetDetails> {
+        let mut details = XlsxSheetDetails::default();
+        let Some(sheet_part) = self
+            .sheets
+            .get(sheet_index)
+            .and_then(|sheet| sheet.part.clone())
+        else {
+            return Ok(details);
+        };
+        let relationships = xlsx_relationships(&mut self.archive, &sheet_part)?;
+        if options.comments {
+            let sheet_folder = sheet_part.rsplit_once('/').map_or("", |(folder, _)| folder);
+            if let Some(comments_rel) = relationships
+                .iter()
+                .find(|rel| rel.rel_type.ends_with("/comments"))
+            {
+                details.comments =
+                    self.comments(&xlsx_target_part(sheet_folder, &comments_rel.target))?;
+            }
+        }
+        let styles = self.cell_styles(options)?;
+        let styled = styles.zero_padded.iter().any(Option::is_some)
+            || styles.duration.contains(&true)
+            || styles.percent.iter().any(Option::is_some);
+        if !(options.dimension
+            || options.hidden_rows
+            || options.hidden_cols
+            || options.hyperlinks
+            || options.merged_cells
+            || options.autofilter
+            || styled)
+        {
+            return Ok(details);
+        }
+
+        let Some(mut reader) = xlsx_part_reader(&mut self.archive, &sheet_part)? else {
+            return Ok(details);
+        };
+        let mut buf = Vec::new();
+        // rows without an r attribute follow the previous row
+        let mut row = 0_u32;
+        let mut in_autofilter = false;
+        // the sheet column & criteria of the current <filterColumn> of the AutoFilter
+        let mut filter_column: Option<(u32, Option<FilterCriteria>)> = None;
+        loop {
+            let (e, has_children) = match next_xml_event(&mut reader, &mut buf)? {
+                Event::Start(e) => (e, true),
+                Event::Empty(e) => (e, false),
+                Event::End(ref e) => {
+                    match e.local_name().as_ref() {
+                        b"filterColumn" => {
+                            if let (Some(autofilter), Some((col, Some(criteria)))) =
+                                (details.autofilter.as_mut(), filter_column.take())
+                            {
+                                autofilter.columns.push((col, criteria));
+                            }
+                        }
+                        b"autoFilter" => in_autofilter = false,
+                        _ => {}
+                    }
+                    continue;
+                }
+                Event::Eof => break,
+                _ => continue,
+            };
+            match e.local_name().as_ref() {
+                b"dimension" if options.dimension => {
+                    details.dimension = xml_attr(&e, b"ref").and_then(|r| parse_a1_range(&r));
+                }
+                b"col" if options.hidden_cols && xml_flag(&e, b"hidden") => {
+                    let min = xml_attr(&e, b"min").and_then(|min| min.parse::<u32>().ok());
+                    let max = xml_attr(&e, b"max").and_then(|max| max.parse::<u32>().ok());
+                    if let (Some(min), Some(max)) = (min, max) {
+                        details.hidden_cols.extend(min.saturating_sub(1)..max);
+                    }
+                }
+                b"row" => {
+                    row = xml_attr(&e, b"r")
+                        .and_then(|r| r.parse::<u32>().ok())
+                        .unwrap_or(row + 1);
+                    if options.hidden_rows && xml_flag(&e, b"hidden") {
+                        details.hidden_rows.insert(row.saturating_sub(1));
+                    }
+                }
+                // only the numeric cells (without a t attribute, or t="n") have a number format
+                b"c" if styled && xml_attr(&e, b"t").map_or(true, |cell_type| cell_type == "n") => {
+                    let style = xml_attr(&e, b"s").and_then(|style| style.parse::<usize>().ok());
+                    let position = xml_attr(&e, b"r").and_then(|r| parse_a1_cell(&r));
+                    let (Some(style), Some(position)) = (style, position) else {
+                        continue;
+                    };
+                    if let Some(&Some(width)) = styles.zero_padded.get(style) {
+                        details.zero_padded_cells.insert(position, width);
+                    }
+                    if styles.duration.get(style).copied().unwrap_or_default() {
+                        details.duration_cells.insert(position);
+                    }
+                    if let Some(&Some(decimals)) = styles.percent.get(style) {
+                        details.percent_cells.insert(position, decimals);
+                    }
+                }
+                b"hyperlink" if options.hyperlinks => {
+                    let url = xml_attr(&e, b"id").and_then(|id| {
+                        relationships
+                            .iter()
+                            .find(|rel| rel.id == id)
+                            .map(|rel| rel.target.clone())
+                    });
+                    let target = match (url, xml_attr(&e, b"location")) {
+                        (Some(url), Some(location)) => format!("{url}#{location}"),
+                        (Some(target), None) | (None, Some(target)) => target,
+                        (None, None) => continue,
+                    };
+                    // a hyperlink can span a range of cells (e.g. A2:C2)
+                    let Some((start, end)) = xml_attr(&e, b"ref").and_then(|r| parse_a1_range(&r))
+                    else {
+                        continue;
+                    };
+                    for link_row in start.0..=end.0 {
+                        for link_col in start.1..=end.1 {
+                            details
+                                .hyperlinks
+                                .insert((link_row, link_col), target.clone());
+                        }
+                    }
+                }
+                b"mergeCell" if options.merged_cells => {
+                    if let Some(region) = xml_attr(&e, b"ref").and_then(|r| parse_a1_range(&r)) {
+                        details.merged_cells.push(region);
+                    }
+                }
+                // only the first AutoFilter is the sheet's, the others are of custom views
+                b"autoFilter" if options.autofilter && details.autofilter.is_none() => {
+                    details.autofilter =
+                        xml_attr(&e, b"ref")
+                            .and_then(|r| parse_a1_range(&r))
+                            .map(|range| AutoFilter {
+                                range,
+                                columns: Vec::new(),
+                            });
+                    in_autofilter = has_children && details.autofilter.is_some();
+                }
+                b"filterColumn" if in_autofilter => {
+                    let col_id = xml_attr(&e, b"colId").and_then(|id| id.parse::<u32>().ok());
+                    let start_col = details
+                        .autofilter
+                        .as_ref()
+                        .map(|autofilter| autofilter.range.0 .1);
+                    if let (Some(col_id), Some(start_col)) = (col_id, start_col) {
+                        filter_column = Some((start_col + col_id, None));
+                    } else {
+                        details.autofilter = None;
+                        in_autofilter = false;
+                    }
+                }
+                name if in_autofilter => {
+                    if let Some((_, ref mut criteria)) = filter_column {
+                        parse_filter_element(name, &e, criteria);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(details)
+    }
+
+    /// returns the comments (notes) of the comments part of a sheet, keyed by their
+    /// zero-based row, with each comment prefixed by its cell reference, in column order
+    fn comments(&mut self, comments_part: &str) -> io::Result<HashMap<u32, Vec<String>>> {
+        let mut comments: HashMap<u32, Vec<(u32, String)>> = HashMap::new();
+        let Some(mut reader) = xlsx_part_reader(&mut self.archive, comments_part)? else {
+            return Ok(HashMap::new());
+        };
+        let mut buf = Vec::new();
+        // the reference, position & text of the current <comment>
+        let mut comment: Option<(String, (u32, u32), String)> = None;
+        let mut in_text = false;
+        loop {
+            match next_xml_event(&mut reader, &mut buf)? {
+                Event::Start(ref e) => match e.local_name().as_ref() {
+                    b"comment" => {
+                        comment = xml_attr(e, b"ref").and_then(|cell_ref| {
+                            parse_a1_cell(&cell_ref)
+                                .map(|position| (cell_ref, position, String::new()))
+                        });
+                    }
+                    b"t" => in_text = true,
+                    _ => {}
+                },
+                Event::Text(ref e) if in_text => {
+                    if let (Some((_, _, text)), Ok(t)) = (comment.as_mut(), e.unescape()) {
+                        text.push_str(&t);
+                    }
+                }
+                Event::End(ref e) => match e.local_name().as_ref() {
+                    b"t" => in_text = false,
+                    b"comment" => {
+                        if let Some((cell_ref, (row, col), text)) = comment.take() {
+                            let text = text.trim();
+                            if !text.is_empty() {
+                                comments
+                                    .entry(row)
+                                    .or_default()
+                                    .push((col, format!("{cell_ref}: {text}")));
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(comments
+            .into_iter()
+            .map(|(row, mut row_comments)| {
+                row_comments.sort_by_key(|(col, _)| *col);
+                (
+                    row,
+                    row_comments
+                        .into_iter()
+                        .map(|(_, comment)| comment)
+                        .collect(),
+                )
+            })
+            .collect())
+    }
+
+    /// returns the number format details of each cell style (xl/styles.xml) requested by
+    /// options, or no styles if none are requested
+    fn cell_styles(&mut self, options: &XlsxSheetOptions) -> io::Result<XlsxCellStyles> {
+        let mut styles = XlsxCellStyles::default();
+        if !(options.zero_padded || options.duration || options.percent) {
+            return Ok(styles);
+        }
+        let Some(mut reader) = xlsx_part_reader(&mut self.archive, "xl/styles.xml")? else {
+            return Ok(styles);
+        };
+        let mut buf = Vec::new();
+        // the custom number formats, by numFmtId
+        let mut num_fmts: HashMap<String, String> = HashMap::new();
+        let mut in_cell_xfs = false;
+        loop {
+            match next_xml_event(&mut reader, &mut buf)? {
+                Event::Start(ref e) | Event::Empty(ref e) => match e.local_name().as_ref() {
+                    b"numFmt" => {
+                        if let (Some(id), Some(code)) =
+                            (xml_attr(e, b"numFmtId"), xml_attr(e, b"formatCode"))
+                        {
+                            num_fmts.insert(id, code);
+                        }
+                    }
+                    b"cellXfs" => in_cell_xfs = true,
+                    b"xf" if in_cell_xfs => {
+                        // the format code is None for the built-in number formats
+                        let num_fmt_id = xml_attr(e, b"numFmtId").unwrap_or_default();
+                        let code = num_fmts.get(&num_fmt_id).map(String::as_str);
+                        if options.zero_padded {
+                            styles.zero_padded.push(zero_padded_width(code));
+                        }
+                        if options.duration {
+                            styles.duration.push(is_elapsed_time_format(code));
+                        }
+                        if options.percent {
+                            styles.percent.push(percent_decimals(&num_fmt_id, code));
+                        }
+                    }
+                    _ => {}
+                },
+                Event::End(ref e) if e.local_name().as_ref() == b"cellXfs" => break,
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(styles)
+    }
+}
+
+/// applies an element of a <filterColumn> of an AutoFilter to its criteria
+fn parse_filter_element(name: &[u8], e: &BytesStart, criteria: &mut Option<FilterCriteria>) {
+    match name {
+        b"filters" => {
+            *criteria = Some(FilterCriteria::Values {
+                values: Vec::new(),
+                blank:  xml_flag(e, b"blank"),
+            });
+        }
+        b"filter" => {
+            if let (Some(FilterCriteria::Values { values, .. }), Some(value)) =
+                (criteria.as_mut(), xml_attr(e, b"val"))
+            {
+                values.push(value);
+            }
+        }
+        b"customFilters" => {
+            *criteria = Some(FilterCriteria::Custom {
+                and:         xml_flag(e, b"and"),
+                comparisons: Vec::new(),
+            });
+        }
+        b"customFilter" => {
+            let op = match xml_attr(e, b"operator").as_deref() {
+                None | Some("equal") => FilterOperator::Equal,
+                Some("notEqual") => FilterOperator::NotEqual,
+                Some("lessThan") => FilterOperator::LessThan,
+                Some("lessThanOrEqual") => FilterOperator::LessThanOrEqual,
+                Some("greaterThan") => FilterOperator::GreaterThan,
+                Some("greaterThanOrEqual") => FilterOperator::GreaterThanOrEqual,
+                Some(_) => {
+                    *criteria = Some(FilterCriteria::Unsupported("custom"));
+                    return;
+                }
+            };
+            if let Some(FilterCriteria::Custom { comparisons, .. }) = criteria.as_mut() {
+                comparisons.push((op, xml_attr(e, b"val").unwrap_or_default()));
+            }
+        }
+        b"dateGroupItem" => *criteria = Some(FilterCriteria::Unsupported("date group")),
+        b"top10" => *criteria = Some(FilterCriteria::Unsupported("top 10")),
+        b"dynamicFilter" => *criteria = Some(FilterCriteria::Unsupported("dynamic")),
+        b"colorFilter" => *criteria = Some(FilterCriteria::Unsupported("color")),
+        b"iconFilter" => *criteria = Some(FilterCriteria::Unsupported("icon")),
+        _ => {}
+    }
+}
+
+/// returns the zero-padded width of a custom number format that only has zeros
+/// (e.g. 5 for 00000), for --keep-text-format
+fn zero_padded_width(code: Option<&str>) -> Option<usize> {
+    // only the format of the positive numbers matters
+    let positive_code = code?.split(';').next().unwrap_or_default();
+    (!positive_code.is_empty() && positive_code.bytes().all(|b| b == b'0'))
+        .then_some(positive_code.len())
+}
+
+/// returns true if a custom number format is an elapsed time format (e.g. [h]:mm:ss),
+/// for --elapsed-time
+fn is_elapsed_time_format(code: Option<&str>) -> bool {
+    // the elapsed hours, minutes or seconds are in brackets, like the colors & locales
+    code.map_or(false, |code| {
+        let positive_code = code.split(';').next().unwrap_or_default().to_lowercase();
+        ["[h", "[m", "[s"]
+            .iter()
+            .any(|elapsed| positive_code.contains(elapsed))
+    })
+}
+
+/// returns the decimals of a percentage number format (e.g. 2 for 0.00%), given its
+/// numFmtId and its format code (None for the built-in formats), for --percent-format
+fn percent_decimals(num_fmt_id: &str, code: Option<&str>) -> Option<usize> {
+    match (num_fmt_id, code) {
+        // the built-in 0% & 0.00% formats
+        ("9", None) => Some(0),
+        ("10", None) => Some(2),
+        (_, Some(code)) => {
+            // only the format of the positive numbers matters, without its quoted text
+            let positive_code = code.split(';').next().unwrap_or_default();
+            let unquoted: String = positive_code.split('"').step_by(2).collect();
+            if !unquoted.contains('%') {
+                return None;
+            }
+            let decimals = unquoted.split_once('.').map_or(0, |(_, fraction)| {
+                fraction
+                    .chars()
+                    .take_while(|c| matches!(c, '0' | '#' | '?'))
+                    .count()
+            });
+            Some(decimals)
+        }
+        _ => None,
+    }
+}
+
+/// returns range with the cells that have a hyperlink replaced by its target, for
+/// --hyperlinks url, or by their text followed by the target in parentheses with both.
+/// The hyperlinks outside of the range are ignored.
+fn link_cells(
+    mut range: Range<DataType>,
+    hyperlinks: &HashMap<(u32, u32), String>,
+    both: bool,
+) -> Range<DataType> {
+    let (Some(range_start), Some(range_end)) = (range.start(), range.end()) else {
+        return range;
+    };
+    for (&(row, col), target) in hyperlinks {
+        if row < range_start.0 || row > range_end.0 || col < range_start.1 || col > range_end.1 {
+            continue;
+        }
+        let label = range
+            .get_value((row, col))
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        let linked = if both && !label.is_empty() {
+            format!("{label} ({target})")
+        } else {
+            target.clone()
+        };
+        range.set_value((row, col), DataType::String(linked));
+    }
+    range
+}
+
+/// returns range with the value of the top-left cell of each merged cell region copied
+/// to the other cells of the region, for --merge-fill.
+/// The regions are clipped to the range, so it keeps its size.
+fn fill_merged_cells(
+    mut range: Range<DataType>,
+    merged_cells: &[((u32, u32), (u32, u32))],
+) -> Range<DataType> {
+    let (Some(range_start), Some(range_end)) = (range.start(), range.end()) else {
+        return range;
+    };
+    for &(start, end) in merged_cells {
+        let Some(anchor) = range
+            .get_value(start)
+            .filter(|cell| !cell.is_empty())
+            .cloned()
+        else {
+            continue;
+        };
+        for row in start.0.max(range_start.0)..=end.0.min(range_end.0) {
+            for col in start.1.max(range_start.1)..=end.1.min(range_end.1) {
+                if (row, col) != start {
+                    range.set_value((row, col), anchor.clone());
+                }
+            }
+        }
+    }
+    range
+}
+
+/// parses the reference of a defined name (e.g. 'Sales Data'!$A$1:$D$10) into its
+/// sheet name, its range without the $ signs (e.g. A1:D10) and its zero-based
+/// (start, end) positions. Returns None if it is not a reference to a single range.
+fn parse_defined_name(reference: &str) -> Option<(String, String, ((u32, u32), (u32, u32)))> {
+    let reference = reference.trim().trim_start_matches('=');
+    if reference.contains(',') {
+        return None;
+    }
+    let (sheet, range_ref) = reference.rsplit_once('!')?;
+    let sheet = match sheet.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+        Some(quoted_sheet) => quoted_sheet.replace("''", "'"),
+        None => sheet.to_string(),
+    };
+    let positions = parse_a1_range(range_ref)?;
+    Some((sheet, range_ref.replace('$', ""), positions))
+}
+
+/// parses an A1-style cell reference (e.g. "C5" or "$C$5") into its zero-based (row, column)
+fn parse_a1_cell(cell_ref: &str) -> Option<(u32, u32)> {
+    let cell_ref = cell_ref.replace('$', "");
+    let digits_idx = cell_ref.find(|c: char| c.is_ascii_digit())?;
+    let (col_letters, row_digits) = cell_ref.split_at(digits_idx);
+    if col_letters.is_empty() || !col_letters.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let mut col = 0_u32;
+    for c in col_letters.chars() {
+        col = col
+            .checked_mul(26)?
+            .checked_add(u32::from(c.to_ascii_uppercase()) - u32::from('A') + 1)?;
+    }
+    let row = row_digits.parse::<u32>().ok()?;
+    if row == 0 {
+        return None;
+    }
+    Some((row - 1, col - 1))
+}
+
+/// formats a zero-based (row, col) position as an A1-style cell reference
+fn a1_cell((row, col): (u32, u32)) -> String {
+    let mut letters = Vec::new();
+    let mut col = col + 1;
+    while col > 0 {
+        col -= 1;
+        letters.push(char::from(b'A' + (col % 26) as u8));
+        col /= 26;
+    }
+    letters.iter().rev().collect::<String>() + itoa::Buffer::new().format(row + 1)
+}
+
+/// parses an A1-style range (e.g. "A1:D10", or just "A1") into its zero-based
+/// (start, end) positions
+fn parse_a1_range(range_ref: &str) -> Option<((u32, u32), (u32, u32))> {
+    let (start_ref, end_ref) = range_ref.split_once(':').unwrap_or((range_ref, range_ref));
+    let (start, end) = (parse_a1_cell(start_ref)?, parse_a1_cell(end_ref)?);
+    if start.0 > end.0 || start.1 > end.1 {
+        return None;
+    }
+    Some((start, end))
+}
+
+/// moves the non-empty cells out of range, passing them to f with their relative
+/// row & column. The cells are moved rather than cloned, and range is dropped when done,
+/// so reshaping a sheet doesn't copy its cells. This doesn't lower the peak memory use,
+/// as calamine 0.19 reads the whole sheet into range first.
+fn drain_used_cells(mut range: Range<DataType>, mut f: impl FnMut(usize, usize, DataType)) {
+    let (height, width) = range.get_size();
+    for row in 0..height {
+        for col in 0..width {
+            let cell = std::mem::take(&mut range[(row, col)]);
+            if !cell.is_empty() {
+                f(row, col, cell);
+            }
+        }
+    }
+}
+
+/// returns range resized to the absolute start & end positions.
+/// Cells outside of the new range are dropped, and new cells are empty.
+fn resize_range(range: Range<DataType>, start: (u32, u32), end: (u32, u32)) -> Range<DataType> {
+    let mut resized = Range::new(start, end);
+    if let Some((row_offset, col_offset)) = range.start() {
+        drain_used_cells(range, |row, col, cell| {
+            let pos = (row_offset + row as u32, col_offset + col as u32);
+            if (start.0..=end.0).contains(&pos.0) && (start.1..=end.1).contains(&pos.1) {
+                resized.set_value(pos, cell);
+            }
+        });
+    }
+    resized
+}
+
+/// returns the range with its rows & columns swapped, for --transpose
+fn transpose_range(range: Range<DataType>) -> Range<DataType> {
+    let (Some((start_row, start_col)), Some((end_row, end_col))) = (range.start(), range.end())
+    else {
+        return Range::empty();
+    };
+    let mut transposed = Range::new((start_col, start_row), (end_col, end_row));
+    drain_used_cells(range, |row, col, cell| {
+        transposed.set_value((start_col + col as u32, start_row + row as u32), cell);
+    });
+    transposed
+}
+
+/// formats a datetime cell with the --date-format, or in the ISO 8601 format if it is not set.
+/// With timezones, the datetime is in the first timezone & formatted in the second one,
+/// with its UTC offset if there is no --date-format.
+fn format_datetime(
+    dt: NaiveDateTime,
+    date_format: Option<&str>,
+    timezones: Option<(Tz, Tz)>,
+) -> String {
+    let Some((assumed_tz, output_tz)) = timezones else {
+        return match date_format {
+            Some(date_format) => dt.format(date_format).to_string(),
+            None => format!("{dt}"),
+        };
+    };
+    // ambiguous local times (when the clocks go back) are the earliest of the two,
+    // and the skipped ones (when the clocks go forward) don't exist
+    let Some(zoned) = assumed_tz.from_local_datetime(&dt).earliest() else {
+        return format!("ERROR: {dt} does not exist in the {assumed_tz} timezone");
+    };
+    zoned
+        .with_timezone(&output_tz)
+        .format(date_format.unwrap_or("%Y-%m-%d %H:%M:%S%:z"))
+        .to_string()
+}
+
+/// formats a number of days (e.g. a duration or a time of day) as HH:MM:SS,
+/// with the hours going past 24 for the durations of more than a day
+fn format_elapsed_time(days: f64) -> String {
+    let seconds = (days * 86_400.0).round() as i64;
+    let sign = if seconds < 0 { "-" } else { "" };
+    let seconds = seconds.abs();
+    format!(
+        "{sign}{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// returns the first max_length characters of text followed by ellipsis,
+/// or None if text is not longer than max_length characters
+fn truncate_cell(text: &str, max_length: usize, ellipsis: &str) -> Option<String> {
+    text.char_indices()
+        .nth(max_length)
+        .map(|(byte_idx, _)| format!("{}{ellipsis}", &text[..byte_idx]))
+}
+
+/// returns the text Excel displays for an error cell
+fn excel_error_text(error: &CellErrorType) -> &'static str {
+    match error {
+        CellErrorType::Div0 => "#DIV/0!",
+        CellErrorType::NA => "#N/A",
+        CellErrorType::Name => "#NAME?",
+        CellErrorType::Null => "#NULL!",
+        CellErrorType::Num => "#NUM!",
+        CellErrorType::Ref => "#REF!",
+        CellErrorType::Value => "#VALUE!",
+        CellErrorType::GettingData => "#DATA!",
+    }
+}
+
+/// returns the tightest range that contains all the non-empty cells of range.
+/// Empty strings are considered empty.
+fn tight_range(range: Range<DataType>) -> Range<DataType> {
+    let Some((row_offset, col_offset)) = range.start() else {
+        return Range::empty();
+    };
+    let mut bounds: Option<((u32, u32), (u32, u32))> = None;
+    for (row, col, cell) in range.used_cells() {
+        if cell.is_empty() || matches!(cell, DataType::String(s) if s.is_empty()) {
+            continue;
+        }
+        let (row, col) = (row_offset + row as u32, col_offset + col as u32);
+        bounds = Some(match bounds {
+            None => ((row, col), (row, col)),
+            Some((start, end)) => (
+                (start.0.min(row), start.1.min(col)),
+                (end.0.max(row), end.1.max(col)),
+            ),
+        });
+    }
+    match bounds {
+        Some((start, end)) => resize_range(range, start, end),
+        None => Range::empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests_for_sheet_filenames {
+
+    use super::*;
+
+    #[test]
+    fn test_sanitize_illegal_characters() {
+        let mut used_names = Vec::new();
+        assert_eq!(
+            sanitize_sheet_filename("Q1 <draft>|v2.", &mut used_names),
+            "Q1 _draft__v2"
+        );
+        assert_eq!(
+            sanitize_sheet_filename("a/b\\c:d*e?f", &mut used_names),
+            "a_b_c_d_e_f"
+        );
+        assert_eq!(sanitize_sheet_filename(" . ", &mut used_names), "sheet");
+    }
+
+    #[test]
+    fn test_sanitize_collisions() {
+        let mut used_names = Vec::new();
+        assert_eq!(sanitize_sheet_filename("Data", &mut used_names), "Data");
+        assert_eq!(sanitize_sheet_filename("DATA", &mut used_names), "DATA_2");
+        assert_eq!(sanitize_sheet_filename("data.", &mut used_names), "data_3");
+        assert_eq!(used_names, vec!["Data", "DATA_2", "data_3"]);
+    }
+
+    #[test]
+    fn test_sanitize_windows_reserved_names() {
+        let mut used_names = Vec::new();
+        assert_eq!(sanitize_sheet_filename("CON", &mut used_names), "CON_");
+        assert_eq!(sanitize_sheet_filename("nul", &mut used_names), "nul_");
+        assert_eq!(sanitize_sheet_filename("Com1", &mut used_names), "Com1_");
+        assert_eq!(sanitize_sheet_filename("LPT9", &mut used_names), "LPT9_");
+        assert_eq!(
+            sanitize_sheet_filename("PRN.backup", &mut used_names),
+            "PRN_.backup"
+        );
+        assert_eq!(sanitize_sheet_filename("AUX ", &mut used_names), "AUX_");
+        // only the device names themselves are reserved
+        assert_eq!(
+            sanitize_sheet_filename("CONSOLE", &mut used_names),
+            "CONSOLE"
+        );
+        assert_eq!(sanitize_sheet_filename("COM10", &mut used_names), "COM10");
+    }
 }
@@ -9,8 +9,15 @@ By default, there is a row for the N most frequent values for each field in the
 data. The order and number of values can be tweaked with --asc and --limit,
 respectively.
 
+With --combine, the frequency table is instead computed over the distinct
+combinations of the selected columns' values (e.g. the frequency of each
+(region, product) pair), with a row per combination:
+
+    <selected column 1>,<selected column 2>,...,count
+
 Since this computes an exact frequency table, memory proportional to the
-cardinality of each column is required.
+cardinality of each column (or of the column combinations with --combine)
+is required.
 
 For examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_frequency.rs.
 
@@ -30,6 +37,11 @@ frequency options:
     -a, --asc              Sort the frequency tables in ascending order by
                            count. The default is descending order.
     --no-nulls             Don't include NULLs in the frequency table.
+                           With --combine, combinations with a NULL in any
+                           of the selected columns are not included.
+    --combine              Compute the frequency of the combinations of the
+                           selected columns' values instead of each column
+                           separately. The --limit & --asc options still apply.
     -j, --jobs <arg>       The number of jobs to run in parallel.
                            This works better when the given CSV data has
                            an index already created. Note that a file handle
@@ -68,6 +80,7 @@ pub struct Args {
     pub flag_limit:      usize,
     pub flag_asc:        bool,
     pub flag_no_nulls:   bool,
+    pub flag_combine:    bool,
     pub flag_jobs:       Option<usize>,
     pub flag_output:     Option<String>,
     pub flag_no_headers: bool,
@@ -84,6 +97,10 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     }
 
     let mut wtr = Config::new(&args.flag_output).writer()?;
+    if args.flag_combine {
+        return args.combined_ftable(&mut wtr);
+    }
+
     let (headers, tables) = match args.rconfig().indexed()? {
         Some(ref mut idx) if util::njobs(args.flag_jobs) > 1 => args.parallel_ftables(idx),
         _ => args.sequential_ftables(),
@@ -140,6 +157,64 @@ impl Args {
             .collect()
     }
 
+    fn combined_ftable<W: io::Write>(&self, wtr: &mut csv::Writer<W>) -> CliResult<()> {
+        let rconfig = self.rconfig();
+        let mut rdr = rconfig.reader()?;
+        let (headers, sel) = self.sel_headers(&mut rdr)?;
+
+        let mut ftab: Frequencies<Vec<ByteString>> = Frequencies::new();
+        let mut row_work = csv::ByteRecord::new();
+        'rows: while rdr.read_byte_record(&mut row_work)? {
+            let mut combination: Vec<ByteString> = Vec::with_capacity(sel.len());
+            for field in sel.select(&row_work) {
+                let field_work = match std::str::from_utf8(field) {
+                    Ok(s) => s.trim().as_bytes().to_vec(),
+                    Err(_) => field.to_vec(),
+                };
+                if field_work.is_empty() && self.flag_no_nulls {
+                    continue 'rows;
+                }
+                combination.push(field_work);
+            }
+            ftab.add(combination);
+        }
+
+        let mut header_row: Vec<ByteString> = if rconfig.no_headers {
+            sel.iter()
+                .map(|i| (i + 1).to_string().into_bytes())
+                .collect()
+        } else {
+            headers.iter().map(<[u8]>::to_vec).collect()
+        };
+        header_row.push(b"count".to_vec());
+        wtr.write_record(&header_row)?;
+
+        let mut counts = if self.flag_asc {
+            ftab.least_frequent()
+        } else {
+            ftab.most_frequent()
+        };
+        if self.flag_limit > 0 {
+            counts.truncate(self.flag_limit);
+        }
+        for (combination, count) in counts {
+            let mut row: Vec<&[u8]> = combination
+                .iter()
+                .map(|value| {
+                    if value.is_empty() {
+                        &b"(NULL)"[..]
+                    } else {
+                        &value[..]
+                    }
+                })
+                .collect();
+            let count = count.to_string();
+            row.push(count.as_bytes());
+            wtr.write_record(row)?;
+        }
+        Ok(wtr.flush()?)
+    }
+
     pub fn sequential_ftables(&self) -> CliResult<(Headers, FTables)> {
         let mut rdr = self.rconfig().reader()?;
         let (headers, sel) = self.sel_headers(&mut rdr)?;
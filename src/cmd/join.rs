@@ -14,6 +14,13 @@ separating them with a comma. Specify a range of columns with `-`. Both
 columns1 and columns2 must specify exactly the same number of columns.
 (See 'qsv select --help' for the full syntax.)
 
+Joins are done by loading one of the inputs into an in-memory hash index, and
+streaming the other input against it. By default, <input2> is indexed for
+inner, --left, --left-anti, --left-semi and --full joins, and <input1> is
+indexed for --right joins. Use --index-side to load the smaller input into
+memory instead. Note that the output rows follow the order of the streamed
+input, so changing the indexed side changes the order of the joined rows.
+
 For examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_join.rs.
 
 Usage:
@@ -30,7 +37,7 @@ join options:
                            first CSV data set, including rows with no
                            corresponding row in the second data set. When no
                            corresponding row exists, it is padded out with
-                           empty fields.
+                           empty fields (or the --fill value).
     --left-anti            Do a 'left anti' join. This returns all rows in
                            first CSV data set that has no match with the 
                            second data set.
@@ -41,12 +48,14 @@ join options:
                            second CSV data set, including rows with no
                            corresponding row in the first data set. When no
                            corresponding row exists, it is padded out with
-                           empty fields. (This is the reverse of 'outer left'.)
+                           empty fields (or the --fill value).
+                           (This is the reverse of 'outer left'.)
     --full                 Do a 'full outer' join. This returns all rows in
                            both data sets with matching records joined. If
                            there is no match, the missing side will be padded
-                           out with empty fields. (This is the combination of
-                           'outer left' and 'outer right'.)
+                           out with empty fields (or the --fill value).
+                           (This is the combination of 'outer left' and
+                           'outer right'.)
     --cross                USE WITH CAUTION.
                            This returns the cartesian product of the CSV
                            data sets given. The number of rows return is
@@ -56,6 +65,23 @@ join options:
                            Otherwise, empty fields are completely ignored.
                            (In fact, any row that has an empty field in the
                            key specified is ignored.)
+    --match-flag <name>    Append a column named <name>, set to true if the row
+                           has a join partner in the other data set, and to false
+                           otherwise. Only valid with --left, --right, --full,
+                           --left-anti & --left-semi. With --left-anti and
+                           --left-semi, ALL the rows of the first CSV data set are
+                           returned (still without the columns of the second data
+                           set) and the match flag tells whether they have a match.
+    --fill <value>         The value of the fields padding out the rows without a
+                           join partner with --left, --right & --full, instead of
+                           empty fields (e.g. NULL or 0).
+    --index-side <side>    The input to load into the hash index: 'left' for
+                           <input1>, 'right' for <input2> or 'auto' to index
+                           the smaller input by file size (when reading from
+                           stdin, auto uses the default side of the join).
+                           When not set, the default side of the join is used.
+                           --left-anti and --left-semi only support indexing
+                           <input2>, and --cross does not use an index.
 
 Common options:
     -h, --help             Display this message
@@ -67,7 +93,7 @@ Common options:
                            Must be a single character. (default: ,)
 "#;
 
-use std::{collections::hash_map::Entry, fmt, io, iter::repeat, str};
+use std::{collections::hash_map::Entry, fmt, fs, io, iter::repeat, str};
 
 use ahash::AHashMap;
 use byteorder::{BigEndian, WriteBytesExt};
@@ -96,11 +122,35 @@ struct Args {
     flag_no_headers:  bool,
     flag_ignore_case: bool,
     flag_nulls:       bool,
+    flag_match_flag:  Option<String>,
+    flag_fill:        Option<String>,
+    flag_index_side:  Option<String>,
     flag_delimiter:   Option<Delimiter>,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum IndexSide {
+    Left,
+    Right,
+    Auto,
+}
+
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
+    let index_side = args.index_side()?;
+    if args.flag_match_flag.is_some()
+        && (args.flag_cross
+            || !(args.flag_left
+                || args.flag_left_anti
+                || args.flag_left_semi
+                || args.flag_right
+                || args.flag_full))
+    {
+        return fail!(
+            "--match-flag can only be used with --left, --right, --full, --left-anti or \
+             --left-semi."
+        );
+    }
     let mut state = args.new_io_state()?;
     match (
         args.flag_left,
@@ -112,31 +162,34 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     ) {
         (true, false, false, false, false, false) => {
             state.write_headers()?;
-            state.outer_join(false)
+            state.hash_join(args.index_left(index_side, false), true, false)
         }
         (false, true, false, false, false, false) => {
+            args.check_index_right(index_side)?;
             state.write_headers1()?;
             state.left_join(true)
         }
         (false, false, true, false, false, false) => {
+            args.check_index_right(index_side)?;
             state.write_headers1()?;
             state.left_join(false)
         }
         (false, false, false, true, false, false) => {
             state.write_headers()?;
-            state.outer_join(true)
+            state.hash_join(args.index_left(index_side, true), false, true)
         }
         (false, false, false, false, true, false) => {
             state.write_headers()?;
-            state.full_outer_join()
+            state.hash_join(args.index_left(index_side, false), true, true)
         }
         (false, false, false, false, false, true) => {
+            args.check_index_right(index_side)?;
             state.write_headers()?;
             state.cross_join()
         }
         (false, false, false, false, false, false) => {
             state.write_headers()?;
-            state.inner_join()
+            state.hash_join(args.index_left(index_side, false), false, false)
         }
         _ => fail!("Please pick exactly one join operation."),
     }
@@ -151,6 +204,8 @@ struct IoState<R, W: io::Write> {
     no_headers: bool,
     casei:      bool,
     nulls:      bool,
+    match_flag: Option<String>,
+    fill:       String,
 }
 
 impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
@@ -158,6 +213,9 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
         if !self.no_headers {
             let mut headers = self.rdr1.byte_headers()?.clone();
             headers.extend(self.rdr2.byte_headers()?.iter());
+            if let Some(ref match_flag) = self.match_flag {
+                headers.push_field(match_flag.as_bytes());
+            }
             self.wtr.write_record(&headers)?;
         }
         Ok(())
@@ -165,58 +223,82 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
 
     fn write_headers1(&mut self) -> CliResult<()> {
         if !self.no_headers {
-            let headers = self.rdr1.byte_headers()?;
-            self.wtr.write_record(headers)?;
-        }
-        Ok(())
-    }
-
-    fn inner_join(mut self) -> CliResult<()> {
-        let mut scratch = csv::ByteRecord::new();
-        let mut validx = ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.nulls)?;
-        for row in self.rdr1.byte_records() {
-            let row = row?;
-            let key = get_row_key(&self.sel1, &row, self.casei);
-            if let Some(rows) = validx.values.get(&key) {
-                for &rowi in rows.iter() {
-                    validx.idx.seek(rowi as u64)?;
-
-                    validx.idx.read_byte_record(&mut scratch)?;
-                    let combined = row.iter().chain(scratch.iter());
-                    self.wtr.write_record(combined)?;
-                }
+            let mut headers = self.rdr1.byte_headers()?.clone();
+            if let Some(ref match_flag) = self.match_flag {
+                headers.push_field(match_flag.as_bytes());
             }
+            self.wtr.write_record(&headers)?;
         }
         Ok(())
     }
 
-    fn outer_join(mut self, right: bool) -> CliResult<()> {
-        if right {
+    /// Joins the inputs by loading one of them into a hash index and
+    /// streaming the other one against it. Unmatched rows of the left
+    /// (`keep_left`) and/or right (`keep_right`) input are padded out
+    /// with the --fill value (empty fields by default).
+    fn hash_join(mut self, index_left: bool, keep_left: bool, keep_right: bool) -> CliResult<()> {
+        let (pad1, pad2) = self.get_padding()?;
+        // rdr2 is always the indexed input, and rdr1 the streamed one
+        let (keep_streamed, keep_indexed, pad_streamed, pad_indexed) = if index_left {
             ::std::mem::swap(&mut self.rdr1, &mut self.rdr2);
             ::std::mem::swap(&mut self.sel1, &mut self.sel2);
-        }
+            (keep_right, keep_left, pad2, pad1)
+        } else {
+            (keep_left, keep_right, pad1, pad2)
+        };
 
+        let match_flag = self.match_flag.is_some();
         let mut scratch = csv::ByteRecord::new();
-        let (_, pad2) = self.get_padding()?;
         let mut validx = ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.nulls)?;
+
+        // Keep track of which indexed rows we've written.
+        let mut indexed_written: Vec<_> = if keep_indexed {
+            repeat(false).take(validx.num_rows).collect()
+        } else {
+            vec![]
+        };
         for row in self.rdr1.byte_records() {
             let row = row?;
             let key = get_row_key(&self.sel1, &row, self.casei);
             if let Some(rows) = validx.values.get(&key) {
                 for &rowi in rows.iter() {
+                    if keep_indexed {
+                        indexed_written[rowi] = true;
+                    }
                     validx.idx.seek(rowi as u64)?;
-                    let row1 = row.iter();
                     validx.idx.read_byte_record(&mut scratch)?;
-                    if right {
-                        self.wtr.write_record(scratch.iter().chain(row1))?;
-                    } else {
-                        self.wtr.write_record(row1.chain(&scratch))?;
-                    }
+                    write_joined(
+                        &mut self.wtr,
+                        index_left,
+                        &row,
+                        &scratch,
+                        match_flag.then_some(true),
+                    )?;
                 }
-            } else if right {
-                self.wtr.write_record(pad2.iter().chain(&row))?;
-            } else {
-                self.wtr.write_record(row.iter().chain(&pad2))?;
+            } else if keep_streamed {
+                write_joined(
+                    &mut self.wtr,
+                    index_left,
+                    &row,
+                    &pad_indexed,
+                    match_flag.then_some(false),
+                )?;
+            }
+        }
+
+        // OK, now write any indexed row that didn't get joined with a
+        // streamed row.
+        for (i, &written) in indexed_written.iter().enumerate() {
+            if !written {
+                validx.idx.seek(i as u64)?;
+                validx.idx.read_byte_record(&mut scratch)?;
+                write_joined(
+                    &mut self.wtr,
+                    index_left,
+                    &pad_streamed,
+                    &scratch,
+                    match_flag.then_some(false),
+                )?;
             }
         }
         Ok(())
@@ -224,6 +306,16 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
 
     fn left_join(mut self, anti: bool) -> CliResult<()> {
         let validx = ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.nulls)?;
+        if self.match_flag.is_some() {
+            // flag all the rows instead of filtering them
+            for row in self.rdr1.byte_records() {
+                let mut row = row?;
+                let key = get_row_key(&self.sel1, &row, self.casei);
+                row.push_field(match_flag_value(validx.values.contains_key(&key)));
+                self.wtr.write_record(&row)?;
+            }
+            return Ok(());
+        }
         let mut first_row: bool = true;
         for row in self.rdr1.byte_records() {
             let row = row?;
@@ -246,41 +338,6 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
         Ok(())
     }
 
-    fn full_outer_join(mut self) -> CliResult<()> {
-        let mut scratch = csv::ByteRecord::new();
-        let (pad1, pad2) = self.get_padding()?;
-        let mut validx = ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.nulls)?;
-
-        // Keep track of which rows we've written from rdr2.
-        let mut rdr2_written: Vec<_> = repeat(false).take(validx.num_rows).collect();
-        for row1 in self.rdr1.byte_records() {
-            let row1 = row1?;
-            let key = get_row_key(&self.sel1, &row1, self.casei);
-            if let Some(rows) = validx.values.get(&key) {
-                for &rowi in rows.iter() {
-                    rdr2_written[rowi] = true;
-
-                    validx.idx.seek(rowi as u64)?;
-                    validx.idx.read_byte_record(&mut scratch)?;
-                    self.wtr.write_record(row1.iter().chain(&scratch))?;
-                }
-            } else {
-                self.wtr.write_record(row1.iter().chain(&pad2))?;
-            }
-        }
-
-        // OK, now write any row from rdr2 that didn't get joined with a row
-        // from rdr1.
-        for (i, &written) in rdr2_written.iter().enumerate() {
-            if !written {
-                validx.idx.seek(i as u64)?;
-                validx.idx.read_byte_record(&mut scratch)?;
-                self.wtr.write_record(pad1.iter().chain(&scratch))?;
-            }
-        }
-        Ok(())
-    }
-
     fn cross_join(mut self) -> CliResult<()> {
         let mut pos = csv::Position::new();
         pos.set_byte(0);
@@ -303,14 +360,63 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
     fn get_padding(&mut self) -> CliResult<(csv::ByteRecord, csv::ByteRecord)> {
         let len1 = self.rdr1.byte_headers()?.len();
         let len2 = self.rdr2.byte_headers()?.len();
+        let fill = self.fill.as_bytes();
         Ok((
-            repeat(b"").take(len1).collect(),
-            repeat(b"").take(len2).collect(),
+            repeat(fill).take(len1).collect(),
+            repeat(fill).take(len2).collect(),
         ))
     }
 }
 
 impl Args {
+    fn index_side(&self) -> CliResult<Option<IndexSide>> {
+        let Some(side) = &self.flag_index_side else {
+            return Ok(None);
+        };
+        match side.to_lowercase().as_str() {
+            "left" => Ok(Some(IndexSide::Left)),
+            "right" => Ok(Some(IndexSide::Right)),
+            "auto" => Ok(Some(IndexSide::Auto)),
+            _ => fail_clierror!(
+                "Invalid --index-side: {side}. Valid values are left, right or auto."
+            ),
+        }
+    }
+
+    /// Returns true if <input1> should be loaded into the hash index,
+    /// with `default_left` being the default side of the join.
+    fn index_left(&self, index_side: Option<IndexSide>, default_left: bool) -> bool {
+        match index_side {
+            None => default_left,
+            Some(IndexSide::Left) => true,
+            Some(IndexSide::Right) => false,
+            Some(IndexSide::Auto) => {
+                if self.arg_input1 == "-" || self.arg_input2 == "-" {
+                    return default_left;
+                }
+                match (
+                    fs::metadata(&self.arg_input1),
+                    fs::metadata(&self.arg_input2),
+                ) {
+                    (Ok(meta1), Ok(meta2)) if meta1.len() != meta2.len() => {
+                        meta1.len() < meta2.len()
+                    }
+                    _ => default_left,
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::unused_self)]
+    fn check_index_right(&self, index_side: Option<IndexSide>) -> CliResult<()> {
+        if index_side == Some(IndexSide::Left) {
+            return fail_clierror!(
+                "--index-side left is not supported with --left-anti, --left-semi or --cross."
+            );
+        }
+        Ok(())
+    }
+
     fn new_io_state(
         &self,
     ) -> CliResult<IoState<Box<dyn SeekRead + 'static>, Box<dyn io::Write + 'static>>> {
@@ -335,6 +441,8 @@ impl Args {
             no_headers: rconf1.no_headers,
             casei: self.flag_ignore_case,
             nulls: self.flag_nulls,
+            match_flag: self.flag_match_flag.clone(),
+            fill: self.flag_fill.clone().unwrap_or_default(),
         })
     }
 
@@ -448,6 +556,34 @@ impl<R> fmt::Debug for ValueIndex<R> {
     }
 }
 
+/// Writes a joined record, always putting the fields of <input1> first,
+/// followed by the --match-flag value if `matched` is set.
+#[inline]
+fn write_joined<W: io::Write>(
+    wtr: &mut csv::Writer<W>,
+    index_left: bool,
+    streamed: &csv::ByteRecord,
+    indexed: &csv::ByteRecord,
+    matched: Option<bool>,
+) -> CliResult<()> {
+    let flag = matched.map(match_flag_value);
+    if index_left {
+        wtr.write_record(indexed.iter().chain(streamed).chain(flag))?;
+    } else {
+        wtr.write_record(streamed.iter().chain(indexed).chain(flag))?;
+    }
+    Ok(())
+}
+
+#[inline]
+fn match_flag_value(matched: bool) -> &'static [u8] {
+    if matched {
+        b"true"
+    } else {
+        b"false"
+    }
+}
+
 #[inline]
 fn get_row_key(sel: &Selection, row: &csv::ByteRecord, casei: bool) -> Vec<ByteString> {
     sel.select(row).map(|v| transform(v, casei)).collect()
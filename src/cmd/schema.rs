@@ -375,24 +375,29 @@ pub fn infer_schema_from_stats(args: &Args, input_filename: &str) -> CliResult<M
 /// returns tuple (`csv_fields`, `csv_stats`, `stats_col_index_map`)
 fn get_stats_records(args: &Args) -> CliResult<(ByteRecord, Vec<Stats>, AHashMap<String, usize>)> {
     let stats_args = crate::cmd::stats::Args {
-        arg_input:            args.arg_input.clone(),
-        flag_select:          crate::select::SelectColumns::parse("").unwrap(),
-        flag_everything:      false,
-        flag_typesonly:       false,
-        flag_mode:            false,
-        flag_cardinality:     true,
-        flag_median:          false,
-        flag_quartiles:       false,
-        flag_mad:             false,
-        flag_nulls:           false,
-        flag_round:           4,
-        flag_infer_dates:     true,
-        flag_dates_whitelist: args.flag_dates_whitelist.to_string(),
-        flag_prefer_dmy:      args.flag_prefer_dmy,
-        flag_jobs:            Some(util::njobs(args.flag_jobs)),
-        flag_output:          None,
-        flag_no_headers:      args.flag_no_headers,
-        flag_delimiter:       args.flag_delimiter,
+        arg_input:                    args.arg_input.clone(),
+        flag_select:                  crate::select::SelectColumns::parse("").unwrap(),
+        flag_everything:              false,
+        flag_group_by:                None,
+        flag_online:                  false,
+        flag_typesonly:               false,
+        flag_mode:                    false,
+        flag_cardinality:             true,
+        flag_entropy:                 false,
+        flag_entropy_max_cardinality: 10_000,
+        flag_median:                  false,
+        flag_quartiles:               false,
+        flag_mad:                     false,
+        flag_nulls:                   false,
+        flag_variance_algorithm:      "welford".to_string(),
+        flag_round:                   4,
+        flag_infer_dates:             true,
+        flag_dates_whitelist:         args.flag_dates_whitelist.to_string(),
+        flag_prefer_dmy:              args.flag_prefer_dmy,
+        flag_jobs:                    Some(util::njobs(args.flag_jobs)),
+        flag_output:                  None,
+        flag_no_headers:              args.flag_no_headers,
+        flag_delimiter:               args.flag_delimiter,
     };
 
     let (csv_fields, csv_stats) = match stats_args.rconfig().indexed() {
@@ -477,6 +482,7 @@ fn get_unique_values(
         flag_limit:      args.flag_enum_threshold,
         flag_asc:        false,
         flag_no_nulls:   true,
+        flag_combine:    false,
         flag_jobs:       Some(util::njobs(args.flag_jobs)),
         flag_output:     None,
         flag_no_headers: args.flag_no_headers,
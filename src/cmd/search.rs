@@ -37,6 +37,15 @@ search options:
                            Return exit code 1 if no match is found.
                            No output is produced.
     -c, --count            Return number of matches to stderr.
+    -B, --before <n>       Also write the <n> rows before each matching row, like
+                           grep's -B option. Overlapping rows are only written once.
+    -A, --after <n>        Also write the <n> rows after each matching row, like
+                           grep's -A option. Overlapping rows are only written once.
+    -C, --context <n>      Set both --before and --after to <n>, unless they're set.
+                           The context options cannot be used with --flag.
+    --group-separator <sep>  With the context options, write a row with <sep> in its
+                           first column (and empty other columns) between the
+                           groups of rows that are not contiguous.
     --size-limit <mb>      Set the approximate size limit (MB) of the compiled
                            regular expression. If the compiled expression exceeds this 
                            number, then a compilation error is returned.
@@ -59,7 +68,7 @@ Common options:
     -Q, --quiet            Do not return number of matches to stderr.
 "#;
 
-use std::env;
+use std::{collections::VecDeque, env};
 
 #[cfg(any(feature = "full", feature = "lite"))]
 use indicatif::{HumanCount, ProgressBar, ProgressDrawTarget};
@@ -76,26 +85,36 @@ use crate::{
 #[allow(dead_code)]
 #[derive(Deserialize)]
 struct Args {
-    arg_input:           Option<String>,
-    arg_regex:           String,
-    flag_select:         SelectColumns,
-    flag_output:         Option<String>,
-    flag_no_headers:     bool,
-    flag_delimiter:      Option<Delimiter>,
-    flag_invert_match:   bool,
-    flag_unicode:        bool,
-    flag_ignore_case:    bool,
-    flag_flag:           Option<String>,
-    flag_size_limit:     usize,
-    flag_dfa_size_limit: usize,
-    flag_quick:          bool,
-    flag_count:          bool,
-    flag_progressbar:    bool,
-    flag_quiet:          bool,
+    arg_input:            Option<String>,
+    arg_regex:            String,
+    flag_select:          SelectColumns,
+    flag_output:          Option<String>,
+    flag_no_headers:      bool,
+    flag_delimiter:       Option<Delimiter>,
+    flag_invert_match:    bool,
+    flag_unicode:         bool,
+    flag_ignore_case:     bool,
+    flag_flag:            Option<String>,
+    flag_size_limit:      usize,
+    flag_dfa_size_limit:  usize,
+    flag_quick:           bool,
+    flag_count:           bool,
+    flag_before:          Option<usize>,
+    flag_after:           Option<usize>,
+    flag_context:         Option<usize>,
+    flag_group_separator: Option<String>,
+    flag_progressbar:     bool,
+    flag_quiet:           bool,
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
+    let before = args.flag_before.or(args.flag_context).unwrap_or(0);
+    let after = args.flag_after.or(args.flag_context).unwrap_or(0);
+    let with_context = before > 0 || after > 0;
+    if with_context && args.flag_flag.is_some() {
+        return fail!("--before, --after & --context cannot be used with --flag.");
+    }
     let regex_unicode = if env::var("QSV_REGEX_UNICODE").is_ok() {
         true
     } else {
@@ -144,6 +163,20 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         progress.set_draw_target(ProgressDrawTarget::hidden());
     }
 
+    // with the context options, the rows before the current row that were not written yet,
+    // the number of rows still to write after the last match, and the last written row
+    let mut before_rows: VecDeque<csv::ByteRecord> = VecDeque::with_capacity(before);
+    let mut after_left = 0_usize;
+    let mut last_written_row: Option<u64> = None;
+    let separator_record = args.flag_group_separator.as_ref().map(|separator| {
+        let mut separator_record = csv::ByteRecord::new();
+        separator_record.push_field(separator.as_bytes());
+        for _ in 1..headers.len() {
+            separator_record.push_field(b"");
+        }
+        separator_record
+    });
+
     let mut record = csv::ByteRecord::new();
     let mut flag_rowi: u64 = 1;
     let mut match_ctr: u64 = 0;
@@ -178,6 +211,30 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 b"0"
             });
             wtr.write_byte_record(&record)?;
+        } else if with_context {
+            if m {
+                let first_row = row_ctr - before_rows.len() as u64;
+                if let Some(ref separator_record) = separator_record {
+                    if last_written_row.map_or(false, |last_row| first_row > last_row + 1) {
+                        wtr.write_byte_record(separator_record)?;
+                    }
+                }
+                for before_record in before_rows.drain(..) {
+                    wtr.write_byte_record(&before_record)?;
+                }
+                wtr.write_byte_record(&record)?;
+                after_left = after;
+                last_written_row = Some(row_ctr);
+            } else if after_left > 0 {
+                wtr.write_byte_record(&record)?;
+                after_left -= 1;
+                last_written_row = Some(row_ctr);
+            } else if before > 0 {
+                if before_rows.len() == before {
+                    before_rows.pop_front();
+                }
+                before_rows.push_back(record.clone());
+            }
         } else if m {
             wtr.write_byte_record(&record)?;
         }
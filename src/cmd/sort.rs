@@ -5,9 +5,16 @@ reverse, unique or random (with optional seed) order.
 The sort is done in lexicographical order.
 https://en.wikipedia.org/wiki/Lexicographic_order
 
-Note that this requires reading all of the CSV data into memory. If
-you need to sort a large file that may not fit into memory, use the
-extsort command instead.
+Note that by default, this requires reading all of the CSV data into memory.
+If you need to sort a large file that may not fit into memory, set --memory-limit
+or use the extsort command instead.
+
+With --memory-limit, rows are buffered until their size reaches the limit. The
+buffered rows are then sorted and spilled to a temporary file, and once all the
+rows have been read, the spill files are merged into the sorted output. If all
+the rows fit within the limit, nothing is spilled. The spill files are created in
+--tmp-dir, and are deleted as soon as they are closed, including when qsv errors
+out or is interrupted. There is no memory limit by default.
 
 For examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_sort.rs.
 
@@ -28,6 +35,12 @@ sort options:
     -j, --jobs <arg>        The number of jobs to run in parallel.
                             When not set, the number of jobs is set to the
                             number of CPUs detected.
+    --memory-limit <size>   The size of the rows to buffer in memory before sorting
+                            and spilling them to disk, in bytes or with a KB, MB
+                            or GB suffix (e.g. 500MB). Cannot be used with --random.
+    --tmp-dir <path>        The directory of the --memory-limit spill files.
+                            When not set, the TMPDIR environment variable is used,
+                            falling back on the system's temporary directory.
 
 Common options:
     -h, --help              Display this message
@@ -40,8 +53,16 @@ Common options:
                             Must be a single character. (default: ,)
 "#;
 
-use std::cmp;
+use std::{
+    cmp,
+    collections::BinaryHeap,
+    fs::File,
+    io::{Seek, SeekFrom},
+    mem,
+    path::{Path, PathBuf},
+};
 
+use csv::ByteRecord;
 use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
 use rayon::prelude::*;
 use serde::Deserialize;
@@ -56,18 +77,20 @@ use crate::{
 
 #[derive(Deserialize)]
 struct Args {
-    arg_input:        Option<String>,
-    flag_select:      SelectColumns,
-    flag_numeric:     bool,
-    flag_reverse:     bool,
-    flag_random:      bool,
-    flag_seed:        Option<u64>,
-    flag_ignore_case: bool,
-    flag_jobs:        Option<usize>,
-    flag_output:      Option<String>,
-    flag_no_headers:  bool,
-    flag_delimiter:   Option<Delimiter>,
-    flag_unique:      bool,
+    arg_input:         Option<String>,
+    flag_select:       SelectColumns,
+    flag_numeric:      bool,
+    flag_reverse:      bool,
+    flag_random:       bool,
+    flag_seed:         Option<u64>,
+    flag_ignore_case:  bool,
+    flag_jobs:         Option<usize>,
+    flag_output:       Option<String>,
+    flag_no_headers:   bool,
+    flag_delimiter:    Option<Delimiter>,
+    flag_unique:       bool,
+    flag_memory_limit: Option<String>,
+    flag_tmp_dir:      Option<String>,
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
@@ -80,9 +103,20 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         .no_headers(args.flag_no_headers)
         .select(args.flag_select);
 
-    // we're loading the entire file into memory, we need to check avail mem
-    if let Some(path) = rconfig.path.clone() {
-        util::mem_file_check(&path, false)?;
+    let memory_limit = match args.flag_memory_limit {
+        Some(ref memory_limit) => Some(parse_memory_size(memory_limit)?),
+        None => None,
+    };
+    if memory_limit.is_some() && random {
+        return fail!("--memory-limit cannot be used with --random.");
+    }
+
+    // without a memory limit, we're loading the entire file into memory,
+    // we need to check avail mem
+    if memory_limit.is_none() {
+        if let Some(path) = rconfig.path.clone() {
+            util::mem_file_check(&path, false)?;
+        }
     }
 
     let mut rdr = rconfig.reader()?;
@@ -98,56 +132,28 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let ignore_case = args.flag_ignore_case;
 
-    let mut all = rdr.byte_records().collect::<Result<Vec<_>, _>>()?;
-    match (numeric, reverse, random) {
-        (_, _, true) => {
-            // we don't need cryptographically strong RNGs for this
-            // add DevSkim lint ignores to suppress warning
-            if let Some(val) = seed {
-                let mut rng = StdRng::seed_from_u64(val); //DevSkim: ignore DS148264
-                SliceRandom::shuffle(&mut *all, &mut rng); //DevSkim: ignore DS148264
-            } else {
-                let mut rng = ::rand::thread_rng();
-                SliceRandom::shuffle(&mut *all, &mut rng); //DevSkim: ignore DS148264
-            }
-        }
-        (false, false, false) => all.par_sort_by(|r1, r2| {
-            let a = sel.select(r1);
-            let b = sel.select(r2);
-            if ignore_case {
-                iter_cmp_ignore_case(a, b)
-            } else {
-                iter_cmp(a, b)
-            }
-        }),
-        (true, false, false) => all.par_sort_by(|r1, r2| {
-            let a = sel.select(r1);
-            let b = sel.select(r2);
+    let compare = |r1: &ByteRecord, r2: &ByteRecord| {
+        let (a, b) = if reverse {
+            (sel.select(r2), sel.select(r1))
+        } else {
+            (sel.select(r1), sel.select(r2))
+        };
+        if numeric {
             iter_cmp_num(a, b)
-        }),
-        (false, true, false) => all.par_sort_by(|r1, r2| {
-            let a = sel.select(r1);
-            let b = sel.select(r2);
-            if ignore_case {
-                iter_cmp_ignore_case(b, a)
-            } else {
-                iter_cmp(b, a)
-            }
-        }),
-        (true, true, false) => all.par_sort_by(|r1, r2| {
-            let a = sel.select(r1);
-            let b = sel.select(r2);
-            iter_cmp_num(b, a)
-        }),
-    }
+        } else if ignore_case {
+            iter_cmp_ignore_case(a, b)
+        } else {
+            iter_cmp(a, b)
+        }
+    };
 
     let mut wtr = Config::new(&args.flag_output).writer()?;
-    let mut prev: Option<csv::ByteRecord> = None;
     rconfig.write_headers(&mut rdr, &mut wtr)?;
-    for r in all {
+    let mut prev: Option<ByteRecord> = None;
+    let mut write_sorted = |r: ByteRecord| -> CliResult<()> {
         if args.flag_unique {
             match prev {
-                Some(other_r) => match iter_cmp(sel.select(&r), sel.select(&other_r)) {
+                Some(ref other_r) => match iter_cmp(sel.select(&r), sel.select(other_r)) {
                     cmp::Ordering::Equal => (),
                     _ => {
                         wtr.write_byte_record(&r)?;
@@ -162,10 +168,182 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         } else {
             wtr.write_byte_record(&r)?;
         }
+        Ok(())
+    };
+
+    let Some(memory_limit) = memory_limit else {
+        let mut all = rdr.byte_records().collect::<Result<Vec<_>, _>>()?;
+        if random {
+            // we don't need cryptographically strong RNGs for this
+            // add DevSkim lint ignores to suppress warning
+            if let Some(val) = seed {
+                let mut rng = StdRng::seed_from_u64(val); //DevSkim: ignore DS148264
+                SliceRandom::shuffle(&mut *all, &mut rng); //DevSkim: ignore DS148264
+            } else {
+                let mut rng = ::rand::thread_rng();
+                SliceRandom::shuffle(&mut *all, &mut rng); //DevSkim: ignore DS148264
+            }
+        } else {
+            all.par_sort_by(compare);
+        }
+
+        for r in all {
+            write_sorted(r)?;
+        }
+        return Ok(wtr.flush()?);
+    };
+
+    let tmp_dir = args
+        .flag_tmp_dir
+        .as_ref()
+        .map_or_else(std::env::temp_dir, PathBuf::from);
+
+    // sort the rows in chunks of up to --memory-limit, spilling each chunk
+    // to disk if the rows don't all fit in a single chunk
+    let mut spill_files: Vec<File> = Vec::new();
+    let mut chunk: Vec<ByteRecord> = Vec::new();
+    let mut chunk_size = 0_usize;
+    for result in rdr.byte_records() {
+        let record = result?;
+        chunk_size += record.as_slice().len() + record.len() * mem::size_of::<usize>();
+        chunk.push(record);
+        if chunk_size >= memory_limit {
+            chunk.par_sort_by(compare);
+            spill_files.push(spill_chunk(&chunk, &tmp_dir)?);
+            chunk.clear();
+            chunk_size = 0;
+        }
+    }
+    chunk.par_sort_by(compare);
+
+    if spill_files.is_empty() {
+        for r in chunk {
+            write_sorted(r)?;
+        }
+        return Ok(wtr.flush()?);
+    }
+    if !chunk.is_empty() {
+        spill_files.push(spill_chunk(&chunk, &tmp_dir)?);
+    }
+    drop(chunk);
+    log::info!("merging {} sort spill files...", spill_files.len());
+
+    // k-way merge of the sorted spill files
+    let mut spill_rdrs = spill_files
+        .into_iter()
+        .map(|spill_file| {
+            csv::ReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .from_reader(spill_file)
+        })
+        .collect::<Vec<_>>();
+    let mut heap = BinaryHeap::with_capacity(spill_rdrs.len());
+    for (spill_idx, spill_rdr) in spill_rdrs.iter_mut().enumerate() {
+        let mut record = ByteRecord::new();
+        if spill_rdr.read_byte_record(&mut record)? {
+            heap.push(MergeRecord {
+                record,
+                spill_idx,
+                compare: &compare,
+            });
+        }
+    }
+    while let Some(MergeRecord {
+        record, spill_idx, ..
+    }) = heap.pop()
+    {
+        let mut next_record = ByteRecord::new();
+        if spill_rdrs[spill_idx].read_byte_record(&mut next_record)? {
+            heap.push(MergeRecord {
+                record: next_record,
+                spill_idx,
+                compare: &compare,
+            });
+        }
+        write_sorted(record)?;
     }
     Ok(wtr.flush()?)
 }
 
+/// The next record of a spill file, ordered so that the BinaryHeap (a max-heap)
+/// pops the smallest record first. Ties are popped in spill file order,
+/// which keeps the sort stable as the spill files are in input order.
+struct MergeRecord<'a> {
+    record:    ByteRecord,
+    spill_idx: usize,
+    compare:   &'a (dyn Fn(&ByteRecord, &ByteRecord) -> cmp::Ordering + Sync),
+}
+
+impl Ord for MergeRecord<'_> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        (self.compare)(&other.record, &self.record)
+            .then_with(|| other.spill_idx.cmp(&self.spill_idx))
+    }
+}
+
+impl PartialOrd for MergeRecord<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for MergeRecord<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == cmp::Ordering::Equal
+    }
+}
+
+impl Eq for MergeRecord<'_> {}
+
+/// Write a sorted chunk to a spill file in `tmp_dir`, rewound so it can be read back.
+/// The spill file has no name, so the OS deletes it as soon as it is closed -
+/// even if qsv errors out or is interrupted.
+fn spill_chunk(chunk: &[ByteRecord], tmp_dir: &Path) -> CliResult<File> {
+    let spill_file = match tempfile::tempfile_in(tmp_dir) {
+        Ok(spill_file) => spill_file,
+        Err(e) => {
+            return fail_clierror!(
+                "Cannot create sort spill file in {}: {e}",
+                tmp_dir.display()
+            )
+        }
+    };
+    let mut spill_wtr = csv::WriterBuilder::new()
+        .flexible(true)
+        .from_writer(spill_file);
+    for record in chunk {
+        spill_wtr.write_byte_record(record)?;
+    }
+    let mut spill_file = match spill_wtr.into_inner() {
+        Ok(spill_file) => spill_file,
+        Err(e) => return fail_clierror!("Cannot write sort spill file: {e}"),
+    };
+    spill_file.seek(SeekFrom::Start(0))?;
+    Ok(spill_file)
+}
+
+/// Parse a --memory-limit size, in bytes or with a (case-insensitive) KB, MB or GB suffix
+fn parse_memory_size(size: &str) -> CliResult<usize> {
+    let size_upper = size.trim().to_uppercase();
+    let number_end = size_upper
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size_upper.len());
+    let (number, unit) = size_upper.split_at(number_end);
+    let multiplier = match unit.trim() {
+        "" | "B" => 1_f64,
+        "K" | "KB" => 1_000_f64,
+        "M" | "MB" => 1_000_000_f64,
+        "G" | "GB" => 1_000_000_000_f64,
+        _ => return fail_clierror!("Invalid --memory-limit: {size}"),
+    };
+    match number.parse::<f64>() {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Ok(number) if number > 0.0 => Ok((number * multiplier) as usize),
+        _ => fail_clierror!("Invalid --memory-limit: {size}"),
+    }
+}
+
 /// Order `a` and `b` lexicographically using `Ord`
 #[inline]
 pub fn iter_cmp<A, L, R>(mut a: L, mut b: R) -> cmp::Ordering
@@ -59,6 +59,17 @@ stats options:
                               This is provided here because piping 'qsv select'
                               into 'qsv stats' will disable the use of indexing.
     --everything              Show all statistics available.
+    --group-by <cols>         Compute the statistics separately for each group of rows with
+                              the same values in the <cols> columns. The group values are
+                              prepended to each row of statistics, and the groups are reported
+                              in the order they're first seen. Indexes are not used with
+                              --group-by, and sparsity is computed against each group's row count.
+                              See 'qsv select --help' for the format details.
+    --online                  With --group-by, write out the statistics of each group as soon
+                              as the group ends, so only one group is kept in memory at a time.
+                              This requires the rows of each group to be contiguous (e.g. by
+                              sorting on the --group-by columns with the sort or extsort commands)
+                              - stats aborts if a group shows up again after it ended.
     --typesonly               Infer data types only and do not compute statistics.
                               Note that if you want to infer dates, you'll still need to use
                               the --infer-dates and --dates-whitelist options.
@@ -66,8 +77,23 @@ stats options:
                               This requires loading all CSV data in memory.
     --cardinality             Show the cardinality.
                               This requires loading all CSV data in memory.
+    --entropy                 Show the Shannon entropy (in bits, using log2) of the values of
+                              each column, and its normalized form (the entropy divided by
+                              log2(cardinality), between 0 and 1) - a measure of how evenly
+                              mixed a categorical column is.
+                              The value counts of each column are kept in memory, so entropy is
+                              not computed for columns with more distinct values than
+                              --entropy-max-cardinality.
+                              Not included with --everything.
+    --entropy-max-cardinality <n>  The cardinality above which --entropy is not computed
+                              for a column. [default: 10000]
     --median                  Show the median.
-                              This requires loading all CSV data in memory.
+                              The median is exact - with an even number of values, it is
+                              the average of the two middle values (e.g. 2.5 for 1,2,3,4),
+                              the same as the q2_median of --quartiles. It is empty for
+                              non-numeric & non-date columns.
+                              This requires loading all CSV data in memory, as an exact
+                              median cannot be computed in bounded memory.
     --mad                     Shows the median absolute deviation (MAD).
                               This requires loading all CSV data in memory.
     --quartiles               Show the quartiles, the IQR, the lower/upper inner/outer
@@ -80,6 +106,13 @@ stats options:
                               [default: 4]
     --nulls                   Include NULLs in the population size for computing
                               mean and standard deviation.
+    --variance-algorithm <alg>  The algorithm used to compute the variance & stddev.
+                              "welford" uses Welford's online algorithm, which stays accurate
+                              on columns with large magnitudes and a small variance.
+                              "naive" uses the textbook sum of squares formula, which can lose
+                              most of its precision on such columns - it's only meant
+                              for comparison & testing.
+                              [default: welford]
     --infer-dates             Infer date/datetime datatypes. This is an expensive
                               option and should only be used when you know there
                               are date/datetime fields.
@@ -146,6 +179,7 @@ use std::{
     sync::atomic::{AtomicBool, Ordering},
 };
 
+use ahash::{AHashMap, AHashSet};
 use itertools::Itertools;
 use once_cell::sync::OnceCell;
 use qsv_dateparser::parse_with_preference;
@@ -164,24 +198,29 @@ use crate::{
 #[allow(clippy::unsafe_derive_deserialize)]
 #[derive(Clone, Deserialize)]
 pub struct Args {
-    pub arg_input:            Option<String>,
-    pub flag_select:          SelectColumns,
-    pub flag_everything:      bool,
-    pub flag_typesonly:       bool,
-    pub flag_mode:            bool,
-    pub flag_cardinality:     bool,
-    pub flag_median:          bool,
-    pub flag_mad:             bool,
-    pub flag_quartiles:       bool,
-    pub flag_round:           u32,
-    pub flag_nulls:           bool,
-    pub flag_infer_dates:     bool,
-    pub flag_dates_whitelist: String,
-    pub flag_prefer_dmy:      bool,
-    pub flag_jobs:            Option<usize>,
-    pub flag_output:          Option<String>,
-    pub flag_no_headers:      bool,
-    pub flag_delimiter:       Option<Delimiter>,
+    pub arg_input:                    Option<String>,
+    pub flag_select:                  SelectColumns,
+    pub flag_everything:              bool,
+    pub flag_group_by:                Option<SelectColumns>,
+    pub flag_online:                  bool,
+    pub flag_typesonly:               bool,
+    pub flag_mode:                    bool,
+    pub flag_cardinality:             bool,
+    pub flag_entropy:                 bool,
+    pub flag_entropy_max_cardinality: usize,
+    pub flag_median:                  bool,
+    pub flag_mad:                     bool,
+    pub flag_quartiles:               bool,
+    pub flag_round:                   u32,
+    pub flag_nulls:                   bool,
+    pub flag_variance_algorithm:      String,
+    pub flag_infer_dates:             bool,
+    pub flag_dates_whitelist:         String,
+    pub flag_prefer_dmy:              bool,
+    pub flag_jobs:                    Option<usize>,
+    pub flag_output:                  Option<String>,
+    pub flag_no_headers:              bool,
+    pub flag_delimiter:               Option<Delimiter>,
 }
 
 static INFER_DATE_FLAGS: once_cell::sync::OnceCell<Vec<bool>> = OnceCell::new();
@@ -203,6 +242,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         args.flag_median = false;
         args.flag_quartiles = false;
         args.flag_mad = false;
+        args.flag_entropy = false;
     }
 
     let mut wtr = Config::new(&args.flag_output).writer()?;
@@ -217,12 +257,27 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             || args.flag_median
             || args.flag_quartiles
             || args.flag_mad
+            || args.flag_entropy
         {
             util::mem_file_check(&path, false)?;
         }
     }
 
     log::info!("scanning {record_count} records...");
+    if !matches!(args.flag_variance_algorithm.as_str(), "welford" | "naive") {
+        return fail_clierror!(
+            "Invalid --variance-algorithm: {}. It must be welford or naive.",
+            args.flag_variance_algorithm
+        );
+    }
+    if let Some(ref group_by) = args.flag_group_by {
+        args.grouped_stats(group_by, &mut wtr)?;
+        wtr.flush()?;
+        return Ok(());
+    }
+    if args.flag_online {
+        return fail!("--online requires --group-by.");
+    }
     let (headers, stats) = match fconfig.indexed()? {
         None => args.sequential_stats(&args.flag_dates_whitelist),
         Some(idx) => {
@@ -356,6 +411,121 @@ impl Args {
         stats
     }
 
+    /// computes the stats of each --group-by group. With --online, each group is written
+    /// out as soon as it ends, which requires the rows of each group to be contiguous.
+    fn grouped_stats<W: io::Write>(
+        &self,
+        group_by: &SelectColumns,
+        wtr: &mut csv::Writer<W>,
+    ) -> CliResult<()> {
+        let mut rdr = self.rconfig().reader()?;
+        let all_headers = rdr.byte_headers()?.clone();
+        let (headers, sel) = self.sel_headers(&mut rdr)?;
+        let group_sel = group_by.selection(&all_headers, !self.flag_no_headers)?;
+
+        init_date_inference(
+            self.flag_infer_dates,
+            self.flag_prefer_dmy,
+            &headers,
+            &self.flag_dates_whitelist,
+        )?;
+
+        let mut group_headers = csv::StringRecord::new();
+        for col_index in group_sel.iter() {
+            if self.flag_no_headers {
+                group_headers.push_field(&col_index.to_string());
+            } else {
+                group_headers.push_field(&String::from_utf8_lossy(&all_headers[*col_index]));
+            }
+        }
+        group_headers.extend(self.stat_headers().iter());
+        wtr.write_record(&group_headers)?;
+
+        let mut record = csv::ByteRecord::new();
+        if self.flag_online {
+            let mut group_key: Vec<Vec<u8>> = Vec::new();
+            // only the keys of the groups that ended are kept, to catch a group that's not
+            // contiguous instead of reporting it twice
+            let mut ended_groups: AHashSet<Vec<Vec<u8>>> = AHashSet::new();
+            let mut group_stats: Option<Vec<Stats>> = None;
+            let mut group_rows = 0_u64;
+            while rdr.read_byte_record(&mut record)? {
+                let key = group_sel.select(&record).map(<[u8]>::to_vec).collect_vec();
+                if group_stats.is_none() || key != group_key {
+                    if let Some(stats) = group_stats.take() {
+                        if ended_groups.contains(&key) {
+                            return fail_clierror!(
+                                "Aborting! Input not grouped by the --group-by columns! {:?} \
+                                 shows up again after {:?}",
+                                display_group_key(&key),
+                                display_group_key(&group_key)
+                            );
+                        }
+                        self.write_group_stats(wtr, &headers, &group_key, stats, group_rows)?;
+                        wtr.flush()?;
+                        ended_groups.insert(std::mem::take(&mut group_key));
+                    }
+                    group_key = key;
+                    group_stats = Some(self.new_stats(sel.len()));
+                    group_rows = 0;
+                }
+                if let Some(stats) = group_stats.as_mut() {
+                    add_to_stats(stats, &sel, &record);
+                }
+                group_rows += 1;
+            }
+            if let Some(stats) = group_stats {
+                self.write_group_stats(wtr, &headers, &group_key, stats, group_rows)?;
+            }
+        } else {
+            let mut group_index: AHashMap<Vec<Vec<u8>>, usize> = AHashMap::new();
+            let mut groups: Vec<(Vec<Vec<u8>>, Vec<Stats>, u64)> = Vec::new();
+            while rdr.read_byte_record(&mut record)? {
+                let key = group_sel.select(&record).map(<[u8]>::to_vec).collect_vec();
+                let idx = if let Some(idx) = group_index.get(&key) {
+                    *idx
+                } else {
+                    groups.push((key.clone(), self.new_stats(sel.len()), 0));
+                    group_index.insert(key, groups.len() - 1);
+                    groups.len() - 1
+                };
+                let (_, stats, group_rows) = &mut groups[idx];
+                add_to_stats(stats, &sel, &record);
+                *group_rows += 1;
+            }
+            for (group_key, stats, group_rows) in groups {
+                self.write_group_stats(wtr, &headers, &group_key, stats, group_rows)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_group_stats<W: io::Write>(
+        &self,
+        wtr: &mut csv::Writer<W>,
+        headers: &csv::ByteRecord,
+        group_key: &[Vec<u8>],
+        stats: Vec<Stats>,
+        group_rows: u64,
+    ) -> CliResult<()> {
+        for (i, (header, mut stat)) in headers.iter().zip(stats.into_iter()).enumerate() {
+            let header = if self.flag_no_headers {
+                i.to_string().into_bytes()
+            } else {
+                header.to_vec()
+            };
+            let stat = stat.to_record_with_count(self.flag_round, group_rows);
+            wtr.write_record(
+                group_key
+                    .iter()
+                    .map(Vec::as_slice)
+                    .chain(std::iter::once(&*header))
+                    .chain(stat.iter().map(str::as_bytes)),
+            )?;
+        }
+        Ok(())
+    }
+
     fn sel_headers<R: io::Read>(
         &self,
         rdr: &mut csv::Reader<R>,
@@ -377,16 +547,21 @@ impl Args {
         let mut stats: Vec<Stats> = Vec::with_capacity(record_len);
         stats.extend(
             repeat(Stats::new(WhichStats {
-                include_nulls: self.flag_nulls,
-                sum:           !self.flag_typesonly,
-                range:         !self.flag_typesonly,
-                dist:          !self.flag_typesonly,
-                cardinality:   self.flag_everything || self.flag_cardinality,
-                median:        !self.flag_everything && self.flag_median && !self.flag_quartiles,
-                mad:           self.flag_everything || self.flag_mad,
-                quartiles:     self.flag_everything || self.flag_quartiles,
-                mode:          self.flag_everything || self.flag_mode,
-                typesonly:     self.flag_typesonly,
+                include_nulls:           self.flag_nulls,
+                naive_variance:          self.flag_variance_algorithm == "naive",
+                sum:                     !self.flag_typesonly,
+                range:                   !self.flag_typesonly,
+                dist:                    !self.flag_typesonly,
+                cardinality:             self.flag_everything || self.flag_cardinality,
+                median:                  !self.flag_everything
+                    && self.flag_median
+                    && !self.flag_quartiles,
+                mad:                     self.flag_everything || self.flag_mad,
+                quartiles:               self.flag_everything || self.flag_quartiles,
+                mode:                    self.flag_everything || self.flag_mode,
+                entropy:                 self.flag_entropy,
+                entropy_max_cardinality: self.flag_entropy_max_cardinality,
+                typesonly:               self.flag_typesonly,
             }))
             .take(record_len),
         );
@@ -446,10 +621,30 @@ impl Args {
             fields.push("antimode_count");
             fields.push("antimode_occurrences");
         }
+        if self.flag_entropy {
+            fields.push("entropy");
+            fields.push("normalized_entropy");
+        }
         csv::StringRecord::from(fields)
     }
 }
 
+#[inline]
+fn add_to_stats(stats: &mut [Stats], sel: &Selection, record: &csv::ByteRecord) {
+    let infer_date_flags = INFER_DATE_FLAGS.get().unwrap();
+    for (i, field) in sel.select(record).enumerate() {
+        stats[i].add(field, infer_date_flags[i]);
+    }
+}
+
+// the group values of a --group-by group, for error messages
+fn display_group_key(group_key: &[Vec<u8>]) -> String {
+    group_key
+        .iter()
+        .map(|value| String::from_utf8_lossy(value))
+        .join(",")
+}
+
 #[inline]
 fn init_date_inference(
     infer_dates: bool,
@@ -503,16 +698,19 @@ fn init_date_inference(
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 struct WhichStats {
-    include_nulls: bool,
-    sum:           bool,
-    range:         bool,
-    dist:          bool,
-    cardinality:   bool,
-    median:        bool,
-    mad:           bool,
-    quartiles:     bool,
-    mode:          bool,
-    typesonly:     bool,
+    include_nulls:           bool,
+    naive_variance:          bool,
+    sum:                     bool,
+    range:                   bool,
+    dist:                    bool,
+    cardinality:             bool,
+    median:                  bool,
+    mad:                     bool,
+    quartiles:               bool,
+    mode:                    bool,
+    entropy:                 bool,
+    entropy_max_cardinality: usize,
+    typesonly:               bool,
 }
 
 impl Commute for WhichStats {
@@ -528,14 +726,86 @@ pub struct Stats {
     sum:       Option<TypedSum>,
     minmax:    Option<TypedMinMax>,
     online:    Option<OnlineStats>,
+    // the sums for --variance-algorithm naive
+    naive:     Option<NaiveVariance>,
     nullcount: u64,
     modes:     Option<Unsorted<Vec<u8>>>,
     median:    Option<Unsorted<f64>>,
     mad:       Option<Unsorted<f64>>,
     quartiles: Option<Unsorted<f64>>,
+    // the value counts for --entropy. Set to None when the column's cardinality
+    // goes over --entropy-max-cardinality
+    entropy:   Option<AHashMap<Vec<u8>, u64>>,
     which:     WhichStats,
 }
 
+/// returns the Shannon entropy (in bits) of the value counts, and the entropy
+/// normalized by the maximum entropy for the cardinality (log2(cardinality)).
+/// A column with a single distinct value has an entropy of 0.
+fn entropy(counts: &AHashMap<Vec<u8>, u64>) -> Option<(f64, f64)> {
+    let total: u64 = counts.values().sum();
+    if total == 0 {
+        return None;
+    }
+    let total = total as f64;
+    let entropy = counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum::<f64>();
+    let normalized_entropy = if counts.len() > 1 {
+        entropy / (counts.len() as f64).log2()
+    } else {
+        0.0
+    };
+    Some((entropy, normalized_entropy))
+}
+
+/// the textbook sum of squares variance, kept for --variance-algorithm naive.
+/// It suffers from catastrophic cancellation when the values are large compared
+/// to their spread, which is why Welford's algorithm is used by default.
+#[derive(Clone, Default)]
+struct NaiveVariance {
+    count:  u64,
+    sum:    f64,
+    sum_sq: f64,
+}
+
+impl NaiveVariance {
+    #[inline]
+    fn add(&mut self, n: f64) {
+        self.count += 1;
+        self.sum += n;
+        self.sum_sq += n * n;
+    }
+
+    #[inline]
+    fn add_null(&mut self) {
+        self.add(0.0);
+    }
+
+    /// the population variance, as also computed by `OnlineStats`
+    #[allow(clippy::cast_precision_loss)]
+    fn variance(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        let count = self.count as f64;
+        (self.sum_sq - self.sum * self.sum / count) / count
+    }
+}
+
+impl Commute for NaiveVariance {
+    #[inline]
+    fn merge(&mut self, other: NaiveVariance) {
+        self.count += other.count;
+        self.sum += other.sum;
+        self.sum_sq += other.sum_sq;
+    }
+}
+
 fn timestamp_ms_to_rfc3339(timestamp: i64, typ: FieldType) -> String {
     use chrono::prelude::*;
 
@@ -566,6 +836,11 @@ impl Stats {
         if which.dist {
             online = Some(stats::OnlineStats::default());
         }
+        let naive = if which.dist && which.naive_variance {
+            Some(NaiveVariance::default())
+        } else {
+            None
+        };
         if which.mode || which.cardinality {
             modes = Some(stats::Unsorted::default());
         }
@@ -577,16 +852,23 @@ impl Stats {
         if which.mad {
             mad = Some(stats::Unsorted::default());
         }
+        let entropy = if which.entropy {
+            Some(AHashMap::new())
+        } else {
+            None
+        };
         Stats {
             typ: FieldType::default(),
             sum,
             minmax,
             online,
+            naive,
             nullcount: 0,
             modes,
             median,
             mad,
             quartiles,
+            entropy,
             which,
         }
     }
@@ -616,6 +898,12 @@ impl Stats {
         if let Some(v) = self.modes.as_mut() {
             v.add(sample.to_vec());
         };
+        if let Some(counts) = self.entropy.as_mut() {
+            *counts.entry(sample.to_vec()).or_insert(0) += 1;
+            if counts.len() > self.which.entropy_max_cardinality {
+                self.entropy = None;
+            }
+        }
         if sample_type == TNull {
             self.nullcount += 1;
         }
@@ -634,6 +922,11 @@ impl Stats {
                             v.add_null();
                         };
                     }
+                    if self.which.include_nulls {
+                        if let Some(v) = self.naive.as_mut() {
+                            v.add_null();
+                        };
+                    }
                 } else {
                     let n = from_bytes::<f64>(sample);
                     if let Some(v) = self.median.as_mut() {
@@ -648,6 +941,9 @@ impl Stats {
                     if let Some(v) = self.online.as_mut() {
                         v.add(n);
                     }
+                    if let Some(v) = self.naive.as_mut() {
+                        v.add(n);
+                    }
                 }
             }
             TDateTime | TDate => {
@@ -685,6 +981,22 @@ impl Stats {
 
     #[allow(clippy::wrong_self_convention)]
     pub fn to_record(&mut self, round_places: u32) -> csv::StringRecord {
+        // stats is also called by the `schema` and `tojsonl` commands to infer a schema,
+        // sparsity is not required by those cmds and we don't necessarily have the
+        // record_count when called by those cmds, so just set sparsity to nullcount
+        // (div by 1) so we don't panic.
+        let record_count = *RECORD_COUNT.get().unwrap_or(&1);
+        self.to_record_with_count(round_places, record_count)
+    }
+
+    /// like `to_record`, but with sparsity computed against `record_count` rows,
+    /// e.g. the row count of a --group-by group
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_record_with_count(
+        &mut self,
+        round_places: u32,
+        record_count: u64,
+    ) -> csv::StringRecord {
         // we're doing typesonly
         if self.which.typesonly {
             return csv::StringRecord::from(vec![self.typ.to_string()]);
@@ -751,8 +1063,15 @@ impl Stats {
         } else if let Some(ref v) = self.online {
             if self.typ == TFloat || self.typ == TInteger {
                 pieces.push(util::round_num(v.mean(), round_places));
-                pieces.push(util::round_num(v.stddev(), round_places));
-                pieces.push(util::round_num(v.variance(), round_places));
+                if let Some(ref naive) = self.naive {
+                    // the naive variance can even go negative on ill-conditioned data
+                    let variance = naive.variance();
+                    pieces.push(util::round_num(variance.max(0.0).sqrt(), round_places));
+                    pieces.push(util::round_num(variance, round_places));
+                } else {
+                    pieces.push(util::round_num(v.stddev(), round_places));
+                    pieces.push(util::round_num(v.variance(), round_places));
+                }
             } else {
                 pieces.push(timestamp_ms_to_rfc3339(v.mean() as i64, typ));
                 // instead of returning stdev in seconds, let's return it in
@@ -781,12 +1100,8 @@ impl Stats {
         pieces.push(buffer.format(self.nullcount).to_owned());
 
         // sparsity
-        // stats is also called by the `schema` and `tojsonl` commands to infer a schema,
-        // sparsity is not required by those cmds and we don't necessarily have the
-        // record_count when called by those cmds, so just set sparsity to nullcount
-        // (div by 1) so we don't panic.
         #[allow(clippy::cast_precision_loss)]
-        let sparsity: f64 = self.nullcount as f64 / *RECORD_COUNT.get().unwrap_or(&1) as f64;
+        let sparsity: f64 = self.nullcount as f64 / record_count as f64;
         pieces.push(util::round_num(sparsity, round_places));
 
         // median
@@ -977,6 +1292,17 @@ impl Stats {
                 }
             }
         }
+
+        // entropy
+        if self.which.entropy {
+            if let Some((entropy, normalized_entropy)) = self.entropy.as_ref().and_then(entropy) {
+                pieces.push(util::round_num(entropy, round_places));
+                pieces.push(util::round_num(normalized_entropy, round_places));
+            } else {
+                pieces.push(empty());
+                pieces.push(empty());
+            }
+        }
         csv::StringRecord::from(pieces)
     }
 }
@@ -988,10 +1314,20 @@ impl Commute for Stats {
         self.sum.merge(other.sum);
         self.minmax.merge(other.minmax);
         self.online.merge(other.online);
+        self.naive.merge(other.naive);
         self.nullcount += other.nullcount;
         self.modes.merge(other.modes);
         self.median.merge(other.median);
         self.quartiles.merge(other.quartiles);
+        // if one of the chunks went over --entropy-max-cardinality, so does the merge
+        if let (Some(mut counts), Some(other_counts)) = (self.entropy.take(), other.entropy) {
+            for (value, count) in other_counts {
+                *counts.entry(value).or_insert(0) += count;
+            }
+            if counts.len() <= self.which.entropy_max_cardinality {
+                self.entropy = Some(counts);
+            }
+        }
         self.which.merge(other.which);
     }
 }
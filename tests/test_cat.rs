@@ -68,6 +68,47 @@ fn cat_rows_headers() {
     assert_eq!(got, expected);
 }
 
+#[test]
+fn cat_rows_skip_repeated_headers() {
+    let rows1 = vec![svec!["h1", "h2"], svec!["a", "b"], svec!["h1", "h2"]];
+    let rows2 = vec![svec!["h1", "h2"], svec![" h1", "h2 "], svec!["y", "z"]];
+
+    let expected = vec![svec!["h1", "h2"], svec!["a", "b"], svec!["y", "z"]];
+    let got: Vec<Vec<String>> = run_cat(
+        "cat_rows_skip_repeated_headers",
+        "rows",
+        rows1,
+        rows2,
+        |cmd| {
+            cmd.arg("--skip-repeated-headers");
+        },
+    );
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn cat_rows_skip_repeated_headers_no_headers() {
+    let rows1 = vec![svec!["h1", "h2"], svec!["a", "b"]];
+    let rows2 = vec![svec!["h1", "h2"], svec!["h1", "h3"], svec!["y", "z"]];
+
+    let expected = vec![
+        svec!["h1", "h2"],
+        svec!["a", "b"],
+        svec!["h1", "h3"],
+        svec!["y", "z"],
+    ];
+    let got: Vec<Vec<String>> = run_cat(
+        "cat_rows_skip_repeated_headers_no_headers",
+        "rows",
+        rows1,
+        rows2,
+        |cmd| {
+            cmd.args(["--skip-repeated-headers", "--no-headers"]);
+        },
+    );
+    assert_eq!(got, expected);
+}
+
 #[test]
 fn prop_cat_cols() {
     fn p(rows1: CsvData, rows2: CsvData) -> TestResult {
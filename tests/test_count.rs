@@ -138,3 +138,47 @@ fn count_width() {
     let expected = "2;9";
     assert_eq!(got, expected.to_string());
 }
+
+#[test]
+fn count_duplicates() {
+    let wrk = Workdir::new("count_duplicates");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["letter", "number"],
+            svec!["alpha", "13"],
+            svec!["beta", "24"],
+            svec!["alpha", "13"],
+            svec!["alpha", "24"],
+            svec!["alpha", "13"],
+        ],
+    );
+    let mut cmd = wrk.command("count");
+    cmd.arg("--duplicates").arg("in.csv");
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "5;3;2";
+    assert_eq!(got, expected.to_string());
+}
+
+#[test]
+fn count_duplicates_keys() {
+    let wrk = Workdir::new("count_duplicates_keys");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["letter", "number"],
+            svec!["alpha", "13"],
+            svec!["beta", "24"],
+            svec!["alpha", "13"],
+            svec!["alpha", "24"],
+            svec!["alpha", "13"],
+        ],
+    );
+    let mut cmd = wrk.command("count");
+    cmd.args(["--duplicates", "--keys", "letter"]).arg("in.csv");
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "5;2;3";
+    assert_eq!(got, expected.to_string());
+}
@@ -233,3 +233,103 @@ fn dedup_not_sorted2() {
     let got: String = wrk.output_stderr(&mut cmd);
     assert!(got.contains("Aborting! Input not sorted!"));
 }
+
+#[test]
+fn dedup_fuzzy() {
+    let wrk = Workdir::new("dedup_fuzzy");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["name", "city"],
+            svec!["Acme Corp.", "NYC"],
+            svec!["ACME corp", "nyc"],
+            svec!["Beta, Inc", "LA"],
+            svec!["acme-corp", "N.Y.C."],
+            svec!["beta inc", "la"],
+            svec!["Gamma", "SF"],
+        ],
+    );
+
+    let mut cmd = wrk.command("dedup");
+    cmd.arg("--fuzzy").args(["--select", "name"]).arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "city"],
+        svec!["Acme Corp.", "NYC"],
+        svec!["Beta, Inc", "LA"],
+        svec!["Gamma", "SF"],
+    ];
+    assert_eq!(got, expected);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(got_err, "3\n2\n");
+}
+
+#[test]
+fn dedup_fuzzy_sorted_error() {
+    let wrk = Workdir::new("dedup_fuzzy_sorted_error");
+    wrk.create("in.csv", vec![svec!["N", "S"], svec!["10", "a"]]);
+
+    let mut cmd = wrk.command("dedup");
+    cmd.arg("--fuzzy").arg("--sorted").arg("in.csv");
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn dedup_count() {
+    let wrk = Workdir::new("dedup_count");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["N", "S"],
+            svec!["10", "a"],
+            svec!["2", "b"],
+            svec!["10", "c"],
+            svec!["10", "d"],
+            svec!["3", "e"],
+        ],
+    );
+
+    let mut cmd = wrk.command("dedup");
+    cmd.arg("--count").args(["--select", "N"]).arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["N", "S", "count"],
+        svec!["10", "a", "3"],
+        svec!["2", "b", "1"],
+        svec!["3", "e", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn dedup_count_sorted_column() {
+    let wrk = Workdir::new("dedup_count_sorted_column");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["N", "S"],
+            svec!["1", "a"],
+            svec!["1", "b"],
+            svec!["2", "c"],
+        ],
+    );
+
+    let mut cmd = wrk.command("dedup");
+    cmd.arg("--count")
+        .args(["--count-column", "occurrences"])
+        .arg("--sorted")
+        .args(["--select", "N"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["N", "S", "occurrences"],
+        svec!["1", "a", "2"],
+        svec!["2", "c", "1"],
+    ];
+    assert_eq!(got, expected);
+}
@@ -143,6 +143,37 @@ fn excel_date_xlsx() {
     assert_eq!(got, expected);
 }
 
+#[test]
+fn excel_datetime_cells() {
+    let wrk = Workdir::new("excel_datetime_cells");
+
+    let xlsx_file = wrk.load_test_file("excel-datetime-cells.xlsx");
+
+    // date-formatted cells are dates whatever the --dates-whitelist, while the
+    // plain numbers of the columns that are not whitelisted stay serials
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--dates-whitelist", "none"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["formatted", "plain"],
+        svec!["2021-01-01", "44197"],
+        svec!["2021-01-01 12:00:00", "44197.5"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--dates-whitelist", "plain"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["formatted", "plain"],
+        svec!["2021-01-01", "2021-01-01"],
+        svec!["2021-01-01 12:00:00", "2021-01-01 12:00:00"],
+    ];
+    assert_eq!(got, expected);
+}
+
 #[test]
 fn excel_date_whitelist_xls() {
     let wrk = Workdir::new("excel_date_whitelist_xls");
@@ -345,8 +376,32 @@ fn excel_invalid_sheet_neg_index() {
     cmd.arg("--sheet").arg("-100").arg(xls_file);
 
     let got = wrk.output_stderr(&mut cmd);
-    let expected = "5 2-column rows exported from \"Last\" sheet\n".to_string();
+    let expected = "sheet index -100 is out of range of the 8 sheets\n".to_string();
     assert_eq!(got, expected);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_neg_sheet_index() {
+    let wrk = Workdir::new("excel_neg_sheet_index");
+
+    let xlsx_file = wrk.load_test_file("excel-four-sheets.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "-1"]).arg(&xlsx_file);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, vec![svec!["sheet"], svec!["fourth"]]);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "-4"]).arg(&xlsx_file);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, vec![svec!["sheet"], svec!["first"]]);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "-5"]).arg(&xlsx_file);
+    let got = wrk.output_stderr(&mut cmd);
+    assert_eq!(got, "sheet index -5 is out of range of the 4 sheets\n");
+    wrk.assert_err(&mut cmd);
 }
 
 #[test]
@@ -433,7 +488,8 @@ fn excel_metadata() {
             "safe_headers_count",
             "unsafe_headers",
             "unsafe_headers_count",
-            "duplicate_headers_count"
+            "duplicate_headers_count",
+            "hidden"
         ],
         svec![
             "0",
@@ -445,7 +501,8 @@ fn excel_metadata() {
             "2",
             "[]",
             "0",
-            "0"
+            "0",
+            "false"
         ],
         svec![
             "1",
@@ -457,7 +514,8 @@ fn excel_metadata() {
             "2",
             "[\"\"]",
             "1",
-            "0"
+            "0",
+            "false"
         ],
         svec![
             "2",
@@ -469,9 +527,10 @@ fn excel_metadata() {
             "2",
             "[]",
             "0",
-            "0"
+            "0",
+            "false"
         ],
-        svec!["3", "Sheet1", "[]", "0", "0", "[]", "0", "[]", "0", "0"],
+        svec!["3", "Sheet1", "[]", "0", "0", "[]", "0", "[]", "0", "0", "false"],
         svec![
             "4",
             "trim test",
@@ -482,7 +541,8 @@ fn excel_metadata() {
             "2",
             "[\"   col2\"]",
             "1",
-            "0"
+            "0",
+            "false"
         ],
         svec![
             "5",
@@ -494,7 +554,8 @@ fn excel_metadata() {
             "4",
             "[]",
             "0",
-            "0"
+            "0",
+            "false"
         ],
         svec![
             "6",
@@ -506,7 +567,8 @@ fn excel_metadata() {
             "4",
             "[]",
             "0",
-            "0"
+            "0",
+            "false"
         ],
         svec![
             "7",
@@ -518,7 +580,8 @@ fn excel_metadata() {
             "2",
             "[]",
             "0",
-            "0"
+            "0",
+            "false"
         ],
     ];
     assert_eq!(got, expected);
@@ -556,7 +619,8 @@ fn excel_metadata_pretty_json() {
       "safe_headers_count": 2,
       "unsafe_headers": [],
       "unsafe_headers_count": 0,
-      "duplicate_headers_count": 0
+      "duplicate_headers_count": 0,
+      "hidden": false
     },
     {
       "index": 1,
@@ -577,7 +641,8 @@ fn excel_metadata_pretty_json() {
         ""
       ],
       "unsafe_headers_count": 1,
-      "duplicate_headers_count": 0
+      "duplicate_headers_count": 0,
+      "hidden": false
     },
     {
       "index": 2,
@@ -595,7 +660,8 @@ fn excel_metadata_pretty_json() {
       "safe_headers_count": 2,
       "unsafe_headers": [],
       "unsafe_headers_count": 0,
-      "duplicate_headers_count": 0
+      "duplicate_headers_count": 0,
+      "hidden": false
     },
     {
       "index": 3,
@@ -607,7 +673,8 @@ fn excel_metadata_pretty_json() {
       "safe_headers_count": 0,
       "unsafe_headers": [],
       "unsafe_headers_count": 0,
-      "duplicate_headers_count": 0
+      "duplicate_headers_count": 0,
+      "hidden": false
     },
     {
       "index": 4,
@@ -628,7 +695,8 @@ fn excel_metadata_pretty_json() {
         "   col2"
       ],
       "unsafe_headers_count": 1,
-      "duplicate_headers_count": 0
+      "duplicate_headers_count": 0,
+      "hidden": false
     },
     {
       "index": 5,
@@ -650,7 +718,8 @@ fn excel_metadata_pretty_json() {
       "safe_headers_count": 4,
       "unsafe_headers": [],
       "unsafe_headers_count": 0,
-      "duplicate_headers_count": 0
+      "duplicate_headers_count": 0,
+      "hidden": false
     },
     {
       "index": 6,
@@ -672,7 +741,8 @@ fn excel_metadata_pretty_json() {
       "safe_headers_count": 4,
       "unsafe_headers": [],
       "unsafe_headers_count": 0,
-      "duplicate_headers_count": 0
+      "duplicate_headers_count": 0,
+      "hidden": false
     },
     {
       "index": 7,
@@ -690,7 +760,8 @@ fn excel_metadata_pretty_json() {
       "safe_headers_count": 2,
       "unsafe_headers": [],
       "unsafe_headers_count": 0,
-      "duplicate_headers_count": 0
+      "duplicate_headers_count": 0,
+      "hidden": false
     }
   ]
 }"#;
@@ -698,6 +769,101 @@ fn excel_metadata_pretty_json() {
     wrk.assert_success(&mut cmd);
 }
 
+#[test]
+fn excel_metadata_hidden_sheets() {
+    let wrk = Workdir::new("excel_metadata_hidden_sheets");
+
+    let xlsx_file = wrk.load_test_file("excel-hidden-sheets.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--metadata").arg("j").arg(xlsx_file);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let metadata: serde_json::Value = serde_json::from_str(&got).unwrap();
+    let sheets = metadata["sheet"].as_array().unwrap();
+    let got_sheets: Vec<(&str, bool, u64, u64)> = sheets
+        .iter()
+        .map(|sheet| {
+            (
+                sheet["name"].as_str().unwrap(),
+                sheet["hidden"].as_bool().unwrap(),
+                sheet["num_rows"].as_u64().unwrap(),
+                sheet["num_columns"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    let expected = vec![
+        ("data", false, 3, 2),
+        ("lookup", true, 2, 2),
+        ("internal", true, 3, 1),
+    ];
+    assert_eq!(got_sheets, expected);
+}
+
+#[test]
+fn excel_list_sheets() {
+    let wrk = Workdir::new("excel_list_sheets");
+
+    let xlsx_file = wrk.load_test_file("excel-hidden-sheets.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--list-sheets").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["index", "sheet_name", "rows", "columns", "hidden"],
+        svec!["0", "data", "3", "2", "false"],
+        svec!["1", "lookup", "2", "2", "true"],
+        svec!["2", "internal", "3", "1", "true"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_list_sheets_json() {
+    let wrk = Workdir::new("excel_list_sheets_json");
+
+    let xlsx_file = wrk.load_test_file("excel-hidden-sheets.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--list-sheets").arg("--json").arg(&xlsx_file);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = r#"[{"index":0,"name":"data","hidden":false,"rows":3,"cols":2},{"index":1,"name":"lookup","hidden":true,"rows":2,"cols":2},{"index":2,"name":"internal","hidden":true,"rows":3,"cols":1}]"#;
+    assert_eq!(got, expected);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--json").arg(&xlsx_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert_eq!(got, "--json can only be used with --list-sheets.\n");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_no_sheets() {
+    let wrk = Workdir::new("excel_no_sheets");
+
+    let xlsx_file = wrk.load_test_file("excel-no-sheets.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&xlsx_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert_eq!(got, "Workbook contains no sheets.\n");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--list-sheets").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(
+        got,
+        vec![svec!["index", "sheet_name", "rows", "columns", "hidden"]]
+    );
+}
+
 #[test]
 fn excel_message() {
     let wrk = Workdir::new("excel_message");
@@ -736,3 +902,2787 @@ fn excel_empty_sheet2_message() {
     let got = wrk.output_stderr(&mut cmd);
     assert_eq!(got, "0 0-column rows exported from \"Sheet1\" sheet\n");
 }
+
+#[test]
+fn excel_detect_header_row() {
+    let wrk = Workdir::new("excel_detect_header_row");
+
+    let xlsx_file = wrk.load_test_file("excel-preamble.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--detect-header-row").arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "product", "amount"],
+        svec!["east", "widget", "10"],
+        svec!["west", "gadget", "20.5"],
+        svec!["north", "gizmo", "30"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_header_row() {
+    let wrk = Workdir::new("excel_header_row");
+
+    let xlsx_file = wrk.load_test_file("excel-preamble.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--header-row", "3"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "product", "amount"],
+        svec!["east", "widget", "10"],
+        svec!["west", "gadget", "20.5"],
+        svec!["north", "gizmo", "30"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--header-row", "7"]).arg(&xlsx_file);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_header_case() {
+    let wrk = Workdir::new("excel_header_case");
+
+    let xlsx_file = wrk.load_test_file("excel-header-case.xlsx");
+
+    for (header_case, expected_headers) in [
+        ("lower", svec!["first name", "lastname", "zip-code  #"]),
+        ("upper", svec!["FIRST NAME", "LASTNAME", "ZIP-CODE  #"]),
+        ("snake", svec!["first_name", "last_name", "zip_code"]),
+        ("camel", svec!["firstName", "lastName", "zipCode"]),
+        ("none", svec!["First Name", "lastName", "Zip-Code  #"]),
+    ] {
+        let mut cmd = wrk.command("excel");
+        cmd.args(["--header-case", header_case]).arg(&xlsx_file);
+
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = vec![expected_headers, svec!["Jane", "Doe", "10001"]];
+        assert_eq!(got, expected);
+    }
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--header-case", "kebab"]).arg(&xlsx_file);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_date_format() {
+    let wrk = Workdir::new("excel_date_format");
+
+    let xls_file = wrk.load_test_file("excel-xls.xls");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "date test"])
+        .args(["--date-format", "%m/%d/%Y %H:%M"])
+        .arg(&xls_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got[1][0], "12/25/2001 00:00");
+    assert_eq!(got[2][0], "09/11/2001 08:30");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "date test"])
+        .args(["--date-format", "%Y-%m-%d %Q"])
+        .arg(&xls_file);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_date_system_1904() {
+    let wrk = Workdir::new("excel_date_system_1904");
+
+    let xlsx_file = wrk.load_test_file("excel-1904.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["event", "when"],
+        svec!["epoch", "1904-01-01"],
+        svec!["new year", "2022-01-01"],
+        svec!["noon", "2022-01-01 12:00:00"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--date-system", "1900"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got[2], svec!["new year", "2017-12-31"]);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--date-system", "1901"]).arg(&xlsx_file);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_output_precision() {
+    let wrk = Workdir::new("excel_output_precision");
+
+    let xlsx_file = wrk.load_test_file("excel-xlsx.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--output-precision", "2"]).arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let got_numbers: Vec<String> = got.iter().map(|row| row[2].clone()).collect();
+    let expected = svec!["number", "42", "3.14", "3.14", "1.50", "20.02", "37", "14.23", "14.20"];
+    assert_eq!(got_numbers, expected);
+    // date columns are not affected
+    assert_eq!(got[1][3], "2001-09-11 08:30:00");
+}
+
+#[test]
+fn excel_output_precision_trim_trailing_zeros() {
+    let wrk = Workdir::new("excel_output_precision_trim_trailing_zeros");
+
+    let xlsx_file = wrk.load_test_file("excel-xlsx.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--output-precision", "2"])
+        .arg("--trim-trailing-zeros")
+        .arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let got_numbers: Vec<String> = got.iter().map(|row| row[2].clone()).collect();
+    let expected = svec!["number", "42", "3.14", "3.14", "1.5", "20.02", "37", "14.23", "14.2"];
+    assert_eq!(got_numbers, expected);
+}
+
+#[test]
+fn excel_dimension_used() {
+    let wrk = Workdir::new("excel_dimension_used");
+
+    let xlsx_file = wrk.load_test_file("excel-dimension.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "", ""],
+        svec!["1", "a", "", ""],
+        svec!["2", "b", "", ""],
+        svec!["", "", "", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_dimension_full() {
+    let wrk = Workdir::new("excel_dimension_full");
+
+    let xlsx_file = wrk.load_test_file("excel-dimension.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--dimension", "full"]).arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["", "", "", "", ""],
+        svec!["", "id", "name", "", ""],
+        svec!["", "1", "a", "", ""],
+        svec!["", "2", "b", "", ""],
+        svec!["", "", "", "", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_dimension_auto() {
+    let wrk = Workdir::new("excel_dimension_auto");
+
+    let xlsx_file = wrk.load_test_file("excel-dimension.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--dimension", "auto"]).arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "name"], svec!["1", "a"], svec!["2", "b"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_large_reshaped() {
+    let wrk = Workdir::new("excel_large_reshaped");
+
+    // the range is resized & transposed in turn, moving its cells to each reshaped
+    // range. This only checks the reshaped output of a large sheet, not the memory use
+    let xlsx_file = wrk.load_test_file("excel-large.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--dimension", "auto"])
+        .args(["--range", "A1:C5001"])
+        .arg("--transpose")
+        .arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got.len(), 3);
+    assert!(got.iter().all(|row| row.len() == 5001));
+    assert_eq!(got[0][..3], svec!["id", "1", "2"]);
+    assert_eq!(got[1][5000], "name5000");
+    assert_eq!(got[2][..3], svec!["amount", "1.5", "3"]);
+    assert_eq!(got[2][5000], "7500");
+}
+
+#[test]
+fn excel_merge_fill() {
+    let wrk = Workdir::new("excel_merge_fill");
+
+    let xlsx_file = wrk.load_test_file("excel-merged-cells.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["category", "item", "price"],
+        svec!["fruit", "apple", "1"],
+        svec!["", "pear", "2"],
+        svec!["veg", "leek", "3"],
+        svec!["", "kale", "4"],
+        svec!["total", "", "10"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--merge-fill").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["category", "item", "price"],
+        svec!["fruit", "apple", "1"],
+        svec!["fruit", "pear", "2"],
+        svec!["veg", "leek", "3"],
+        svec!["veg", "kale", "4"],
+        svec!["total", "total", "10"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_pad_rows() {
+    let wrk = Workdir::new("excel_pad_rows");
+
+    let xlsx_file = wrk.load_test_file("excel-ragged.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "wide3,narrow,wide4"])
+        .arg("--flexible")
+        .arg("--pad-rows")
+        .args(["--empty-token", "NA"])
+        .arg(xlsx_file);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "id,name,city\n1,ann,Oslo\n2,bob,NA\n3,cy,Rome";
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_hyperlinks() {
+    let wrk = Workdir::new("excel_hyperlinks");
+
+    let xlsx_file = wrk.load_test_file("excel-hyperlinks.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["site", "note"],
+        svec!["qsv", "csv toolkit"],
+        svec!["docs", "no link"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--hyperlinks", "url"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["site", "note"],
+        svec!["https://github.com/jqnatividad/qsv", "csv toolkit"],
+        svec!["docs", "no link"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--hyperlinks", "both"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["site", "note"],
+        svec!["qsv (https://github.com/jqnatividad/qsv)", "csv toolkit"],
+        svec!["docs", "no link"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_comments_column() {
+    let wrk = Workdir::new("excel_comments_column");
+
+    let xlsx_file = wrk.load_test_file("excel-comments.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--comments-column", "comments"]).arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "amount", "status", "comments"],
+        svec!["1", "100", "ok", ""],
+        svec!["2", "-5", "ok", "B3: looks wrong; C3: refund?"],
+        svec!["3", "7", "late", "C4: check the date"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_comments_column_unreadable() {
+    let wrk = Workdir::new("excel_comments_column_unreadable");
+
+    // the comments part has a mismatched end tag
+    let xlsx_file = wrk.load_test_file("excel-bad-comments.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--comments-column", "comments"]).arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "amount", "status", "comments"],
+        svec!["1", "100", "ok", ""],
+        svec!["2", "-5", "ok", ""],
+        svec!["3", "7", "late", ""],
+    ];
+    assert_eq!(got, expected);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert!(got_err.contains(r#"cannot read the "review" sheet details used by --comments-column"#));
+}
+
+#[test]
+fn excel_xlsx_only_flags_ignored() {
+    let wrk = Workdir::new("excel_xlsx_only_flags_ignored");
+
+    let xls_file = wrk.load_test_file("excel-xls.xls");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--merge-fill")
+        .args(["--hyperlinks", "url"])
+        .args(["--comments-column", "comments"])
+        .arg(xls_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert_eq!(got.matches("only work with xlsx/xlsm workbooks").count(), 1);
+    assert!(got.contains(
+        "--hyperlinks, --merge-fill, --comments-column only work with xlsx/xlsm workbooks, so \
+         they are ignored for the xls workbook excel-xls.xls."
+    ));
+}
+
+#[test]
+fn excel_properties() {
+    let wrk = Workdir::new("excel_properties");
+
+    let xlsx_file = wrk.load_test_file("excel-properties.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--properties").arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["property", "value"],
+        svec!["format", "xlsx"],
+        svec!["sheet_count", "2"],
+        svec!["date_system", "1904"],
+        svec!["title", "Q1 sales"],
+        svec!["creator", "Ann Lee"],
+        svec!["lastModifiedBy", "Bob Ray"],
+        svec!["created", "2023-01-05T09:30:00Z"],
+        svec!["modified", "2023-03-31T17:00:00Z"],
+        svec!["Application", "Microsoft Excel"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_properties_list_sheets() {
+    let wrk = Workdir::new("excel_properties_list_sheets");
+
+    let xlsx_file = wrk.load_test_file("excel-properties.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--properties").arg("--list-sheets").arg(xlsx_file);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_percent_format() {
+    let wrk = Workdir::new("excel_percent_format");
+
+    let xlsx_file = wrk.load_test_file("excel-percent.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["item", "share", "rate", "count"],
+        svec!["a", "0.25", "0.0725", "3"],
+        svec!["b", "0.07", "0.125", "4"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--percent-format", "times100"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["item", "share", "rate", "count"],
+        svec!["a", "25", "7.25", "3"],
+        svec!["b", "7", "12.5", "4"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--percent-format", "sign"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["item", "share", "rate", "count"],
+        svec!["a", "25%", "7.25%", "3"],
+        svec!["b", "7%", "12.5%", "4"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_dump_types() {
+    let wrk = Workdir::new("excel_dump_types");
+
+    let xlsx_file = wrk.load_test_file("excel-dump-types.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--dump-types").arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "column_name",
+            "ints",
+            "floats",
+            "strings",
+            "dates",
+            "bools",
+            "errors",
+            "empties"
+        ],
+        svec!["id", "0", "3", "0", "0", "0", "0", "0"],
+        svec!["amount", "0", "1", "1", "0", "0", "0", "1"],
+        svec!["when", "0", "0", "0", "2", "0", "1", "0"],
+        svec!["ok", "0", "0", "0", "0", "2", "0", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_durations_and_times() {
+    let wrk = Workdir::new("excel_durations_and_times");
+
+    let xlsx_file = wrk.load_test_file("excel-durations.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--elapsed-time").arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["day", "start", "worked"],
+        svec!["2023-01-01", "08:30:00", "36:00:00"],
+        svec!["2023-01-02", "12:30:00", "07:30:00"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_timezones() {
+    let wrk = Workdir::new("excel_timezones");
+
+    let xlsx_file = wrk.load_test_file("excel-timezones.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--assume-timezone", "America/New_York"])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["event", "at"],
+        svec!["attack", "2001-09-11 08:30:00-04:00"],
+        svec!["meeting", "2023-01-15 12:00:00-05:00"],
+        svec!["birthday", "1980-12-25"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--assume-timezone", "America/New_York"])
+        .args(["--output-timezone", "UTC"])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["event", "at"],
+        svec!["attack", "2001-09-11 12:30:00+00:00"],
+        svec!["meeting", "2023-01-15 17:00:00+00:00"],
+        svec!["birthday", "1980-12-25"],
+    ];
+    assert_eq!(got, expected);
+
+    // without --assume-timezone, the datetimes are in UTC
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--output-timezone", "Asia/Tokyo"])
+        .args(["--date-format", "%Y-%m-%d %H:%M %Z"])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["event", "at"],
+        svec!["attack", "2001-09-11 17:30 JST"],
+        svec!["meeting", "2023-01-15 21:00 JST"],
+        svec!["birthday", "1980-12-25 00:00 UTC"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_invalid_timezone() {
+    let wrk = Workdir::new("excel_invalid_timezone");
+
+    let xlsx_file = wrk.load_test_file("excel-timezones.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--assume-timezone", "Mars/Olympus_Mons"])
+        .arg(xlsx_file);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_trim_columns() {
+    let wrk = Workdir::new("excel_trim_columns");
+
+    let xlsx_file = wrk.load_test_file("excel-trim-columns.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--trim-columns", "notes"])
+        .args(["--empty-token", "NULL"])
+        .arg(xlsx_file);
+
+    // the code column keeps its whitespace, but the header row is trimmed
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["code", "notes"],
+        svec!["  A1 ", "first line"],
+        svec![" B2", "NULL"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_large_integers() {
+    let wrk = Workdir::new("excel_large_integers");
+
+    let xlsx_file = wrk.load_test_file("excel-large-ints.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "id"],
+        svec!["ann", "123456789012345"],
+        svec!["bob", "1000000000000000"],
+        // 12345678901234567 is beyond what Excel stores exactly
+        svec!["cy", "12345678901234568"],
+    ];
+    assert_eq!(got, expected);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert!(got_err
+        .starts_with("WARNING: 1 numeric cells are integers larger than 9,007,199,254,740,992"));
+}
+
+#[test]
+fn excel_count_only() {
+    let wrk = Workdir::new("excel_count_only");
+
+    let xlsx_file = wrk.load_test_file("excel-skip-empty.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--count-only").arg(&xlsx_file);
+
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(got, "5");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--count-only").arg("--skip-empty").arg(&xlsx_file);
+
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(got, "3");
+}
+
+#[test]
+fn excel_count_only_all_sheets() {
+    let wrk = Workdir::new("excel_count_only_all_sheets");
+
+    let xlsx_file = wrk.load_test_file("excel-ragged.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--count-only").arg("--all-sheets").arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["sheet", "rows"],
+        svec!["wide3", "1"],
+        svec!["narrow", "1"],
+        svec!["wide4", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_rich_text() {
+    let wrk = Workdir::new("excel_rich_text");
+
+    let xlsx_file = wrk.load_test_file("excel-rich-text.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&xlsx_file);
+
+    // the formatted runs of rich text and inline strings are flattened into plain text
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "description"],
+        svec!["1", "Bold and red text"],
+        svec!["2", "inline string"],
+        svec!["3", "  spaced runs  "],
+        svec!["4", "plain"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_multiple_inputs() {
+    let wrk = Workdir::new("excel_multiple_inputs");
+
+    let jan_file = wrk.load_test_file("excel-sales-jan.xlsx");
+    let feb_file = wrk.load_test_file("excel-sales-feb.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&jan_file).arg(&feb_file);
+
+    // the header row is only written once, from the first workbook
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "amount"],
+        svec!["North", "100"],
+        svec!["South", "200"],
+        svec!["North", "150"],
+        svec!["East", "75"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--with-filename").arg(&jan_file).arg(&feb_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["source_file", "region", "amount"],
+        vec![jan_file.clone(), "North".to_string(), "100".to_string()],
+        vec![jan_file.clone(), "South".to_string(), "200".to_string()],
+        vec![feb_file.clone(), "North".to_string(), "150".to_string()],
+        vec![feb_file.clone(), "East".to_string(), "75".to_string()],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_multiple_inputs_different_headers() {
+    let wrk = Workdir::new("excel_multiple_inputs_different_headers");
+
+    let jan_file = wrk.load_test_file("excel-sales-jan.xlsx");
+    let mar_file = wrk.load_test_file("excel-sales-mar.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&jan_file).arg(&mar_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains(&format!(
+        r#"The "Sales" sheet of {mar_file} has a different header row than the first workbook."#
+    )));
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--flexible").arg(&jan_file).arg(&mar_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "amount"],
+        svec!["North", "100"],
+        svec!["South", "200"],
+        svec!["West", "50"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--all-sheets")
+        .args(["--output", "."])
+        .arg(&jan_file)
+        .arg(&mar_file);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_datetime_threshold() {
+    let wrk = Workdir::new("excel_datetime_threshold");
+
+    let xlsx_file = wrk.load_test_file("excel-date-fractions.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&xlsx_file);
+
+    // the tiny fractions of round-tripped serials are dates, not datetimes
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "date"],
+        svec!["1", "2011-07-05"],
+        svec!["2", "2011-07-06"],
+        svec!["3", "2011-07-05 12:00:00"],
+        svec!["4", "2011-07-05"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--datetime-threshold", "0.6"]).arg(&xlsx_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.starts_with("--datetime-threshold must be at least 0 and less than 0.5: 0.6"));
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_template() {
+    let wrk = Workdir::new("excel_template");
+
+    let xltx_file = wrk.load_test_file("excel-sales-template.xltx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&xltx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "amount"],
+        svec!["North", "100"],
+        svec!["South", "200"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_csv_input() {
+    let wrk = Workdir::new("excel_csv_input");
+    wrk.create(
+        "data.csv",
+        vec![svec!["region", "amount"], svec!["North", "100"]],
+    );
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("data.csv");
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.starts_with(
+        "data.csv is already a CSV file, not an Excel/ODS workbook, so it does not need to be \
+         exported."
+    ));
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_too_many_rows() {
+    let wrk = Workdir::new("excel_too_many_rows");
+
+    let xlsx_file = wrk.load_test_file("excel-too-many-rows.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--max-rows", "1"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id"], svec!["1"]];
+    assert_eq!(got, expected);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.starts_with(
+        "WARNING: the \"Sheet1\" sheet has a 1,048,580 x 1 range of cells, larger than the \
+         1,048,576 x 16,384 cells of an Excel sheet"
+    ));
+}
+
+#[test]
+fn excel_fail_on_error() {
+    let wrk = Workdir::new("excel_fail_on_error");
+
+    let xlsx_file = wrk.load_test_file("excel-errors.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--fail-on-error").arg(&xlsx_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.starts_with(r#"Error cell at C3 of the "results" sheet: #DIV/0!"#));
+    wrk.assert_err(&mut cmd);
+
+    // the sheets without error cells are exported as usual
+    let xlsx_file = wrk.load_test_file("excel-sales-jan.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--fail-on-error").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "amount"],
+        svec!["North", "100"],
+        svec!["South", "200"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_on_date_error() {
+    let wrk = Workdir::new("excel_on_date_error");
+
+    let xlsx_file = wrk.load_test_file("excel-bad-dates.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "date"],
+        svec!["1", "2011-07-05"],
+        svec!["2", "ERROR: Cannot convert 1000000000000000 to date"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--on-date-error", "passthrough"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "date"],
+        svec!["1", "2011-07-05"],
+        svec!["2", "1000000000000000"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--on-date-error", "empty"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "date"],
+        svec!["1", "2011-07-05"],
+        svec!["2", ""],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--on-date-error", "fail"]).arg(&xlsx_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.starts_with(
+        r#"Cannot convert the 1000000000000000 cell at B3 of the "Sheet1" sheet to a date."#
+    ));
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_trim_keep_newlines() {
+    let wrk = Workdir::new("excel_trim_keep_newlines");
+
+    let xlsx_file = wrk.load_test_file("excel-multiline.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--trim").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "notes"],
+        svec!["1", "line one line two"],
+        svec!["2", "single"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--trim").arg("--keep-newlines").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "notes"],
+        svec!["1", "line one\nline two"],
+        svec!["2", "single"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_coerce() {
+    let wrk = Workdir::new("excel_coerce");
+
+    let xlsx_file = wrk.load_test_file("excel-mixed.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--coerce").arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "amount", "code"],
+        svec!["1", "10", "A1"],
+        svec!["2", "20", "B2"],
+        svec!["3", "30.5", "7"],
+        svec!["4", "n/a", "C3"],
+        svec!["5", "1234", "D4"],
+    ];
+    assert_eq!(got, expected);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(
+        got_err,
+        "3 cells coerced, 1 outliers left as-is\n5 3-column rows exported from \"mixed\" sheet\n"
+    );
+}
+
+#[test]
+fn excel_no_coerce() {
+    let wrk = Workdir::new("excel_no_coerce");
+
+    let xlsx_file = wrk.load_test_file("excel-mixed.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "amount", "code"],
+        svec!["1", "10", "A1"],
+        svec!["2", "20", "B2"],
+        svec!["3", "30.5", "7"],
+        svec!["4", "n/a", "C3"],
+        svec!["5", " 1,234 ", "D4"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_formulas_with_values() {
+    let wrk = Workdir::new("excel_formulas_with_values");
+
+    let xlsx_file = wrk.load_test_file("excel-formulas.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--formulas-with-values").arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "item",
+            "qty",
+            "price",
+            "total",
+            "total_formula",
+            "note",
+            "note_formula"
+        ],
+        svec!["apple", "2", "1.5", "3", "=B2*C2", "ok", ""],
+        svec!["pear", "3", "2", "6", "=B3*C3", "X", "=UPPER(\"x\")"],
+        svec!["plum", "1", "4", "4", "", "manual", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_formulas() {
+    let wrk = Workdir::new("excel_formulas");
+
+    let xlsx_file = wrk.load_test_file("excel-formulas.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--formulas").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["item", "qty", "price", "total", "note"],
+        svec!["apple", "2", "1.5", "=B2*C2", "ok"],
+        svec!["pear", "3", "2", "=B3*C3", "=UPPER(\"x\")"],
+        svec!["plum", "1", "4", "4", "manual"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--formulas")
+        .arg("--formulas-with-values")
+        .arg(&xlsx_file);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_formulas_fallback() {
+    let wrk = Workdir::new("excel_formulas_fallback");
+
+    let xls_file = wrk.load_test_file("excel-xls.xls");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&xls_file);
+    let expected: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--formulas-with-values").arg(&xls_file);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--formulas-with-values")
+        .arg("--strict-formulas")
+        .arg(&xls_file);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_formulas_cached_values_only() {
+    let wrk = Workdir::new("excel_formulas_cached_values_only");
+
+    let xlsx_file = wrk.load_test_file("excel-formulas.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["item", "qty", "price", "total", "note"],
+        svec!["apple", "2", "1.5", "3", "ok"],
+        svec!["pear", "3", "2", "6", "X"],
+        svec!["plum", "1", "4", "4", "manual"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_strict_sheet_name() {
+    let wrk = Workdir::new("excel_strict_sheet_name");
+
+    let xls_file = wrk.load_test_file("excel-xls.xls");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--sheet")
+        .arg("nonexistent")
+        .arg("--strict-sheet")
+        .arg(xls_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.starts_with(r#"Cannot find sheet "nonexistent". Available sheets: ["#));
+    assert!(got.contains(r#""Middle""#));
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_strict_sheet_neg_index() {
+    let wrk = Workdir::new("excel_strict_sheet_neg_index");
+
+    let xls_file = wrk.load_test_file("excel-xls.xls");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--sheet")
+        .arg("-100")
+        .arg("--strict-sheet")
+        .arg(xls_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(
+        got.starts_with("sheet index -100 is out of range of the 8 sheets. Available sheets: [")
+    );
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_strict_sheet_index() {
+    let wrk = Workdir::new("excel_strict_sheet_index");
+
+    let xls_file = wrk.load_test_file("excel-xls.xls");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--sheet")
+        .arg("100")
+        .arg("--strict-sheet")
+        .arg(xls_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(
+        got.starts_with("sheet index 100 is greater than number of sheets 8. Available sheets: [")
+    );
+    assert!(got.contains(r#""Middle""#));
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_strict_sheet_found() {
+    let wrk = Workdir::new("excel_strict_sheet_found");
+
+    let xls_file = wrk.load_test_file("excel-xls.xls");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--sheet")
+        .arg("middle")
+        .arg("--strict-sheet")
+        .arg(xls_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got[0], svec!["Middle sheet col1", "Middle-2"]);
+}
+
+#[test]
+fn excel_output_sheet_placeholder() {
+    let wrk = Workdir::new("excel_output_sheet_placeholder");
+
+    let xlsx_file = wrk.load_test_file("excel-sheet-filenames.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "plain"])
+        .args(["--output", "{sheet}.csv"])
+        .arg(&xlsx_file);
+    wrk.assert_success(&mut cmd);
+
+    let got = std::fs::read_to_string(wrk.path("plain.csv")).unwrap();
+    assert_eq!(got, "x\n1\n");
+}
+
+#[test]
+fn excel_output_sheet_placeholder_sanitized() {
+    let wrk = Workdir::new("excel_output_sheet_placeholder_sanitized");
+
+    let xlsx_file = wrk.load_test_file("excel-sheet-filenames.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--output", "{sheet}.csv"]).arg(&xlsx_file);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(
+        got_err,
+        "sheet name \"Q1 <draft>|v2.\" was changed to \"Q1 _draft__v2\" in the output path\n2 \
+         2-column rows exported from \"Q1 <draft>|v2.\" sheet\n"
+    );
+
+    let got = std::fs::read_to_string(wrk.path("Q1 _draft__v2.csv")).unwrap();
+    assert_eq!(got, "id,name\n1,a\n2,b\n");
+}
+
+#[test]
+fn excel_output_sheet_placeholder_raw() {
+    let wrk = Workdir::new("excel_output_sheet_placeholder_raw");
+
+    let xlsx_file = wrk.load_test_file("excel-sheet-filenames.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--output", "{sheet}.csv"])
+        .arg("--raw-sheet-filenames")
+        .arg(&xlsx_file);
+    wrk.assert_success(&mut cmd);
+
+    let got = std::fs::read_to_string(wrk.path("Q1 <draft>|v2..csv")).unwrap();
+    assert_eq!(got, "id,name\n1,a\n2,b\n");
+}
+
+#[test]
+fn excel_date_whitelist_file_xls() {
+    let wrk = Workdir::new("excel_date_whitelist_file_xls");
+
+    let xls_file = wrk.load_test_file("excel-xls.xls");
+    wrk.create_from_string("whitelist.txt", "# the date columns\n0\n\n 2 \n");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--sheet")
+        .arg("date test")
+        .args(["--dates-whitelist", "@whitelist.txt"])
+        .arg(xls_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["date_col", "num_col", "col_Petsa", "just another col"],
+        svec!["2001-12-25", "1", "1991-07-04", "foo"],
+        svec!["2001-09-11 08:30:00", "3", "2021-01-06", "bar"],
+        svec![
+            "This is not a date and will be passed through",
+            "5",
+            "2001-09-11",
+            "was"
+        ],
+        svec!["1970-01-01", "7", "2009-01-21", "here"],
+        svec!["1989-12-31", "11", "2016-04-01", "42"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_date_whitelist_file_missing() {
+    let wrk = Workdir::new("excel_date_whitelist_file_missing");
+
+    let xls_file = wrk.load_test_file("excel-xls.xls");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--dates-whitelist", "@nonexistent.txt"])
+        .arg(xls_file);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_error_format() {
+    let wrk = Workdir::new("excel_error_format");
+
+    let xlsx_file = wrk.load_test_file("excel-errors.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--error-format", "excel"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "due date", "ratio", "lookup"],
+        svec!["1", "2023-01-01", "0.5", "a"],
+        svec!["2", "2023-01-02", "#DIV/0!", "#N/A"],
+        svec!["3", "2023-01-03", "#DIV/0!", "c"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--error-format", "none"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got[2], svec!["2", "2023-01-02", "", ""]);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--error-format", "pretty"]).arg(&xlsx_file);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_max_cell_length() {
+    let wrk = Workdir::new("excel_max_cell_length");
+
+    let xlsx_file = wrk.load_test_file("excel-long-cells.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--max-cell-length", "6"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "note"],
+        svec!["1", "short"],
+        svec!["2", "a very"],
+        svec!["3", "héllo "],
+    ];
+    assert_eq!(got, expected);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("2 cells truncated to 6 characters"));
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--max-cell-length", "5"])
+        .args(["--ellipsis", "..."])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let got_notes: Vec<String> = got.iter().map(|row| row[1].clone()).collect();
+    assert_eq!(got_notes, svec!["note", "short", "a ver...", "héllo..."]);
+}
+
+#[test]
+fn excel_progressbar() {
+    let wrk = Workdir::new("excel_progressbar");
+
+    let xlsx_file = wrk.load_test_file("excel-long-cells.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--progressbar").arg(&xlsx_file);
+
+    // the progress bar is sent to stderr, leaving the exported CSV as-is
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "note"],
+        svec!["1", "short"],
+        svec!["2", "a very long note indeed"],
+        svec!["3", "héllo wörld"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_summary_json() {
+    let wrk = Workdir::new("excel_summary_json");
+
+    let xlsx_file = wrk.load_test_file("excel-errors.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--summary-json", "summary.json"])
+        .arg("--quiet")
+        .arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "due date", "ratio", "lookup"],
+        svec!["1", "2023-01-01", "0.5", "a"],
+        svec!["2", "2023-01-02", "Div0", "NA"],
+        svec!["3", "2023-01-03", "Div0", "c"],
+    ];
+    assert_eq!(got, expected);
+
+    let summary_json = std::fs::read_to_string(wrk.path("summary.json")).unwrap();
+    let got: serde_json::Value = serde_json::from_str(&summary_json).unwrap();
+    let expected = serde_json::json!({
+        "filename": "excel-errors.xlsx",
+        "format": "xlsx",
+        "sheet": "results",
+        "sheet_index": 0,
+        "output": null,
+        "rows": 3,
+        "columns": 4,
+        "date_columns": ["due date"],
+        "error_cells": 3,
+        "error_cell_counts": {"Div0": 2, "NA": 1},
+    });
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_stats_json() {
+    let wrk = Workdir::new("excel_stats_json");
+
+    let jan_file = wrk.load_test_file("excel-sales-jan.xlsx");
+    let feb_file = wrk.load_test_file("excel-sales-feb.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--stats-json")
+        .arg("--quiet")
+        .arg(&jan_file)
+        .arg(&feb_file);
+
+    // one JSON object per exported sheet, instead of the summary messages
+    let got = wrk.output_stderr(&mut cmd);
+    let got: Vec<serde_json::Value> = got
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let expected = vec![
+        serde_json::json!({
+            "filename": "excel-sales-jan.xlsx",
+            "format": "xlsx",
+            "sheet": "Sales",
+            "sheet_index": 0,
+            "output": null,
+            "rows": 2,
+            "columns": 2,
+            "date_columns": [],
+            "error_cells": 0,
+            "error_cell_counts": {},
+        }),
+        serde_json::json!({
+            "filename": "excel-sales-feb.xlsx",
+            "format": "xlsx",
+            "sheet": "Sales",
+            "sheet_index": 0,
+            "output": null,
+            "rows": 2,
+            "columns": 2,
+            "date_columns": [],
+            "error_cells": 0,
+            "error_cell_counts": {},
+        }),
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_schema() {
+    let wrk = Workdir::new("excel_schema");
+
+    let xlsx_file = wrk.load_test_file("excel-schema.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--schema", "schema.json"]).arg(xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "price", "due_date", "active", "notes", "blank"],
+        svec!["1", "9.5", "2023-01-01", "true", "ok", ""],
+        svec!["2", "10", "2023-01-02", "false", "3", ""],
+        svec!["3", "12.25", "2023-01-03", "true", "", ""],
+    ];
+    assert_eq!(got, expected);
+
+    let schema_json = std::fs::read_to_string(wrk.path("schema.json")).unwrap();
+    let got: serde_json::Value = serde_json::from_str(&schema_json).unwrap();
+    let expected = serde_json::json!({
+        "sheet": "Products",
+        "columns": [
+            {"name": "id", "type": "int", "date": false},
+            {"name": "price", "type": "float", "date": false},
+            {"name": "due_date", "type": "date", "date": true},
+            {"name": "active", "type": "bool", "date": false},
+            {"name": "notes", "type": "string", "date": false},
+            {"name": "blank", "type": "string", "date": false},
+        ],
+    });
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_bool_format() {
+    let wrk = Workdir::new("excel_bool_format");
+
+    let xlsx_file = wrk.load_test_file("excel-bools.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "active", "verified"],
+        svec!["ann", "true", "false"],
+        svec!["bob", "false", ""],
+        svec!["cy", "true", "true"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--bool-format", "Y/N"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "active", "verified"],
+        svec!["ann", "Y", "N"],
+        svec!["bob", "N", ""],
+        svec!["cy", "Y", "Y"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_bool_format_presets() {
+    let wrk = Workdir::new("excel_bool_format_presets");
+
+    let xlsx_file = wrk.load_test_file("excel-bools.xlsx");
+
+    for (preset, true_str, false_str) in [
+        ("lower", "true", "false"),
+        ("upper", "TRUE", "FALSE"),
+        ("tf", "T", "F"),
+        ("10", "1", "0"),
+    ] {
+        let mut cmd = wrk.command("excel");
+        cmd.args(["--bool-format", preset]).arg(&xlsx_file);
+
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = vec![
+            svec!["name", "active", "verified"],
+            svec!["ann", true_str, false_str],
+            svec!["bob", false_str, ""],
+            svec!["cy", true_str, true_str],
+        ];
+        assert_eq!(got, expected, "--bool-format {preset}");
+    }
+}
+
+#[test]
+fn excel_bool_format_invalid() {
+    let wrk = Workdir::new("excel_bool_format_invalid");
+
+    let xlsx_file = wrk.load_test_file("excel-bools.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--bool-format", "yes"]).arg(xlsx_file);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_row_filter() {
+    let wrk = Workdir::new("excel_row_filter");
+
+    let xlsx_file = wrk.load_test_file("excel-bools.xlsx");
+
+    for (row_filter, names) in [
+        ("active==true", vec!["ann", "cy"]),
+        ("active!=true", vec!["bob"]),
+        ("name~=o", vec!["bob"]),
+        ("verified", vec!["ann", "cy"]),
+    ] {
+        let mut cmd = wrk.command("excel");
+        cmd.args(["--row-filter", row_filter])
+            .args(["--bool-format", "true/false"])
+            .arg(&xlsx_file);
+
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let got_names: Vec<&str> = got.iter().skip(1).map(|row| row[0].as_str()).collect();
+        assert_eq!(got[0], svec!["name", "active", "verified"]);
+        assert_eq!(got_names, names, "--row-filter {row_filter}");
+    }
+}
+
+#[test]
+fn excel_row_filter_unknown_column() {
+    let wrk = Workdir::new("excel_row_filter_unknown_column");
+
+    let xlsx_file = wrk.load_test_file("excel-bools.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--row-filter", "status==active"]).arg(xlsx_file);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_keep_serial() {
+    let wrk = Workdir::new("excel_keep_serial");
+
+    let xls_file = wrk.load_test_file("excel-xls.xls");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--sheet")
+        .arg("date test")
+        .args(["--dates-whitelist", "0,2"])
+        .args(["--keep-serial", "serial"])
+        .arg(xls_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "date_col",
+            "date_col_serial",
+            "num_col",
+            "col_Petsa",
+            "col_Petsa_serial",
+            "just another col"
+        ],
+        svec!["2001-12-25", "37250", "1", "1991-07-04", "33423", "foo"],
+        svec![
+            "2001-09-11 08:30:00",
+            "37145.354166666664",
+            "3",
+            "2021-01-06",
+            "44202",
+            "bar"
+        ],
+        svec![
+            "This is not a date and will be passed through",
+            "",
+            "5",
+            "2001-09-11",
+            "37145",
+            "was"
+        ],
+        svec!["1970-01-01", "25569", "7", "2009-01-21", "39834", "here"],
+        svec!["1989-12-31", "32873", "11", "2016-04-01", "42461", "42"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_keep_serial_date() {
+    let wrk = Workdir::new("excel_keep_serial_date");
+
+    let xls_file = wrk.load_test_file("excel-xls.xls");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--sheet")
+        .arg("date test")
+        .args(["--dates-whitelist", "0,2"])
+        .args(["--keep-serial", "date"])
+        .arg(xls_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "date_col",
+            "date_col_date",
+            "num_col",
+            "col_Petsa",
+            "col_Petsa_date",
+            "just another col"
+        ],
+        svec!["37250", "2001-12-25", "1", "33423", "1991-07-04", "foo"],
+        svec![
+            "37145.354166666664",
+            "2001-09-11 08:30:00",
+            "3",
+            "44202",
+            "2021-01-06",
+            "bar"
+        ],
+        svec![
+            "",
+            "This is not a date and will be passed through",
+            "5",
+            "37145",
+            "2001-09-11",
+            "was"
+        ],
+        svec!["25569", "1970-01-01", "7", "39834", "2009-01-21", "here"],
+        svec!["32873", "1989-12-31", "11", "42461", "2016-04-01", "42"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_keep_serial_invalid_layout() {
+    let wrk = Workdir::new("excel_keep_serial_invalid_layout");
+
+    let xls_file = wrk.load_test_file("excel-xls.xls");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--sheet")
+        .arg("date test")
+        .args(["--dates-whitelist", "0,2"])
+        .args(["--keep-serial", "suffix"])
+        .arg(xls_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert_eq!(got, "Invalid --keep-serial: suffix\n");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_skip_hidden_rows_cols() {
+    let wrk = Workdir::new("excel_skip_hidden_rows_cols");
+
+    let xlsx_file = wrk.load_test_file("excel-hidden-rows-cols.xlsx");
+
+    // hidden rows & columns are exported by default
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "internal", "score"],
+        svec!["1", "alpha", "x", "10"],
+        svec!["2", "beta", "y", "20"],
+        svec!["3", "gamma", "z", "30"],
+        svec!["4", "delta", "w", "40"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--skip-hidden-rows")
+        .arg("--skip-hidden-cols")
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "score"],
+        svec!["1", "alpha", "10"],
+        svec!["3", "gamma", "30"],
+        svec!["4", "delta", "40"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--skip-hidden-cols").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got.len(), 5);
+    assert_eq!(got[0], svec!["id", "name", "score"]);
+}
+
+#[test]
+fn excel_coerce_numeric_strings() {
+    let wrk = Workdir::new("excel_coerce_numeric_strings");
+
+    let xlsx_file = wrk.load_test_file("excel-numeric-strings.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--coerce-numeric-strings").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "amount", "note"],
+        svec!["1", "1234", "x"],
+        svec!["2", "12.50", "  hello "],
+        svec!["3", "1,234", "NaN"],
+        svec!["4", "-7e3", "42"],
+    ];
+    assert_eq!(got, expected);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(
+        got_err,
+        "4 numeric text cells coerced\n4 3-column rows exported from \"data\" sheet\n"
+    );
+}
+
+#[test]
+fn excel_list_named_ranges() {
+    let wrk = Workdir::new("excel_list_named_ranges");
+
+    let xlsx_file = wrk.load_test_file("excel-named-ranges.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--list-named-ranges").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "sheet", "range"],
+        svec!["SalesData", "Sales Data", "B2:D4"],
+        svec!["Total", "Summary", "B3"],
+        svec!["TaxRate", "", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_range() {
+    let wrk = Workdir::new("excel_range");
+
+    let xlsx_file = wrk.load_test_file("excel-named-ranges.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--range", "@salesdata"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "q1", "q2"],
+        svec!["north", "10", "20"],
+        svec!["south", "30", "40"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "Sales Data"])
+        .args(["--range", "B2:C3"])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["region", "q1"], svec!["north", "10"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_range_invalid() {
+    let wrk = Workdir::new("excel_range_invalid");
+
+    let xlsx_file = wrk.load_test_file("excel-named-ranges.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--range", "@NoSuchName"]).arg(&xlsx_file);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--range", "@TaxRate"]).arg(&xlsx_file);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_range_open_ended() {
+    let wrk = Workdir::new("excel_range_open_ended");
+
+    let xlsx_file = wrk.load_test_file("excel-named-ranges.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "Sales Data"])
+        .args(["--range", "C3:"])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["10", "20"], svec!["30", "40"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_range_outside_sheet() {
+    let wrk = Workdir::new("excel_range_outside_sheet");
+
+    let xlsx_file = wrk.load_test_file("excel-named-ranges.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "Sales Data"])
+        .args(["--range", "F10:H20"])
+        .arg(&xlsx_file);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "Sales Data"])
+        .args(["--range", "C3:A1"])
+        .arg(&xlsx_file);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_duplicate_sheet_name() {
+    let wrk = Workdir::new("excel_duplicate_sheet_name");
+
+    let xlsx_file = wrk.load_test_file("excel-duplicate-sheets.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "data"]).arg(&xlsx_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.starts_with(r#"2 sheets are named "data" - sheets [0, 1]."#));
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "data"])
+        .args(["--on-duplicate-sheet-name", "first"])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["name", "value"], svec!["first", "1"]];
+    assert_eq!(got, expected);
+
+    // an exact name match takes priority over the case-insensitive ones
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "DATA"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["name", "value"], svec!["second", "2"]];
+    assert_eq!(got, expected);
+
+    // a duplicate sheet can still be exported by its index
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "1"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["name", "value"], svec!["second", "2"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_empty_token() {
+    let wrk = Workdir::new("excel_empty_token");
+
+    let xlsx_file = wrk.load_test_file("excel-empty-cells.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--empty-token", r"\N"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    // the empty string text cell of row 3 is still an empty field
+    let expected = vec![
+        svec!["id", "name", "note"],
+        svec!["1", r"\N", "x"],
+        svec!["2", "", "y"],
+        svec!["3", "Bob", r"\N"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "note"],
+        svec!["1", "", "x"],
+        svec!["2", "", "y"],
+        svec!["3", "Bob", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_concat() {
+    let wrk = Workdir::new("excel_concat");
+
+    let xlsx_file = wrk.load_test_file("excel-concat.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--concat", "first+last=full_name;area+number=phone"])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "id",
+            "first",
+            "last",
+            "area",
+            "number",
+            "full_name",
+            "phone"
+        ],
+        svec![
+            "1",
+            "Jane",
+            "Doe",
+            "212",
+            "555-0100",
+            "Jane Doe",
+            "212 555-0100"
+        ],
+        svec![
+            "2",
+            "Prince",
+            "",
+            "310",
+            "555-0199",
+            "Prince",
+            "310 555-0199"
+        ],
+    ];
+    assert_eq!(got, expected);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(got_err, "2 7-column rows exported from \"people\" sheet\n");
+}
+
+#[test]
+fn excel_concat_drop() {
+    let wrk = Workdir::new("excel_concat_drop");
+
+    let xlsx_file = wrk.load_test_file("excel-concat.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--concat", "area+number=phone"])
+        .args(["--concat-sep", "-"])
+        .arg("--concat-drop")
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "first", "last", "phone"],
+        svec!["1", "Jane", "Doe", "212-555-0100"],
+        svec!["2", "Prince", "", "310-555-0199"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_concat_invalid() {
+    let wrk = Workdir::new("excel_concat_invalid");
+
+    let xlsx_file = wrk.load_test_file("excel-concat.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--concat", "first+middle=full_name"])
+        .arg(&xlsx_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.starts_with(r#"--concat column "middle" not found in the header row."#));
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--concat", "first+last"]).arg(&xlsx_file);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_all_sheets() {
+    let wrk = Workdir::new("excel_all_sheets");
+
+    let xlsx_file = wrk.load_test_file("excel-duplicate-sheets.xlsx");
+    std::fs::create_dir(wrk.path("sheets")).unwrap();
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--all-sheets")
+        .args(["--output", "sheets"])
+        .arg(&xlsx_file);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(
+        got_err,
+        "1 2-column rows exported from \"Data\" sheet\nsheet name \"DATA\" was changed to \
+         \"DATA_2\" in the output path\n1 2-column rows exported from \"DATA\" sheet\n2 rows \
+         exported from 2 sheets\n"
+    );
+
+    let got = std::fs::read_to_string(wrk.path("sheets/Data.csv")).unwrap();
+    assert_eq!(got, "name,value\nfirst,1\n");
+    let got = std::fs::read_to_string(wrk.path("sheets/DATA_2.csv")).unwrap();
+    assert_eq!(got, "name,value\nsecond,2\n");
+}
+
+#[test]
+fn excel_all_sheets_jobs() {
+    let wrk = Workdir::new("excel_all_sheets_jobs");
+
+    let xlsx_file = wrk.load_test_file("excel-duplicate-sheets.xlsx");
+    std::fs::create_dir(wrk.path("sheets")).unwrap();
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--all-sheets")
+        .args(["--jobs", "2"])
+        .args(["--output", "sheets"])
+        .arg(&xlsx_file);
+
+    // the sheets are exported concurrently, so their summaries can be in any order
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert!(
+        got_err.starts_with("sheet name \"DATA\" was changed to \"DATA_2\" in the output path\n")
+    );
+    assert!(got_err.contains("1 2-column rows exported from \"Data\" sheet\n"));
+    assert!(got_err.contains("1 2-column rows exported from \"DATA\" sheet\n"));
+    assert!(got_err.ends_with("2 rows exported from 2 sheets\n"));
+
+    let got = std::fs::read_to_string(wrk.path("sheets/Data.csv")).unwrap();
+    assert_eq!(got, "name,value\nfirst,1\n");
+    let got = std::fs::read_to_string(wrk.path("sheets/DATA_2.csv")).unwrap();
+    assert_eq!(got, "name,value\nsecond,2\n");
+}
+
+#[test]
+fn excel_all_sheets_output_not_dir() {
+    let wrk = Workdir::new("excel_all_sheets_output_not_dir");
+
+    let xlsx_file = wrk.load_test_file("excel-duplicate-sheets.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--all-sheets").arg(&xlsx_file);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--all-sheets")
+        .args(["--output", "out.csv"])
+        .arg(&xlsx_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.starts_with("--output must be an existing directory with --all-sheets: out.csv"));
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_sheet_list() {
+    let wrk = Workdir::new("excel_sheet_list");
+
+    let xlsx_file = wrk.load_test_file("excel-multi-sheets.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "Jan,feb"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "amount"],
+        svec!["1", "10"],
+        svec!["2", "20"],
+        svec!["3", "30"],
+    ];
+    assert_eq!(got, expected);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(
+        got_err,
+        "2 2-column rows exported from \"Jan\" sheet\n1 2-column rows exported from \"Feb\" \
+         sheet\n3 rows exported from 2 sheets\n"
+    );
+}
+
+#[test]
+fn excel_sheet_list_with_sheet_name() {
+    let wrk = Workdir::new("excel_sheet_list_with_sheet_name");
+
+    let xlsx_file = wrk.load_test_file("excel-multi-sheets.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "-2,0"])
+        .arg("--with-sheet-name")
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["source_sheet", "id", "amount"],
+        svec!["Feb", "3", "30"],
+        svec!["Jan", "1", "10"],
+        svec!["Jan", "2", "20"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_sheet_list_mismatched_headers() {
+    let wrk = Workdir::new("excel_sheet_list_mismatched_headers");
+
+    let xlsx_file = wrk.load_test_file("excel-multi-sheets.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "Jan,Notes"]).arg(&xlsx_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains(r#"The "Notes" sheet has a different header row than the first sheet."#));
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "Jan,Notes"])
+        .arg("--flexible")
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "amount"],
+        svec!["1", "10"],
+        svec!["2", "20"],
+        svec!["draft"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_apply_autofilter() {
+    let wrk = Workdir::new("excel_apply_autofilter");
+
+    let xlsx_file = wrk.load_test_file("excel-autofilter.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--apply-autofilter").arg(&xlsx_file);
+
+    // the last row is below the AutoFilter range, so it is not filtered
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "amount", "rep"],
+        svec!["East", "100", "Ann"],
+        svec!["North", "299", "Dee"],
+        svec!["West", "5", "Zed"],
+    ];
+    assert_eq!(got, expected);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(
+        got_err,
+        "cannot interpret the color AutoFilter criteria of column 3 of the \"data\" sheet. \
+         Ignoring it.\n3 rows filtered out by the AutoFilter\n3 3-column rows exported from \
+         \"data\" sheet\n"
+    );
+}
+
+#[test]
+fn excel_apply_autofilter_wildcards_blanks() {
+    let wrk = Workdir::new("excel_apply_autofilter_wildcards_blanks");
+
+    let xlsx_file = wrk.load_test_file("excel-autofilter.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--apply-autofilter")
+        .args(["--sheet", "wild"])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "note"],
+        svec!["Ben", "a"],
+        svec!["BIN", "c"],
+        svec!["", "e"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_apply_autofilter_none() {
+    let wrk = Workdir::new("excel_apply_autofilter_none");
+
+    let xlsx_file = wrk.load_test_file("excel-autofilter.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--apply-autofilter")
+        .args(["--sheet", "plain"])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["x"], svec!["1"]];
+    assert_eq!(got, expected);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert!(
+        got_err.starts_with(r#"no AutoFilter found in the "plain" sheet. Exporting all the rows."#)
+    );
+}
+
+#[test]
+fn excel_jsonl() {
+    let wrk = Workdir::new("excel_jsonl");
+
+    let xlsx_file = wrk.load_test_file("excel-jsonl.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--jsonl")
+        .args(["--output", "out.jsonl"])
+        .arg(&xlsx_file);
+    wrk.assert_success(&mut cmd);
+
+    let got = std::fs::read_to_string(wrk.path("out.jsonl")).unwrap();
+    let expected = concat!(
+        r#"{"name":"apple","count":3,"price":1.5,"active":true,"created":"2023-01-01","note":"red"}"#,
+        "\n",
+        r#"{"name":"pear","count":null,"price":2.25,"active":false,"created":"2023-01-02","note":null}"#,
+        "\n"
+    );
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_transpose() {
+    let wrk = Workdir::new("excel_transpose");
+
+    let xlsx_file = wrk.load_test_file("excel-transpose.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--transpose")
+        .args(["--sheet", "props"])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "age", "city"],
+        svec!["Alice", "30", "Paris"],
+        svec!["Bob", "25", "Rome"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_transpose_range() {
+    let wrk = Workdir::new("excel_transpose_range");
+
+    let xlsx_file = wrk.load_test_file("excel-transpose.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--transpose")
+        .args(["--sheet", "titled"])
+        .args(["--range", "A2:C4"])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "age", "city"],
+        svec!["Alice", "30", "Paris"],
+        svec!["Bob", "25", "Rome"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_select() {
+    let wrk = Workdir::new("excel_select");
+
+    let xlsx_file = wrk.load_test_file("excel-jsonl.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--select", "note,name,4"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "active", "note"],
+        svec!["apple", "true", "red"],
+        svec!["pear", "false", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_select_unknown_column() {
+    let wrk = Workdir::new("excel_select_unknown_column");
+
+    let xlsx_file = wrk.load_test_file("excel-jsonl.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--select", "name,colour"]).arg(&xlsx_file);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_split_by() {
+    let wrk = Workdir::new("excel_split_by");
+
+    let xlsx_file = wrk.load_test_file("excel-split-by.xlsx");
+    std::fs::create_dir(wrk.path("depts")).unwrap();
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--split-by", "dept"])
+        .args(["--output", "depts"])
+        .arg(&xlsx_file);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert!(got_err.starts_with("3 files written by --split-by:\n"));
+    assert!(got_err.contains("Sales.csv: 2 rows\n"));
+    assert!(got_err.contains("HR.csv: 1 rows\n"));
+    assert!(got_err.contains("empty.csv: 1 rows\n"));
+
+    let got = std::fs::read_to_string(wrk.path("depts/Sales.csv")).unwrap();
+    assert_eq!(got, "name,dept\nann,Sales\ncy,Sales\n");
+    let got = std::fs::read_to_string(wrk.path("depts/HR.csv")).unwrap();
+    assert_eq!(got, "name,dept\nbob,HR\n");
+    let got = std::fs::read_to_string(wrk.path("depts/empty.csv")).unwrap();
+    assert_eq!(got, "name,dept\ndee,\n");
+}
+
+#[test]
+fn excel_split_by_open_files() {
+    let wrk = Workdir::new("excel_split_by_open_files");
+
+    let xlsx_file = wrk.load_test_file("excel-split-by.xlsx");
+    std::fs::create_dir(wrk.path("depts")).unwrap();
+
+    // the 3 values are written with a single open file, so Sales.csv is reopened for cy
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--split-by", "dept"])
+        .args(["--split-open-files", "1"])
+        .args(["--output", "depts"])
+        .arg(&xlsx_file);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert!(got_err.starts_with("3 files written by --split-by:\n"));
+
+    let got = std::fs::read_to_string(wrk.path("depts/Sales.csv")).unwrap();
+    assert_eq!(got, "name,dept\nann,Sales\ncy,Sales\n");
+    let got = std::fs::read_to_string(wrk.path("depts/HR.csv")).unwrap();
+    assert_eq!(got, "name,dept\nbob,HR\n");
+    let got = std::fs::read_to_string(wrk.path("depts/empty.csv")).unwrap();
+    assert_eq!(got, "name,dept\ndee,\n");
+}
+
+#[test]
+fn excel_split_by_long_values() {
+    let wrk = Workdir::new("excel_split_by_long_values");
+
+    // the first two codes are longer than a filename, and only differ after 250 bytes
+    let xlsx_file = wrk.load_test_file("excel-split-by-long.xlsx");
+    std::fs::create_dir(wrk.path("codes")).unwrap();
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--split-by", "code"])
+        .args(["--output", "codes"])
+        .arg(&xlsx_file);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert!(got_err.starts_with("3 files written by --split-by:\n"));
+
+    let stem = "x".repeat(240);
+    let got = std::fs::read_to_string(wrk.path(&format!("codes/{stem}.csv"))).unwrap();
+    assert_eq!(got, format!("name,code\nann,{}\n", "x".repeat(300)));
+    let got = std::fs::read_to_string(wrk.path(&format!("codes/{stem}_2.csv"))).unwrap();
+    assert_eq!(got, format!("name,code\nbob,{}y\n", "x".repeat(250)));
+    let got = std::fs::read_to_string(wrk.path("codes/short.csv")).unwrap();
+    assert_eq!(got, "name,code\ncy,short\n");
+}
+
+#[test]
+fn excel_split_by_unknown_column() {
+    let wrk = Workdir::new("excel_split_by_unknown_column");
+
+    let xlsx_file = wrk.load_test_file("excel-split-by.xlsx");
+    std::fs::create_dir(wrk.path("depts")).unwrap();
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--split-by", "team"])
+        .args(["--output", "depts"])
+        .arg(&xlsx_file);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_sheet_regex() {
+    let wrk = Workdir::new("excel_sheet_regex");
+
+    let xlsx_file = wrk.load_test_file("excel-quarters.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet-regex", "^2023-Q[1-4]$"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["source_sheet", "region", "sales"],
+        svec!["2023-Q1", "north", "10"],
+        svec!["2023-Q1", "south", "20"],
+        svec!["2023-Q2", "north", "15"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_sheet_regex_no_match() {
+    let wrk = Workdir::new("excel_sheet_regex_no_match");
+
+    let xlsx_file = wrk.load_test_file("excel-quarters.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet-regex", "^2024-"]).arg(&xlsx_file);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert!(got_err.starts_with("No sheet matches --sheet-regex ^2024-."));
+}
+
+#[test]
+fn excel_sheet_regex_with_sheet() {
+    let wrk = Workdir::new("excel_sheet_regex_with_sheet");
+
+    let xlsx_file = wrk.load_test_file("excel-quarters.xlsx");
+
+    // an explicit --sheet 0 is still a --sheet
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--sheet", "0"])
+        .args(["--sheet-regex", "^2023-Q[1-4]$"])
+        .arg(&xlsx_file);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(
+        got_err,
+        "--sheet-regex cannot be used with --sheet, --all-sheets or a named --range.\n"
+    );
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_delimiter() {
+    let wrk = Workdir::new("excel_delimiter");
+
+    let xlsx_file = wrk.load_test_file("excel-split-by.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--delimiter", r"\t"]).arg(&xlsx_file);
+
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(got, "name\tdept\nann\tSales\nbob\tHR\ncy\tSales\ndee\t");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--delimiter", "|"]).arg(&xlsx_file);
+
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(got, "name|dept\nann|Sales\nbob|HR\ncy|Sales\ndee|");
+}
+
+#[test]
+fn excel_auto_widen() {
+    let wrk = Workdir::new("excel_auto_widen");
+
+    let xlsx_file = wrk.load_test_file("excel-auto-widen.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--auto-widen").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "col_3", "col_4"],
+        svec!["1", "ann", "x", "7"],
+        svec!["2", "bob", "", ""],
+        svec!["3", "cy", "", "9"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_infer_dates() {
+    let wrk = Workdir::new("excel_infer_dates");
+
+    let xlsx_file = wrk.load_test_file("excel-infer-dates.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--infer-dates").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "received", "qty", "mixed"],
+        svec!["ann", "2023-01-01", "3", "45000"],
+        svec!["bob", "2023-02-01 12:00:00", "12", "n/a"],
+        svec!["cy", "2023-03-01", "7", "2.5"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_dates_whitelist_regex() {
+    let wrk = Workdir::new("excel_dates_whitelist_regex");
+
+    let xlsx_file = wrk.load_test_file("excel-whitelist-regex.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--dates-whitelist", "_dt$,^received_"])
+        .arg("--dates-whitelist-regex")
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["update_flag", "ship_dt", "received_on"],
+        svec!["44927", "2023-01-01", "2023-02-01"],
+    ];
+    assert_eq!(got, expected);
+
+    // without --dates-whitelist-regex, "date" is a substring of update_flag
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--dates-whitelist", "date"]).arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got[1], svec!["2023-01-01", "44927", "44958"]);
+}
+
+#[test]
+fn excel_dates_whitelist_mixed() {
+    let wrk = Workdir::new("excel_dates_whitelist_mixed");
+
+    let xlsx_file = wrk.load_test_file("excel-whitelist-regex.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--dates-whitelist", "1,received"])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["update_flag", "ship_dt", "received_on"],
+        svec!["44927", "2023-01-01", "2023-02-01"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_dates_whitelist_unmatched() {
+    let wrk = Workdir::new("excel_dates_whitelist_unmatched");
+
+    let xlsx_file = wrk.load_test_file("excel-whitelist-regex.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--dates-whitelist", "dte,ship,7"])
+        .arg(&xlsx_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert_eq!(
+        got,
+        "WARNING: --dates-whitelist index 7 is beyond the last column of the \"Sheet1\" sheet, \
+         which has 3 columns\nWARNING: date-whitelist pattern \"dte\" matched no columns\n1 \
+         3-column rows exported from \"Sheet1\" sheet\n"
+    );
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--dates-whitelist", "dte,ship,7"])
+        .arg("--quiet")
+        .arg(&xlsx_file);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert_eq!(got, "No error");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--dates-whitelist", "dte,ship,7"])
+        .arg("--strict-whitelist")
+        .arg(&xlsx_file);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_skip_empty() {
+    let wrk = Workdir::new("excel_skip_empty");
+
+    let xlsx_file = wrk.load_test_file("excel-skip-empty.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--skip-empty").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "note"],
+        svec!["ann", "x"],
+        svec!["", "late"],
+        svec!["bob", "y"],
+    ];
+    assert_eq!(got, expected);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert_eq!(got, "3 2-column rows exported from \"Sheet1\" sheet\n");
+}
+
+#[test]
+fn excel_empty_token_trim() {
+    let wrk = Workdir::new("excel_empty_token_trim");
+
+    let xlsx_file = wrk.load_test_file("excel-skip-empty.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--empty-token", "NA"])
+        .arg("--trim")
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "note"],
+        svec!["ann", "x"],
+        svec!["NA", "NA"],
+        svec!["NA", "NA"],
+        svec!["NA", "late"],
+        svec!["bob", "y"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_keep_text_format() {
+    let wrk = Workdir::new("excel_keep_text_format");
+
+    let xlsx_file = wrk.load_test_file("excel-keep-text-format.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--keep-text-format").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["zip", "account", "amount", "plain"],
+        svec!["07030", "000042", "3.5", "7030"],
+        svec!["10001", "123456", "12", "42"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_dedup_headers() {
+    let wrk = Workdir::new("excel_dedup_headers");
+
+    let xlsx_file = wrk.load_test_file("excel-dup-headers.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--dedup-headers").arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "Amount", "Amount_3", "Amount_2", "Amount_4"],
+        svec!["1", "10", "20", "30", "40"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn excel_no_headers() {
+    let wrk = Workdir::new("excel_no_headers");
+
+    let xlsx_file = wrk.load_test_file("excel-no-headers.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--no-headers")
+        .args(["--dates-whitelist", "1"])
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["col_1", "col_2"],
+        svec!["ann", "2023-01-01"],
+        svec!["bob", "2023-02-01"],
+    ];
+    assert_eq!(got, expected);
+
+    // the name patterns of the whitelist don't match the synthesized names
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--no-headers")
+        .args(["--dates-whitelist", "col"])
+        .arg("--jsonl")
+        .arg(&xlsx_file);
+
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(
+        got,
+        "{\"col_1\":\"ann\",\"col_2\":44927}\n{\"col_1\":\"bob\",\"col_2\":44958}"
+    );
+}
+
+#[test]
+fn excel_max_rows() {
+    let wrk = Workdir::new("excel_max_rows");
+
+    let xlsx_file = wrk.load_test_file("excel-skip-empty.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.args(["--max-rows", "2"])
+        .arg("--skip-empty")
+        .arg(&xlsx_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["name", "note"], svec!["ann", "x"], svec!["", "late"]];
+    assert_eq!(got, expected);
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert_eq!(got, "2 2-column rows exported from \"Sheet1\" sheet\n");
+}
+
+#[test]
+fn excel_append() {
+    let wrk = Workdir::new("excel_append");
+
+    let xlsx_file = wrk.load_test_file("excel-no-headers.xlsx");
+
+    for _ in 0..2 {
+        let mut cmd = wrk.command("excel");
+        cmd.arg("--append")
+            .args(["--output", "master.csv"])
+            .arg(&xlsx_file);
+        wrk.assert_success(&mut cmd);
+    }
+
+    let got = std::fs::read_to_string(wrk.path("master.csv")).unwrap();
+    assert_eq!(got, "ann,44927\nbob,44958\nbob,44958\n");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--append").arg(&xlsx_file);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn excel_bom() {
+    let wrk = Workdir::new("excel_bom");
+
+    let xlsx_file = wrk.load_test_file("excel-no-headers.xlsx");
+
+    for _ in 0..2 {
+        let mut cmd = wrk.command("excel");
+        cmd.arg("--bom")
+            .arg("--append")
+            .args(["--output", "bom.csv"])
+            .arg(&xlsx_file);
+        wrk.assert_success(&mut cmd);
+    }
+
+    // the BOM is only written at the start of the file
+    let got = std::fs::read(wrk.path("bom.csv")).unwrap();
+    assert_eq!(got, b"\xEF\xBB\xBFann,44927\nbob,44958\nbob,44958\n");
+}
+
+#[test]
+fn excel_quote_all() {
+    let wrk = Workdir::new("excel_quote_all");
+
+    let xlsx_file = wrk.load_test_file("excel-no-headers.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("--quote-all")
+        .args(["--delimiter", ";"])
+        .arg("--bom")
+        .args(["--output", "quoted.csv"])
+        .arg(&xlsx_file);
+    wrk.assert_success(&mut cmd);
+
+    let got = std::fs::read(wrk.path("quoted.csv")).unwrap();
+    assert_eq!(got, b"\xEF\xBB\xBF\"ann\";\"44927\"\n\"bob\";\"44958\"\n");
+}
+
+#[test]
+fn excel_stdin() {
+    let wrk = Workdir::new("excel_stdin");
+
+    let xlsx_file = wrk.load_test_file("excel-no-headers.xlsx");
+
+    let mut cmd = wrk.command("excel");
+    cmd.arg("-").stdin(std::fs::File::open(&xlsx_file).unwrap());
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["ann", "44927"], svec!["bob", "44958"]];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("excel");
+    cmd.stdin(std::process::Stdio::null());
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert_eq!(got, "No workbook was read from stdin.\n");
+    wrk.assert_err(&mut cmd);
+}
@@ -262,3 +262,65 @@ fn assert_eq_ftables(got: &FTables, expected: &FTables) -> bool {
     }
     true
 }
+
+#[test]
+fn frequency_combine() {
+    let wrk = Workdir::new("frequency_combine");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["region", "product", "amount"],
+            svec!["east", "widget", "1"],
+            svec!["west", "widget", "2"],
+            svec!["east", "widget", "3"],
+            svec!["east", "gadget", "4"],
+            svec!["west", "", "5"],
+            svec!["east", "widget", "6"],
+        ],
+    );
+    let mut cmd = wrk.command("frequency");
+    cmd.args(["--select", "region,product"])
+        .arg("--combine")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got[0], svec!["region", "product", "count"]);
+    assert_eq!(got[1], svec!["east", "widget", "3"]);
+    let mut rest: Vec<Vec<String>> = got.into_iter().skip(2).collect();
+    rest.sort();
+    let expected = vec![
+        svec!["east", "gadget", "1"],
+        svec!["west", "(NULL)", "1"],
+        svec!["west", "widget", "1"],
+    ];
+    assert_eq!(rest, expected);
+}
+
+#[test]
+fn frequency_combine_limit_no_nulls() {
+    let wrk = Workdir::new("frequency_combine_limit_no_nulls");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["region", "product"],
+            svec!["east", "widget"],
+            svec!["west", ""],
+            svec!["west", ""],
+            svec!["west", ""],
+            svec!["east", "widget"],
+            svec!["east", "gadget"],
+        ],
+    );
+    let mut cmd = wrk.command("frequency");
+    cmd.arg("--combine")
+        .arg("--no-nulls")
+        .args(["--limit", "1"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "product", "count"],
+        svec!["east", "widget", "2"],
+    ];
+    assert_eq!(got, expected);
+}
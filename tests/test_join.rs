@@ -168,6 +168,225 @@ join_test!(join_left_anti, |wrk: Workdir,
     assert_eq!(got, expected);
 });
 
+join_test!(
+    join_inner_index_left,
+    |wrk: Workdir, mut cmd: process::Command, headers: bool| {
+        cmd.args(["--index-side", "left"]);
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = make_rows(
+            headers,
+            false,
+            vec![
+                svec!["Boston", "MA", "Boston", "Logan Airport"],
+                svec!["Boston", "MA", "Boston", "Boston Garden"],
+                svec!["Buffalo", "NY", "Buffalo", "Ralph Wilson Stadium"],
+            ],
+        );
+        assert_eq!(got, expected);
+    }
+);
+
+join_test!(
+    join_outer_left_index_left,
+    |wrk: Workdir, mut cmd: process::Command, headers: bool| {
+        cmd.arg("--left").args(["--index-side", "left"]);
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = make_rows(
+            headers,
+            false,
+            vec![
+                svec!["Boston", "MA", "Boston", "Logan Airport"],
+                svec!["Boston", "MA", "Boston", "Boston Garden"],
+                svec!["Buffalo", "NY", "Buffalo", "Ralph Wilson Stadium"],
+                svec!["New York", "NY", "", ""],
+                svec!["San Francisco", "CA", "", ""],
+            ],
+        );
+        assert_eq!(got, expected);
+    }
+);
+
+join_test!(
+    join_outer_right_index_right,
+    |wrk: Workdir, mut cmd: process::Command, headers: bool| {
+        cmd.arg("--right").args(["--index-side", "right"]);
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = make_rows(
+            headers,
+            false,
+            vec![
+                svec!["Boston", "MA", "Boston", "Logan Airport"],
+                svec!["Boston", "MA", "Boston", "Boston Garden"],
+                svec!["Buffalo", "NY", "Buffalo", "Ralph Wilson Stadium"],
+                svec!["", "", "Orlando", "Disney World"],
+            ],
+        );
+        assert_eq!(got, expected);
+    }
+);
+
+join_test!(
+    join_outer_full_index_left,
+    |wrk: Workdir, mut cmd: process::Command, headers: bool| {
+        cmd.arg("--full").args(["--index-side", "left"]);
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = make_rows(
+            headers,
+            false,
+            vec![
+                svec!["Boston", "MA", "Boston", "Logan Airport"],
+                svec!["Boston", "MA", "Boston", "Boston Garden"],
+                svec!["Buffalo", "NY", "Buffalo", "Ralph Wilson Stadium"],
+                svec!["", "", "Orlando", "Disney World"],
+                svec!["New York", "NY", "", ""],
+                svec!["San Francisco", "CA", "", ""],
+            ],
+        );
+        assert_eq!(got, expected);
+    }
+);
+
+join_test!(
+    join_left_semi_index_left,
+    |wrk: Workdir, mut cmd: process::Command, _headers: bool| {
+        cmd.arg("--left-semi").args(["--index-side", "left"]);
+        wrk.assert_err(&mut cmd);
+    }
+);
+
+#[test]
+fn join_index_side_auto() {
+    let wrk = Workdir::new("join_index_side_auto");
+    wrk.create(
+        "small.csv",
+        vec![svec!["id", "name"], svec!["2", "b"], svec!["1", "a"]],
+    );
+    wrk.create(
+        "big.csv",
+        vec![
+            svec!["id", "value"],
+            svec!["1", "one"],
+            svec!["2", "two"],
+            svec!["3", "three"],
+        ],
+    );
+
+    // small.csv is smaller, so it is indexed, and big.csv is streamed
+    let mut cmd = wrk.command("join");
+    cmd.args(["--index-side", "auto"])
+        .args(["id", "small.csv", "id", "big.csv"]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "id", "value"],
+        svec!["1", "a", "1", "one"],
+        svec!["2", "b", "2", "two"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_index_side_invalid() {
+    let wrk = Workdir::new("join_index_side_invalid");
+    wrk.create("a.csv", vec![svec!["id"], svec!["1"]]);
+    wrk.create("b.csv", vec![svec!["id"], svec!["1"]]);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["--index-side", "middle"])
+        .args(["id", "a.csv", "id", "b.csv"]);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn join_left_match_flag() {
+    let wrk = setup("join_left_match_flag", true);
+    let mut cmd = wrk.command("join");
+    cmd.arg("--left").args(["--match-flag", "matched"]).args([
+        "city",
+        "cities.csv",
+        "city",
+        "places.csv",
+    ]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["city", "state", "city", "place", "matched"],
+        svec!["Boston", "MA", "Boston", "Logan Airport", "true"],
+        svec!["Boston", "MA", "Boston", "Boston Garden", "true"],
+        svec!["New York", "NY", "", "", "false"],
+        svec!["San Francisco", "CA", "", "", "false"],
+        svec!["Buffalo", "NY", "Buffalo", "Ralph Wilson Stadium", "true"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_left_fill() {
+    let wrk = setup("join_left_fill", true);
+    let mut cmd = wrk.command("join");
+    cmd.arg("--left")
+        .args(["--fill", "NULL"])
+        .args(["city", "cities.csv", "city", "places.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["city", "state", "city", "place"],
+        svec!["Boston", "MA", "Boston", "Logan Airport"],
+        svec!["Boston", "MA", "Boston", "Boston Garden"],
+        svec!["New York", "NY", "NULL", "NULL"],
+        svec!["San Francisco", "CA", "NULL", "NULL"],
+        svec!["Buffalo", "NY", "Buffalo", "Ralph Wilson Stadium"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_right_fill() {
+    let wrk = setup("join_right_fill", true);
+    let mut cmd = wrk.command("join");
+    cmd.arg("--right")
+        .args(["--fill", "0"])
+        .args(["city", "cities.csv", "city", "places.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["city", "state", "city", "place"],
+        svec!["Boston", "MA", "Boston", "Logan Airport"],
+        svec!["Boston", "MA", "Boston", "Boston Garden"],
+        svec!["Buffalo", "NY", "Buffalo", "Ralph Wilson Stadium"],
+        svec!["0", "0", "Orlando", "Disney World"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_left_semi_match_flag() {
+    let wrk = setup("join_left_semi_match_flag", true);
+    let mut cmd = wrk.command("join");
+    cmd.arg("--left-semi")
+        .args(["--match-flag", "has_place"])
+        .args(["city", "cities.csv", "city", "places.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["city", "state", "has_place"],
+        svec!["Boston", "MA", "true"],
+        svec!["New York", "NY", "false"],
+        svec!["San Francisco", "CA", "false"],
+        svec!["Buffalo", "NY", "true"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_inner_match_flag_error() {
+    let wrk = setup("join_inner_match_flag_error", true);
+    let mut cmd = wrk.command("join");
+    cmd.args(["--match-flag", "matched"])
+        .args(["city", "cities.csv", "city", "places.csv"]);
+
+    wrk.assert_err(&mut cmd);
+}
+
 #[test]
 fn join_inner_issue11() {
     let a = vec![svec!["1", "2"], svec!["3", "4"], svec!["5", "6"]];
@@ -461,3 +461,87 @@ fn search_flag_invert_match_count() {
 
     wrk.assert_success(&mut cmd);
 }
+
+fn log_data() -> Vec<Vec<String>> {
+    vec![
+        svec!["time", "event"],
+        svec!["1", "start"],
+        svec!["2", "load"],
+        svec!["3", "ERROR disk"],
+        svec!["4", "ERROR retry"],
+        svec!["5", "load"],
+        svec!["6", "load"],
+        svec!["7", "save"],
+        svec!["8", "ERROR network"],
+        svec!["9", "stop"],
+    ]
+}
+
+#[test]
+fn search_before_after() {
+    let wrk = Workdir::new("search_before_after");
+    wrk.create("data.csv", log_data());
+    let mut cmd = wrk.command("search");
+    cmd.args(["--before", "2"])
+        .args(["--after", "1"])
+        .arg("ERROR")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["time", "event"],
+        svec!["1", "start"],
+        svec!["2", "load"],
+        svec!["3", "ERROR disk"],
+        svec!["4", "ERROR retry"],
+        svec!["5", "load"],
+        svec!["6", "load"],
+        svec!["7", "save"],
+        svec!["8", "ERROR network"],
+        svec!["9", "stop"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn search_context_group_separator() {
+    let wrk = Workdir::new("search_context_group_separator");
+    wrk.create("data.csv", log_data());
+    let mut cmd = wrk.command("search");
+    cmd.args(["--context", "1"])
+        .args(["--group-separator", "--"])
+        .arg("--count")
+        .arg("ERROR")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["time", "event"],
+        svec!["2", "load"],
+        svec!["3", "ERROR disk"],
+        svec!["4", "ERROR retry"],
+        svec!["5", "load"],
+        svec!["--", ""],
+        svec!["7", "save"],
+        svec!["8", "ERROR network"],
+        svec!["9", "stop"],
+    ];
+    assert_eq!(got, expected);
+
+    // only the matching rows are counted
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(got_err, "3\n");
+}
+
+#[test]
+fn search_context_flag() {
+    let wrk = Workdir::new("search_context_flag");
+    wrk.create("data.csv", log_data());
+    let mut cmd = wrk.command("search");
+    cmd.args(["--context", "1"])
+        .args(["--flag", "matched"])
+        .arg("ERROR")
+        .arg("data.csv");
+
+    wrk.assert_err(&mut cmd);
+}
@@ -222,6 +222,110 @@ fn sort_uniq() {
     assert_eq!(got, expected);
 }
 
+#[test]
+fn prop_sort_memory_limit() {
+    fn p(rows: CsvData) -> bool {
+        let wrk = Workdir::new("prop_sort_memory_limit");
+        wrk.create("in.csv", rows.clone());
+
+        let mut cmd = wrk.command("sort");
+        cmd.arg("in.csv")
+            .arg("--no-headers")
+            .args(["--memory-limit", "64"]);
+
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let mut expected = rows.to_vecs();
+        expected.sort_by(|r1, r2| iter_cmp(r1.iter(), r2.iter()));
+        rassert_eq!(got, expected)
+    }
+    qcheck(p as fn(CsvData) -> bool);
+}
+
+#[test]
+fn sort_uniq_memory_limit() {
+    let wrk = Workdir::new("sort_uniq_memory_limit");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["number", "letter"],
+            svec!["2", "c"],
+            svec!["1", "a"],
+            svec!["3", "f"],
+            svec!["2", "b"],
+            svec!["1", "d"],
+            svec!["2", "e"],
+        ],
+    );
+    std::fs::create_dir(wrk.path("spill")).unwrap();
+
+    // each row is 18 bytes, so the rows are spilled two at a time
+    let mut cmd = wrk.command("sort");
+    cmd.arg("-u")
+        .args(["-s", "number"])
+        .arg("-N")
+        .args(["--memory-limit", "20"])
+        .args(["--tmp-dir", "spill"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["number", "letter"],
+        svec!["1", "a"],
+        svec!["2", "c"],
+        svec!["3", "f"],
+    ];
+    assert_eq!(got, expected);
+    // the spill files are gone once the sort is done
+    assert_eq!(std::fs::read_dir(wrk.path("spill")).unwrap().count(), 0);
+}
+
+#[test]
+fn sort_reverse_memory_limit() {
+    let wrk = Workdir::new("sort_reverse_memory_limit");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["R", "S"],
+            svec!["1", "b"],
+            svec!["2", "a"],
+            svec!["3", "d"],
+            svec!["4", "c"],
+        ],
+    );
+
+    let mut cmd = wrk.command("sort");
+    cmd.arg("--reverse")
+        .args(["-s", "S"])
+        .args(["--memory-limit", "1kb"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["R", "S"],
+        svec!["3", "d"],
+        svec!["4", "c"],
+        svec!["1", "b"],
+        svec!["2", "a"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn sort_memory_limit_invalid() {
+    let wrk = Workdir::new("sort_memory_limit_invalid");
+    wrk.create("in.csv", vec![svec!["R"], svec!["1"]]);
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--memory-limit", "lots"]).arg("in.csv");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--memory-limit", "1GB"])
+        .arg("--random")
+        .arg("in.csv");
+    wrk.assert_err(&mut cmd);
+}
+
 #[test]
 fn sort_random() {
     let wrk = Workdir::new("sort_random");
@@ -854,3 +854,217 @@ mod stats_header_fields {
     stats_test_headers!(stats_header_field_name, "field", &["a"], "header");
     stats_test_no_headers!(stats_header_no_field_name, "field", &["a"], "0");
 }
+
+#[test]
+fn stats_group_by() {
+    let wrk = Workdir::new("stats_group_by");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["team", "score"],
+            svec!["b", "10"],
+            svec!["a", "1"],
+            svec!["b", ""],
+            svec!["a", "3"],
+            svec!["c", "5"],
+        ],
+    );
+
+    let mut cmd = wrk.command("stats");
+    cmd.args(["--select", "score"])
+        .args(["--group-by", "team"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let got_cols: Vec<Vec<String>> = got
+        .iter()
+        .map(|row| row[..6].iter().chain(row[12..14].iter()).cloned().collect())
+        .collect();
+    let expected = vec![
+        svec![
+            "team",
+            "field",
+            "type",
+            "sum",
+            "min",
+            "max",
+            "nullcount",
+            "sparsity"
+        ],
+        svec!["b", "score", "Integer", "10", "10", "10", "1", "0.5"],
+        svec!["a", "score", "Integer", "4", "1", "3", "0", "0"],
+        svec!["c", "score", "Integer", "5", "5", "5", "0", "0"],
+    ];
+    assert_eq!(got_cols, expected);
+}
+
+#[test]
+fn stats_group_by_online() {
+    let wrk = Workdir::new("stats_group_by_online");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["team", "score"],
+            svec!["a", "1"],
+            svec!["a", "3"],
+            svec!["c", "5"],
+            svec!["b", "10"],
+        ],
+    );
+
+    let mut cmd = wrk.command("stats");
+    cmd.args(["--select", "score"])
+        .args(["--group-by", "team"])
+        .arg("--online")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let got_cols: Vec<Vec<String>> = got.iter().map(|row| row[..6].to_vec()).collect();
+    let expected = vec![
+        svec!["team", "field", "type", "sum", "min", "max"],
+        svec!["a", "score", "Integer", "4", "1", "3"],
+        svec!["c", "score", "Integer", "5", "5", "5"],
+        svec!["b", "score", "Integer", "10", "10", "10"],
+    ];
+    assert_eq!(got_cols, expected);
+}
+
+#[test]
+fn stats_group_by_online_not_grouped() {
+    let wrk = Workdir::new("stats_group_by_online_not_grouped");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["team", "score"],
+            svec!["b", "10"],
+            svec!["a", "1"],
+            svec!["b", "3"],
+        ],
+    );
+
+    let mut cmd = wrk.command("stats");
+    cmd.args(["--group-by", "team"])
+        .arg("--online")
+        .arg("in.csv");
+
+    let got: String = wrk.output_stderr(&mut cmd);
+    assert!(got.contains(
+        "Aborting! Input not grouped by the --group-by columns! \"b\" shows up again after \"a\""
+    ));
+}
+
+#[test]
+fn stats_entropy() {
+    let wrk = Workdir::new("stats_entropy");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["color", "constant"],
+            svec!["red", "a"],
+            svec!["red", "a"],
+            svec!["blue", "a"],
+            svec!["green", "a"],
+        ],
+    );
+
+    let mut cmd = wrk.command("stats");
+    cmd.arg("--entropy").arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let got_cols: Vec<Vec<String>> = got
+        .iter()
+        .map(|row| {
+            let len = row.len();
+            vec![row[0].clone(), row[len - 2].clone(), row[len - 1].clone()]
+        })
+        .collect();
+    let expected = vec![
+        svec!["field", "entropy", "normalized_entropy"],
+        svec!["color", "1.5", "0.9464"],
+        svec!["constant", "0", "0"],
+    ];
+    assert_eq!(got_cols, expected);
+}
+
+#[test]
+fn stats_entropy_max_cardinality() {
+    let wrk = Workdir::new("stats_entropy_max_cardinality");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["color", "constant"],
+            svec!["red", "a"],
+            svec!["red", "a"],
+            svec!["blue", "a"],
+            svec!["green", "a"],
+        ],
+    );
+
+    let mut cmd = wrk.command("stats");
+    cmd.arg("--entropy")
+        .args(["--entropy-max-cardinality", "2"])
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let got_cols: Vec<Vec<String>> = got
+        .iter()
+        .map(|row| {
+            let len = row.len();
+            vec![row[0].clone(), row[len - 2].clone(), row[len - 1].clone()]
+        })
+        .collect();
+    let expected = vec![
+        svec!["field", "entropy", "normalized_entropy"],
+        svec!["color", "", ""],
+        svec!["constant", "0", "0"],
+    ];
+    assert_eq!(got_cols, expected);
+}
+
+fn stats_variance_algorithm_cols(
+    wrk: &Workdir,
+    test_file: &str,
+    algorithm: &str,
+) -> Vec<Vec<String>> {
+    let mut cmd = wrk.command("stats");
+    cmd.args(["--variance-algorithm", algorithm]).arg(test_file);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let stddev = got[0].iter().position(|h| h == "stddev").unwrap();
+    let variance = got[0].iter().position(|h| h == "variance").unwrap();
+    got.iter()
+        .map(|row| vec![row[0].clone(), row[stddev].clone(), row[variance].clone()])
+        .collect()
+}
+
+#[test]
+fn stats_variance_algorithm() {
+    let wrk = Workdir::new("stats_variance_algorithm");
+    let test_file = wrk.load_test_file("stats-ill-conditioned.csv");
+
+    let got = stats_variance_algorithm_cols(&wrk, &test_file, "welford");
+    let expected = vec![
+        svec!["field", "stddev", "variance"],
+        svec!["measurement", "4.7434", "22.5"],
+    ];
+    assert_eq!(got, expected);
+
+    // the sum of squares cancels out catastrophically with such large values
+    let got = stats_variance_algorithm_cols(&wrk, &test_file, "naive");
+    let expected = vec![
+        svec!["field", "stddev", "variance"],
+        svec!["measurement", "0", "-128"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn stats_variance_algorithm_invalid() {
+    let wrk = Workdir::new("stats_variance_algorithm_invalid");
+    let test_file = wrk.load_test_file("stats-ill-conditioned.csv");
+
+    let mut cmd = wrk.command("stats");
+    cmd.args(["--variance-algorithm", "twopass"]).arg(test_file);
+
+    wrk.assert_err(&mut cmd);
+}